@@ -0,0 +1,252 @@
+// auto_actions.rs — automazioni opzionali di gameflow e champ select
+//
+// Guidate dagli eventi LCU (lcu_events.rs), tutte disattivate di default:
+//   • ready check  → accettato appena il gameflow passa a "ReadyCheck";
+//   • turno di ban → si preseleziona (hover, senza confermare) il primo campione
+//     della lista del ruolo non ancora bannato né scelto/in hover dai compagni;
+//   • turno di pick → hover del primo campione preferito per il ruolo ancora
+//     disponibile (non bannato, non preso, posseduto).
+// Ban e pick non vengono mai confermati: l'utente può cambiarli fino al lock.
+// Ogni azione LCU si tenta una sola volta (per id azione) e finisce nel log, che il
+// frontend riceve con l'evento `lcu-auto-action`. Impostazioni in automation.json;
+// le liste per ruolo usano le lane LCU ("TOP", ..., "UTILITY") più "DEFAULT" per le
+// code senza ruoli o i ruoli senza lista.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+
+use crate::lcu::LcuClient;
+use crate::static_data::{static_data, StaticData};
+
+/// Evento Tauri con ogni azione eseguita (payload: `AutoAction`).
+pub const ACTION_EVENT: &str = "lcu-auto-action";
+
+/// Voci di log conservate.
+const LOG_SIZE: usize = 50;
+/// Lista usata quando il ruolo non ne ha una.
+const DEFAULT_ROLE: &str = "DEFAULT";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationSettings {
+    #[serde(default)]
+    pub auto_accept: bool,
+    #[serde(default)]
+    pub auto_ban: bool,
+    #[serde(default)]
+    pub auto_hover: bool,
+    /// Ruolo → ban in ordine di priorità (nomi o id DDragon).
+    #[serde(default)]
+    pub bans: HashMap<String, Vec<String>>,
+    /// Ruolo → campioni preferiti in ordine di priorità.
+    #[serde(default)]
+    pub picks: HashMap<String, Vec<String>>,
+}
+
+impl AutomationSettings {
+    fn list<'a>(map: &'a HashMap<String, Vec<String>>, position: &str) -> &'a [String] {
+        map.get(position).filter(|l| !l.is_empty())
+            .or_else(|| map.get(DEFAULT_ROLE))
+            .map(|l| l.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoAction {
+    /// "accept" | "ban" | "hover"
+    pub kind: String,
+    pub champion_name: Option<String>,
+    pub position: String,
+    pub ok: bool,
+    pub message: String,
+    pub timestamp_ms: i64,
+}
+
+static SETTINGS_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// Azioni LCU già gestite nella champ select corrente.
+static HANDLED: Mutex<Option<HashSet<i64>>> = Mutex::new(None);
+static LOG: Mutex<VecDeque<AutoAction>> = Mutex::new(VecDeque::new());
+
+pub fn init(app_data_dir: &Path) {
+    let _ = SETTINGS_FILE.set(app_data_dir.join("automation.json"));
+}
+
+pub fn settings() -> AutomationSettings {
+    SETTINGS_FILE.get()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn record(app: &AppHandle, kind: &str, champion_name: Option<String>, position: &str, result: Result<String, String>) {
+    let (ok, message) = match result { Ok(m) => (true, m), Err(m) => (false, m) };
+    eprintln!("[AutoActions] {} {:?} {}: {}", kind, champion_name, position, message);
+    let action = AutoAction {
+        kind: kind.to_string(),
+        champion_name,
+        position: position.to_string(),
+        ok,
+        message,
+        timestamp_ms: chrono::Utc::now().timestamp_millis(),
+    };
+    {
+        let mut log = LOG.lock().unwrap();
+        if log.len() == LOG_SIZE { log.pop_front(); }
+        log.push_back(action.clone());
+    }
+    let _ = app.emit(ACTION_EVENT, action);
+}
+
+fn reset() {
+    HANDLED.lock().unwrap().take();
+}
+
+/// `true` la prima volta che si vede l'azione in questa champ select.
+fn claim(action_id: i64) -> bool {
+    HANDLED.lock().unwrap().get_or_insert_with(HashSet::new).insert(action_id)
+}
+
+// ── Ready check ──────────────────────────────────────────────────────────────
+
+pub fn on_gameflow_phase(app: &AppHandle, lcu: &LcuClient, phase: &str) {
+    if phase != "ChampSelect" { reset(); }
+    if phase != "ReadyCheck" || !settings().auto_accept { return; }
+
+    let app = app.clone();
+    let lcu = lcu.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match lcu.post_status("/lol-matchmaking/v1/ready-check/accept", &json!({})).await {
+            Ok(s) if (200..300).contains(&s) => Ok("ready check accettato".to_string()),
+            Ok(s) => Err(format!("accept rifiutato (HTTP {})", s)),
+            Err(e) => Err(e),
+        };
+        record(&app, "accept", None, "", result);
+    });
+}
+
+// ── Champ select ─────────────────────────────────────────────────────────────
+
+/// Id campione da una lista LCU (`pickable-champion-ids`, ...).
+async fn champion_ids(lcu: &LcuClient, path: &str) -> Option<HashSet<u32>> {
+    let list = lcu.get(path).await?;
+    Some(list.as_array()?.iter().filter_map(|v| v.as_u64()).map(|id| id as u32).collect())
+}
+
+/// Campioni non disponibili: ban completati, pick delle due squadre e hover dei compagni.
+fn unavailable(session: &Value) -> HashSet<u32> {
+    let mut taken = HashSet::new();
+    for phase in session["actions"].as_array().into_iter().flatten().filter_map(Value::as_array) {
+        for a in phase.iter().filter(|a| a["completed"] == true) {
+            if let Some(id) = a["championId"].as_u64() { taken.insert(id as u32); }
+        }
+    }
+    for team in ["myTeam", "theirTeam"] {
+        for p in session[team].as_array().into_iter().flatten() {
+            for key in ["championId", "championPickIntent"] {
+                if let Some(id) = p[key].as_u64() { taken.insert(id as u32); }
+            }
+        }
+    }
+    taken.remove(&0);
+    taken
+}
+
+/// Primo campione della lista disponibile (e, se nota, ammesso dal client).
+fn first_available(list: &[String], taken: &HashSet<u32>, allowed: Option<&HashSet<u32>>, data: &StaticData) -> Option<(u32, String)> {
+    list.iter()
+        .filter_map(|name| data.champions.find(name))
+        .find(|c| !taken.contains(&c.key) && allowed.is_none_or(|a| a.contains(&c.key)))
+        .map(|c| (c.key, c.name.clone()))
+}
+
+pub fn on_session(app: &AppHandle, lcu: &LcuClient, session: &Value) {
+    let settings = settings();
+    if !settings.auto_ban && !settings.auto_hover { return; }
+    let Some(my_cell) = session["localPlayerCellId"].as_i64() else { return };
+
+    // Azione del giocatore locale in corso, non ancora gestita
+    let action = session["actions"].as_array().into_iter().flatten()
+        .filter_map(Value::as_array).flatten()
+        .find(|a| a["actorCellId"].as_i64() == Some(my_cell)
+            && a["isInProgress"] == true && a["completed"] != true
+            && ((a["type"] == "ban" && settings.auto_ban) || (a["type"] == "pick" && settings.auto_hover)));
+    let Some(action) = action else { return };
+    let Some(action_id) = action["id"].as_i64() else { return };
+    // Campione già scelto a mano: non si tocca
+    if action["championId"].as_u64().unwrap_or(0) != 0 || !claim(action_id) { return; }
+
+    let kind = if action["type"] == "ban" { "ban" } else { "hover" };
+    let position = session["myTeam"].as_array().into_iter().flatten()
+        .find(|p| p["cellId"].as_i64() == Some(my_cell))
+        .and_then(|p| p["assignedPosition"].as_str())
+        .filter(|p| !p.is_empty())
+        .unwrap_or(DEFAULT_ROLE)
+        .to_uppercase();
+    let list = if kind == "ban" { &settings.bans } else { &settings.picks };
+    let list = AutomationSettings::list(list, &position).to_vec();
+    if list.is_empty() {
+        record(app, kind, None, &position, Err("nessuna lista per il ruolo".to_string()));
+        return;
+    }
+
+    let app = app.clone();
+    let lcu = lcu.clone();
+    let taken = unavailable(session);
+    tauri::async_runtime::spawn(async move {
+        let data = match static_data().await {
+            Ok(d) => d,
+            Err(e) => { record(&app, kind, None, &position, Err(e)); return; }
+        };
+        let allowed_path = if kind == "ban" {
+            "/lol-champ-select/v1/bannable-champion-ids"
+        } else {
+            "/lol-champ-select/v1/pickable-champion-ids"
+        };
+        let allowed = champion_ids(&lcu, allowed_path).await;
+        let Some((champion_id, name)) = first_available(&list, &taken, allowed.as_ref(), &data) else {
+            record(&app, kind, None, &position, Err("nessun campione disponibile nella lista".to_string()));
+            return;
+        };
+        // Solo selezione: `completed` resta false, il lock è dell'utente
+        let result = match lcu.patch_status(&format!("/lol-champ-select/v1/session/actions/{}", action_id),
+            &json!({"championId": champion_id})).await
+        {
+            Ok(s) if (200..300).contains(&s) => Ok(format!("{} selezionato", name)),
+            Ok(s) => Err(format!("il client ha rifiutato {} (HTTP {})", name, s)),
+            Err(e) => Err(e),
+        };
+        record(&app, kind, Some(name), &position, result);
+    });
+}
+
+pub fn on_session_closed() {
+    reset();
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_automation_settings() -> Result<AutomationSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn set_automation_settings(settings: AutomationSettings) -> Result<(), String> {
+    let path = SETTINGS_FILE.get().ok_or("Cartella dati non disponibile")?;
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Salvataggio automazioni fallito: {}", e))?;
+    eprintln!("[AutoActions] impostazioni: accept={} ban={} hover={}", settings.auto_accept, settings.auto_ban, settings.auto_hover);
+    Ok(())
+}
+
+/// Ultime azioni eseguite, dalla più vecchia.
+#[tauri::command]
+pub async fn get_automation_log() -> Result<Vec<AutoAction>, String> {
+    Ok(LOG.lock().unwrap().iter().cloned().collect())
+}
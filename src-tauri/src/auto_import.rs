@@ -0,0 +1,98 @@
+// auto_import.rs — import automatico in champ select
+//
+// Macchina a stati guidata dagli eventi LCU (lcu_events.rs), senza passare dal frontend:
+// funziona anche con la finestra ridotta a icona.
+//   • fuori dalla champ select (gameflow ≠ "ChampSelect" o sessione chiusa) → reset
+//   • pick confermato (`ChampSelectSession::locked`) → import di rune, spell e item set
+//     una sola volta per (campione, ruolo, modalità)
+//   • cambio di campione o ruolo (trade, reroll ARAM, swap dal bench) → nuovo import
+// Il frontend riceve l'avanzamento con l'evento `champ-select-import`.
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::champ_select::{import_build, ChampSelectSession, ImportResult};
+use crate::lcu::LcuClient;
+
+/// Evento Tauri: `result: null` all'avvio dell'import, poi con il risultato.
+pub const IMPORT_EVENT: &str = "champ-select-import";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LockIn {
+    champion_name: String,
+    assigned_position: String,
+    game_mode: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoImportEvent {
+    pub session: ChampSelectSession,
+    pub result: Option<ImportResult>,
+}
+
+/// Ultimo pick confermato già importato (o in import) nella champ select corrente.
+static CURRENT: Mutex<Option<LockIn>> = Mutex::new(None);
+/// Ultimo evento emesso, per il frontend che apre la tab a import già partito.
+static LAST: Mutex<Option<AutoImportEvent>> = Mutex::new(None);
+/// Un import alla volta: le scritture su rune page e item set non vanno sovrapposte.
+static RUNNING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn reset() {
+    if CURRENT.lock().unwrap().take().is_some() {
+        eprintln!("[AutoImport] champ select terminata");
+    }
+    *LAST.lock().unwrap() = None;
+}
+
+fn emit(app: &AppHandle, event: AutoImportEvent) {
+    *LAST.lock().unwrap() = Some(event.clone());
+    let _ = app.emit(IMPORT_EVENT, event);
+}
+
+// ── Eventi LCU ───────────────────────────────────────────────────────────────
+
+pub fn on_gameflow_phase(phase: &str) {
+    if phase != "ChampSelect" { reset(); }
+}
+
+pub fn on_session(app: &AppHandle, lcu: &LcuClient, session: &ChampSelectSession) {
+    if !session.in_progress { reset(); return; }
+    if !session.locked || session.champion_name.is_empty() { return; }
+
+    let lock_in = LockIn {
+        champion_name: session.champion_name.clone(),
+        assigned_position: session.assigned_position.clone(),
+        game_mode: session.game_mode.clone(),
+    };
+    {
+        let mut current = CURRENT.lock().unwrap();
+        if current.as_ref() == Some(&lock_in) { return; }
+        *current = Some(lock_in.clone());
+    }
+    eprintln!("[AutoImport] pick confermato: {} {} mode={}", lock_in.champion_name, lock_in.assigned_position, lock_in.game_mode);
+
+    let app = app.clone();
+    let lcu = lcu.clone();
+    let session = session.clone();
+    tauri::async_runtime::spawn(async move {
+        let _guard = RUNNING.lock().await;
+        // Nel frattempo il pick è cambiato (o la champ select è finita): vale solo l'ultimo
+        if CURRENT.lock().unwrap().as_ref() != Some(&lock_in) { return; }
+
+        emit(&app, AutoImportEvent { session: session.clone(), result: None });
+        let position = if lock_in.assigned_position.is_empty() { "DEFAULT" } else { &lock_in.assigned_position };
+        let result = import_build(&lcu, &lock_in.champion_name, position, &lock_in.game_mode, None).await;
+        if CURRENT.lock().unwrap().as_ref() == Some(&lock_in) {
+            emit(&app, AutoImportEvent { session, result: Some(result) });
+        }
+    });
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Stato dell'import automatico per la champ select in corso (`null` se nessuno).
+#[tauri::command]
+pub async fn get_auto_import_state() -> Result<Option<AutoImportEvent>, String> {
+    Ok(LAST.lock().unwrap().clone())
+}
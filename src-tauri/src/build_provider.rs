@@ -0,0 +1,183 @@
+// build_provider.rs — sorgenti di build intercambiabili
+//
+// Ogni sorgente implementa `BuildProvider`: dato campione, lane e modalità
+// restituisce una build nel formato di opgg.rs
+//   {"data": {runes, summoner_spells, starter_items, core_items, situ_slots, boots, skill_order, skill_sequence}}
+// così rune, spell e item set si importano con lo stesso codice qualunque sia l'origine.
+// Le sorgenti si registrano in `setup()`: OP.GG è sempre presente, il binario
+// aggiunge quelle che leggono l'archivio match locale (local_builds.rs: dataset
+// di tutte le partite salvate e build personali dell'utente).
+// `BuildPolicy` (salvata in build_policy.json) decide quale sorgente importare
+// per prima e se ripiegare sulle altre; tutte restano nel risultato dell'import
+// così il frontend può passare dall'una all'altra.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use futures_util::future::{join_all, BoxFuture};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::opgg;
+use crate::static_data::static_data;
+
+/// Cosa serve alla sorgente per scegliere la build.
+#[derive(Debug, Clone)]
+pub struct BuildRequest {
+    /// Id DDragon ("MonkeyKing").
+    pub champion_name: String,
+    /// Lane LCU ("TOP", "JUNGLE", "MIDDLE", "BOTTOM", "UTILITY"), vuota se assente.
+    pub position: String,
+    /// "ranked" | "aram" | "urf" | "arena"
+    pub mode: &'static str,
+    /// Utente corrente (vuoto se il client non lo fornisce), per le build personali.
+    /// Lo legge solo PersonalProvider (local_builds.rs, nel binario).
+    #[allow(dead_code)]
+    pub puuid: String,
+}
+
+/// Build di una sorgente.
+#[derive(Debug, Clone)]
+pub struct ProviderBuild {
+    /// Formato di opgg.rs (`{"data": {...}}`).
+    pub build: Value,
+    /// Modalità effettiva: diversa dalla richiesta se la sorgente ha ripiegato sul ranked.
+    pub mode: String,
+    /// Partite su cui è calcolata (solo per le sorgenti locali).
+    pub sample_size: Option<u32>,
+    pub win_rate: Option<f64>,
+}
+
+pub trait BuildProvider: Send + Sync {
+    /// Identificativo stabile, usato da `BuildPolicy` ("opgg", "local", ...).
+    fn id(&self) -> &'static str;
+    /// Nome mostrato nel frontend.
+    fn label(&self) -> &'static str;
+    fn fetch<'a>(&'a self, req: &'a BuildRequest) -> BoxFuture<'a, Result<ProviderBuild, String>>;
+}
+
+// ── Registro ─────────────────────────────────────────────────────────────────
+
+static PROVIDERS: Mutex<Vec<Arc<dyn BuildProvider>>> = Mutex::new(Vec::new());
+
+/// Aggiunge una sorgente (una sola per id).
+pub fn register(provider: Arc<dyn BuildProvider>) {
+    let mut providers = PROVIDERS.lock().unwrap();
+    providers.retain(|p| p.id() != provider.id());
+    providers.push(provider);
+}
+
+pub fn providers() -> Vec<Arc<dyn BuildProvider>> {
+    PROVIDERS.lock().unwrap().clone()
+}
+
+// ── Policy ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildPolicy {
+    /// Sorgente importata per prima.
+    pub primary: String,
+    /// Se la primaria fallisce si importa la prima delle altre che ha risposto.
+    pub fallback: bool,
+}
+
+impl Default for BuildPolicy {
+    fn default() -> Self {
+        BuildPolicy { primary: "opgg".to_string(), fallback: true }
+    }
+}
+
+static POLICY_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn init(app_data_dir: &Path) {
+    let _ = POLICY_FILE.set(app_data_dir.join("build_policy.json"));
+    register(Arc::new(OpggProvider));
+}
+
+pub fn policy() -> BuildPolicy {
+    POLICY_FILE.get()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+/// Sorgenti nell'ordine della policy: primaria, poi le altre nell'ordine di registrazione.
+pub fn ordered(policy: &BuildPolicy) -> Vec<Arc<dyn BuildProvider>> {
+    let mut all = providers();
+    all.sort_by_key(|p| p.id() != policy.primary);
+    all
+}
+
+/// Interroga tutte le sorgenti in parallelo; risultati nell'ordine della policy.
+pub async fn fetch_all(req: &BuildRequest, policy: &BuildPolicy) -> Vec<(Arc<dyn BuildProvider>, Result<ProviderBuild, String>)> {
+    let providers = ordered(policy);
+    let results = join_all(providers.iter().map(|p| p.fetch(req))).await;
+    for (p, r) in providers.iter().zip(&results) {
+        if let Err(e) = r { eprintln!("[Build] {} non disponibile: {}", p.id(), e); }
+    }
+    providers.into_iter().zip(results).collect()
+}
+
+// ── OP.GG ────────────────────────────────────────────────────────────────────
+
+/// Statistiche globali OP.GG (opgg.rs). ARAM, URF e Arena usano i dati della
+/// modalità (senza lane); se OP.GG non li ha per il campione si ripiega sulla
+/// build ranked della lane assegnata (MID se non c'è).
+pub struct OpggProvider;
+
+impl OpggProvider {
+    async fn champion_build(champion_name: &str, position: Option<&str>, game_mode: &str) -> Result<Value, String> {
+        eprintln!("[Build] OP.GG: {} {:?} (mode={})", champion_name, position, game_mode);
+        let pos = position.map(opgg::position);
+        let ddragon = static_data().await.ok();
+        Ok(opgg::champion_build(champion_name, pos, game_mode, ddragon.as_deref()).await?)
+    }
+
+    async fn fetch_mode_build(req: &BuildRequest) -> Result<ProviderBuild, String> {
+        let found = |build: Value, mode: &str| ProviderBuild { build, mode: mode.to_string(), sample_size: None, win_rate: None };
+        if req.mode != "ranked" {
+            match Self::champion_build(&req.champion_name, None, req.mode).await {
+                Ok(build) => return Ok(found(build, req.mode)),
+                Err(e) => eprintln!("[Build] OP.GG {} non disponibile ({}), ripiego su ranked", req.mode, e),
+            }
+        }
+        let build = Self::champion_build(&req.champion_name, Some(&req.position), "ranked").await?;
+        Ok(found(build, "ranked"))
+    }
+}
+
+impl BuildProvider for OpggProvider {
+    fn id(&self) -> &'static str { "opgg" }
+    fn label(&self) -> &'static str { "OP.GG" }
+    fn fetch<'a>(&'a self, req: &'a BuildRequest) -> BoxFuture<'a, Result<ProviderBuild, String>> {
+        Box::pin(Self::fetch_mode_build(req))
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub label: String,
+}
+
+/// Policy corrente + sorgenti disponibili (per il selettore nel frontend).
+#[tauri::command]
+pub async fn get_build_policy() -> Result<Value, String> {
+    let sources: Vec<ProviderInfo> = providers().iter()
+        .map(|p| ProviderInfo { id: p.id().to_string(), label: p.label().to_string() })
+        .collect();
+    Ok(serde_json::json!({ "policy": policy(), "sources": sources }))
+}
+
+#[tauri::command]
+pub async fn set_build_policy(policy: BuildPolicy) -> Result<(), String> {
+    if !providers().iter().any(|p| p.id() == policy.primary) {
+        return Err(format!("Sorgente build '{}' sconosciuta", policy.primary));
+    }
+    let path = POLICY_FILE.get().ok_or("Cartella dati non disponibile")?;
+    let content = serde_json::to_string_pretty(&policy).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Salvataggio policy fallito: {}", e))
+}
@@ -1,434 +1,28 @@
 // champ_select.rs — complete rewrite
-use reqwest::Client;
 use serde_json::{json, Value};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use std::collections::HashMap;
-use base64::{engine::general_purpose, Engine as _};
-use tokio::sync::{OnceCell, RwLock};
+use tauri::State;
 
-// // ── Turso (libSQL HTTP) ───────────────────────────────────────────────────────
-// const TURSO_URL: &str   = env!("TURSO_URL");   // es. libsql://rlp-dataset-xxx.turso.io
-// const TURSO_TOKEN: &str = env!("TURSO_TOKEN"); // JWT token Turso
+use crate::build_provider::{self, BuildRequest};
+use crate::item_sets::{self, ItemSet};
+use crate::lcu::{LcuClient, LcuConnection};
+use crate::rune_pages;
+use crate::rune_validator;
+use crate::summoner_spells;
+use crate::static_data::static_data;
 
-// // ── DB Build cache in-memory ─────────────────────────────────────────────────
-// // Chiave: (champion_id, lane_normalizzata) -> DbBuild gia calcolata.
-// // Viene invalidata solo al riavvio dell'app (durata della session LoL).
-// static DB_BUILD_CACHE: OnceCell<RwLock<HashMap<(i64, String), DbBuild>>> = OnceCell::const_new();
-
-// async fn db_build_cache() -> &'static RwLock<HashMap<(i64, String), DbBuild>> {
-//     DB_BUILD_CACHE.get_or_init(|| async { RwLock::new(HashMap::new()) }).await
-// }
-
-// /// Esegue una query SQL su Turso via HTTP e ritorna le righe come Vec<Vec<Value>>.
-// async fn turso_query(sql: &str, args: Vec<Value>) -> Result<Vec<Vec<Value>>, String> {
-//     let url = TURSO_URL.replace("libsql://", "https://") + "/v2/pipeline";
-//     let body = json!({
-//         "requests": [{
-//             "type": "execute",
-//             "stmt": {
-//                 "sql": sql,
-//                 "args": args.iter().map(|a| match a {
-//                     Value::Number(n) => json!({"type":"integer","value": n.to_string()}),
-//                     Value::String(s) => json!({"type":"text","value": s}),
-//                     _ => json!({"type":"null","value": null}),
-//                 }).collect::<Vec<_>>()
-//             }
-//         }, {"type":"close"}]
-//     });
-//
-//     let client = Client::new();
-//     let resp = client
-//         .post(&url)
-//         .header("Authorization", format!("Bearer {}", TURSO_TOKEN))
-//         .header("Content-Type", "application/json")
-//         .json(&body)
-//         .send().await.map_err(|e| format!("Turso HTTP error: {}", e))?;
-//
-//     let data: Value = resp.json().await.map_err(|e| format!("Turso JSON error: {}", e))?;
-//
-//     // Estrae le righe dalla risposta pipeline
-//     let rows_raw = data.pointer("/results/0/response/result/rows")
-//         .and_then(|v| v.as_array())
-//         .ok_or_else(|| {
-//             let err = data.pointer("/results/0/response/error/message")
-//                 .and_then(|v| v.as_str()).unwrap_or("risposta Turso vuota");
-//             format!("Turso query fallita: {}", err)
-//         })?;
-//
-//     // Ogni riga è un array di {type, value}
-//     let rows: Vec<Vec<Value>> = rows_raw.iter().map(|row| {
-//         row.as_array().unwrap_or(&vec![]).iter().map(|cell| {
-//             match cell["type"].as_str() {
-//                 Some("integer") => cell["value"].as_str()
-//                     .and_then(|s| s.parse::<i64>().ok())
-//                     .map(|n| json!(n))
-//                     .unwrap_or(Value::Null),
-//                 Some("real") => cell["value"].as_str()
-//                     .and_then(|s| s.parse::<f64>().ok())
-//                     .map(|n| json!(n))
-//                     .unwrap_or(Value::Null),
-//                 Some("text") => cell["value"].clone(),
-//                 _ => Value::Null,
-//             }
-//         }).collect()
-//     }).collect();
-//
-//     Ok(rows)
-// }
-//
-// /// Risolve il championid di Turso dal nome (DDragon id).
-// /// Ritorna None se non trovato.
-// async fn turso_get_champion_id(champion_name: &str) -> Option<i64> {
-//     let rows = turso_query(
-//         "SELECT championid FROM championtbl WHERE LOWER(championname) = LOWER(?1) LIMIT 1",
-//         vec![json!(champion_name)],
-//     ).await.ok()?;
-//     rows.into_iter().next()?.into_iter().next()?.as_i64()
-// }
-//
-// /// Struttura per la build dal DB.
-// #[derive(Debug, Serialize, Deserialize, Clone)]
-// pub struct DbBuild {
-//     pub primary_path_id: u32,
-//     pub secondary_path_id: u32,
-//     pub primary_path_name: String,
-//     pub secondary_path_name: String,
-//     pub keystone_id: u32,
-//     pub primary_slot_ids: Vec<u32>,  // [slot1, slot2, slot3] rune primarie (escluso keystone)
-//     pub secondary_rune_ids: Vec<u32>, // [runa1, runa2] rune secondarie più frequenti
-//     pub items: Vec<u32>,
-//     pub sample_size: u32,
-// }
-//
-fn path_id_to_name(id: u32) -> &'static str {
-    match id {
-        8000 => "Precision",
-        8100 => "Domination",
-        8200 => "Sorcery",
-        8400 => "Resolve",
-        8300 => "Inspiration",
-        _ => "Unknown",
-    }
-}
-
-// /// Recupera la build da Turso (primary/secondary path, keystone, item più frequenti)
-// /// per champion_id e lane. Richiede almeno 5 partite campione.
-// async fn turso_get_db_build(champion_id: i64, lane: &str) -> Option<DbBuild> {
-//     let norm_lane = match lane.to_uppercase().as_str() {
-//         "TOP"                     => "TOP",
-//         "JUNGLE"                  => "JUNGLE",
-//         "MIDDLE"|"MID"            => "MIDDLE",
-//         "BOTTOM"|"BOT"|"ADC"      => "BOTTOM",
-//         "SUPPORT"|"UTILITY"       => "UTILITY",
-//         _                         => "MIDDLE",
-//     }.to_string();
-//
-//     // ── Cache check ──────────────────────────────────────────────────────────
-//     {
-//         let cache = db_build_cache().await.read().await;
-//         if let Some(cached) = cache.get(&(champion_id, norm_lane.clone())) {
-//             eprintln!("[RLP DB] cache HIT champion_id={} lane={}", champion_id, norm_lane);
-//             return Some(cached.clone());
-//         }
-//     }
-//     eprintln!("[RLP DB] cache MISS champion_id={} lane={} — querying Turso", champion_id, norm_lane);
-//
-//     // ── Query unica: keystone + path counts + slot1/2/3 + secondary + items ─
-//     // Tutto in un solo round-trip HTTP verso Turso usando subquery e aggregazioni.
-//     // Struttura risultato (1 riga):
-//     //   0  keystone        (MODE: keystone piu frequente)
-//     //   1  cnt             (numero partite)
-//     //   2  prec_ks         path precision count (dai keystone)
-//     //   3  dom_ks
-//     //   4  sorc_ks
-//     //   5  insp_ks
-//     //   6  res_ks
-//     //   7  slot1           (primaryslot1 piu frequente)
-//     //   8  slot2
-//     //   9  slot3
-//     //   10 sec_prec        (secondary path counts)
-//     //   11 sec_dom
-//     //   12 sec_sorc
-//     //   13 sec_insp
-//     //   14 sec_res
-//     //   15 sec_rune_a      (top2 rune secondarie)
-//     //   16 sec_rune_b
-//     //   17..25 item0..item8 (top9 item per frequenza)
-//     let rows = turso_query(
-//         "WITH base AS (
-//             SELECT ms.primarykeystone, ms.primaryslot1, ms.primaryslot2, ms.primaryslot3,
-//                    ms.secondaryslot1,  ms.secondaryslot2,
-//                    ms.item1, ms.item2, ms.item3, ms.item4, ms.item5, ms.item6
-//             FROM matchstatstbl ms
-//             JOIN summonermatchtbl sm ON sm.summonermatchid = ms.summonermatchfk
-//             WHERE sm.championfk = ?1 AND UPPER(ms.lane) = ?2
-//         ),
-//         ks AS (
-//             SELECT primarykeystone as ks, COUNT(*) as cnt
-//             FROM base GROUP BY primarykeystone ORDER BY cnt DESC LIMIT 1
-//         ),
-//         s1 AS (
-//             SELECT primaryslot1 as v FROM base WHERE primaryslot1 > 0
-//             GROUP BY primaryslot1 ORDER BY COUNT(*) DESC LIMIT 1
-//         ),
-//         s2 AS (
-//             SELECT primaryslot2 as v FROM base WHERE primaryslot2 > 0
-//             GROUP BY primaryslot2 ORDER BY COUNT(*) DESC LIMIT 1
-//         ),
-//         s3 AS (
-//             SELECT primaryslot3 as v FROM base WHERE primaryslot3 > 0
-//             GROUP BY primaryslot3 ORDER BY COUNT(*) DESC LIMIT 1
-//         ),
-//         sec_counts AS (
-//             SELECT
-//                 SUM(CASE WHEN secondaryslot1 BETWEEN 8000 AND 8099 OR secondaryslot2 BETWEEN 8000 AND 8099 THEN 1 ELSE 0 END) as sp,
-//                 SUM(CASE WHEN secondaryslot1 BETWEEN 8100 AND 8199 OR secondaryslot2 BETWEEN 8100 AND 8199 THEN 1 ELSE 0 END) as sd,
-//                 SUM(CASE WHEN secondaryslot1 BETWEEN 8200 AND 8299 OR secondaryslot2 BETWEEN 8200 AND 8299 THEN 1 ELSE 0 END) as ss,
-//                 SUM(CASE WHEN secondaryslot1 BETWEEN 8300 AND 8399 OR secondaryslot2 BETWEEN 8300 AND 8399 THEN 1 ELSE 0 END) as si,
-//                 SUM(CASE WHEN secondaryslot1 BETWEEN 8400 AND 8499 OR secondaryslot2 BETWEEN 8400 AND 8499 THEN 1 ELSE 0 END) as sr
-//             FROM base
-//         ),
-//         all_sec AS (
-//             SELECT secondaryslot1 as rune_id FROM base WHERE secondaryslot1 > 0
-//             UNION ALL
-//             SELECT secondaryslot2 FROM base WHERE secondaryslot2 > 0
-//         ),
-//         top_sec AS (
-//             SELECT rune_id, COUNT(*) as cnt FROM all_sec
-//             GROUP BY rune_id ORDER BY cnt DESC LIMIT 2
-//         ),
-//         all_items AS (
-//             SELECT item1 as item_id FROM base WHERE item1 > 1000 AND item1 < 500000
-//             UNION ALL SELECT item2 FROM base WHERE item2 > 1000 AND item2 < 500000
-//             UNION ALL SELECT item3 FROM base WHERE item3 > 1000 AND item3 < 500000
-//             UNION ALL SELECT item4 FROM base WHERE item4 > 1000 AND item4 < 500000
-//             UNION ALL SELECT item5 FROM base WHERE item5 > 1000 AND item5 < 500000
-//             UNION ALL SELECT item6 FROM base WHERE item6 > 1000 AND item6 < 500000
-//         ),
-//         top_items AS (
-//             SELECT item_id, COUNT(*) as cnt FROM all_items
-//             GROUP BY item_id ORDER BY cnt DESC LIMIT 9
-//         )
-//         SELECT
-//             ks.ks, ks.cnt,
-//             SUM(CASE WHEN ks.ks BETWEEN 8000 AND 8099 THEN 1 ELSE 0 END),
-//             SUM(CASE WHEN ks.ks BETWEEN 8100 AND 8199 THEN 1 ELSE 0 END),
-//             SUM(CASE WHEN ks.ks BETWEEN 8200 AND 8299 THEN 1 ELSE 0 END),
-//             SUM(CASE WHEN ks.ks BETWEEN 8300 AND 8399 THEN 1 ELSE 0 END),
-//             SUM(CASE WHEN ks.ks BETWEEN 8400 AND 8499 THEN 1 ELSE 0 END),
-//             COALESCE((SELECT v FROM s1), 0),
-//             COALESCE((SELECT v FROM s2), 0),
-//             COALESCE((SELECT v FROM s3), 0),
-//             (SELECT sp FROM sec_counts),
-//             (SELECT sd FROM sec_counts),
-//             (SELECT ss FROM sec_counts),
-//             (SELECT si FROM sec_counts),
-//             (SELECT sr FROM sec_counts),
-//             COALESCE((SELECT rune_id FROM top_sec ORDER BY cnt DESC LIMIT 1 OFFSET 0), 0),
-//             COALESCE((SELECT rune_id FROM top_sec ORDER BY cnt DESC LIMIT 1 OFFSET 1), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 0), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 1), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 2), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 3), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 4), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 5), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 6), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 7), 0),
-//             COALESCE((SELECT item_id FROM top_items ORDER BY cnt DESC LIMIT 1 OFFSET 8), 0)
-//         FROM ks",
-//         vec![json!(champion_id), json!(norm_lane)],
-//     ).await.ok()?;
-//
-//     let row = rows.first()?;
-//     let get_i64 = |i: usize| row.get(i).and_then(|v| v.as_i64()).unwrap_or(0);
-//     let get_u32 = |i: usize| get_i64(i) as u32;
-//
-//     let keystone_id  = get_u32(0);
-//     let sample_size  = get_u32(1);
-//
-//     if sample_size < 5 {
-//         eprintln!("[RLP DB] Sample size troppo piccolo ({}) per champion_id={}", sample_size, champion_id);
-//         return None;
-//     }
-//
-//     // Primary path dal keystone
-//     let primary_path_id: u32 = match keystone_id {
-//         8005|8008|8021|8010 => 8000,
-//         8112|8124|8128|9923 => 8100,
-//         8229|8230|8214      => 8200,
-//         8437|8439|8465      => 8400,
-//         8351|8360|8369      => 8300,
-//         _ => {
-//             let counts = [(8000u32, get_u32(2)), (8100, get_u32(3)), (8200, get_u32(4)),
-//                           (8300, get_u32(5)), (8400, get_u32(6))];
-//             counts.iter().max_by_key(|(_, c)| *c).map(|(p, _)| *p).unwrap_or(8000)
-//         }
-//     };
-//
-//     let slot1 = get_u32(7);
-//     let slot2 = get_u32(8);
-//     let slot3 = get_u32(9);
-//     let primary_slot_ids = vec![slot1, slot2, slot3];
-//
-//     // Secondary path (escludi primary)
-//     let sec_counts = [
-//         (8000u32, get_u32(10)), (8100, get_u32(11)), (8200, get_u32(12)),
-//         (8300, get_u32(13)),    (8400, get_u32(14)),
-//     ];
-//     let secondary_path_id = sec_counts.iter()
-//         .filter(|(path, _)| *path != primary_path_id)
-//         .max_by_key(|(_, c)| *c)
-//         .map(|(p, _)| *p)
-//         .unwrap_or(8100);
-//
-//     // Filtra le secondary rune per appartenenenza al secondary path
-//     let sec_min = secondary_path_id;
-//     let sec_max = secondary_path_id + 99;
-//     let secondary_rune_ids: Vec<u32> = [get_u32(15), get_u32(16)]
-//         .iter()
-//         .copied()
-//         .filter(|&id| id >= sec_min && id <= sec_max)
-//         .collect();
-//
-//     // Items (filtra zeri)
-//     let items: Vec<u32> = (17..=25)
-//         .map(|i| get_u32(i))
-//         .filter(|&id| id > 0)
-//         .collect();
-//
-//     eprintln!("[RLP DB] build champion_id={} lane={}: primary={} sec={} keystone={} pri_slots={:?} sec_runes={:?} items={:?} sample={}",
-//         champion_id, norm_lane, primary_path_id, secondary_path_id, keystone_id, primary_slot_ids, secondary_rune_ids, items, sample_size);
-//
-//     let build = DbBuild {
-//         primary_path_id,
-//         secondary_path_id,
-//         primary_path_name: path_id_to_name(primary_path_id).to_string(),
-//         secondary_path_name: path_id_to_name(secondary_path_id).to_string(),
-//         keystone_id,
-//         primary_slot_ids,
-//         secondary_rune_ids,
-//         items,
-//         sample_size,
-//     };
-//
-//     // ── Salva in cache ───────────────────────────────────────────────────────
-//     {
-//         let mut cache = db_build_cache().await.write().await;
-//         cache.insert((champion_id, norm_lane), build.clone());
-//     }
-//
-//     Some(build)
-// }
-const PATCH: &str = "16.4.1";
-
-fn spell_name_to_id(name: &str) -> Option<u32> {
-    match name.to_lowercase().as_str() {
-        "flash"|"summonerflash"     => Some(4),
-        "ignite"|"summonerdot"      => Some(14),
-        "teleport"|"summonerteleport" => Some(12),
-        "barrier"|"summonerbarrier"|"summonerinspect" => Some(21),
-        "exhaust"|"summonerexhaust" => Some(3),
-        "ghost"|"summonerhaste"     => Some(6),
-        "heal"|"summonerheal"       => Some(7),
-        "cleanse"|"summonerboost"|"summonerraise" => Some(1),
-        "smite"|"summonersmite"     => Some(11),
-        "clarity"|"summonermana"    => Some(13),
-        _ => None,
-    }
-}
-
-fn rune_path_id(name: &str) -> Option<u32> {
-    match name.to_lowercase().as_str() {
-        "precision"   => Some(8000),
-        "domination"  => Some(8100),
-        "sorcery"     => Some(8200),
-        "resolve"     => Some(8400),
-        "inspiration" => Some(8300),
-        _ => None,
-    }
-}
-
-fn rune_name_to_id(name: &str) -> Option<u32> {
-    match name.to_lowercase().replace(" ","").replace("'","").as_str() {
-        "presstheattack" => Some(8005),
-        "lethaltempolegacy"|"lethaltempo" => Some(8008),
-        "fleetfootwork" => Some(8021),
-        "conqueror" => Some(8010),
-        "electrocute" => Some(8112),
-        "predator" => Some(8124),
-        "darkharvest" => Some(8128),
-        "hailofblades" => Some(9923),
-        "arcanecomet" => Some(8229),
-        "phasherush" => Some(8230),
-        "summonaery"|"summonaerie" => Some(8214),
-        "graspoftheundying" => Some(8437),
-        "aftershock" => Some(8439),
-        "guardian" => Some(8465),
-        "glacialaugment" => Some(8351),
-        "unsealedspeelbook"|"unsealedspellbook" => Some(8360),
-        "firstatrike" => Some(8369),
-        "overheal" => Some(9101),
-        "triumph" => Some(9111),
-        "presenceofmind" => Some(8009),
-        "legendalacrity" => Some(9104),
-        "legendtenacity" => Some(9105),
-        "legendbloodline" => Some(9103),
-        "coupdegrace" => Some(8014),
-        "cutdown" => Some(8017),
-        "laststand" => Some(8299),
-        "cheapshot" => Some(8126),
-        "tasteofblood" => Some(8139),
-        "suddenimpact" => Some(8143),
-        "zombieward" => Some(8136),
-        "ghostporo" => Some(8120),
-        "eyeballcollection" => Some(8138),
-        "relentlesshunter" => Some(8105),
-        "ingenieoushunter"|"ingeniushunter" => Some(8106),
-        "ultimatehunter" => Some(8135),
-        "treasurehunter" => Some(8134),
-        "nullifyingorb" => Some(8224),
-        "manaflowband" => Some(8226),
-        "nimbuscloak" => Some(8275),
-        "transcendence" => Some(8210),
-        "celerity" => Some(8234),
-        "absolutefocus" => Some(8233),
-        "scorch" => Some(8237),
-        "waterwalking" => Some(8232),
-        "gatheringstorm" => Some(8236),
-        "demolish" => Some(8446),
-        "fontoflife" => Some(8463),
-        "shieldbash" => Some(8401),
-        "conditioning" => Some(8429),
-        "secondwind" => Some(8444),
-        "boneplating" => Some(8473),
-        "overgrowth" => Some(8451),
-        "revitalize" => Some(8453),
-        "unflinching" => Some(8242),
-        "hextechflashtraption" => Some(8306),
-        "magicalfootwear" => Some(8304),
-        "perfecttiming" => Some(8313),
-        "futuresmarket" => Some(8321),
-        "miniondematerializer" => Some(8316),
-        "biscuitdelivery" => Some(8345),
-        "cosmicinsight" => Some(8347),
-        "approachvelocity" => Some(8410),
-        "timewarpedtonic" => Some(8352),
-        _ => None,
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChampSelectSession {
     pub in_progress: bool,
     pub champion_name: String,
     pub assigned_position: String,
-    /// "ranked" | "aram" | "urf" | "arurf" | "normal" — ricavato dal gameMode LCU
+    /// "ranked" | "aram" | "urf" | "arena" — ricavato dal gameMode LCU
     pub game_mode: String,
+    /// Pick confermato (azione "pick" completata, o campione assegnato nelle modalità senza pick)
+    pub locked: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
     pub runes_imported: bool,
     pub summoners_imported: bool,
@@ -436,12 +30,91 @@ pub struct ImportResult {
     pub rune_page_name: Option<String>,
     pub primary_path: Option<String>,
     pub summoner_spells: Vec<String>,
+    /// Motivo per cui le spell non sono state toccate (import saltato, non fallito).
+    pub summoners_skipped: Option<String>,
+    /// Spell della build scartate o sostituite perché non ammesse nella coda.
+    pub spell_fixes: Vec<String>,
     pub item_blocks: Option<usize>,
+    /// Dove è finito l'item set: "lcu" (account) o "file" (Config/, ripiego).
+    pub item_set_target: Option<String>,
     pub errors: Vec<String>,
-    // ── Rune IDs completi OP.GG (per visualizzazione frontend) ───────────────
+    // ── Rune IDs completi della build importata (per visualizzazione frontend) ─
     pub opgg_runes: Option<RunePageData>,
-    // ── Build alternativa dal DB ──────────────────────────────────────────────
-    // pub db_build: Option<DbBuildResult>,
+    // ── Provenienza della build ──────────────────────────────────────────────
+    /// Sorgente effettivamente importata ("OP.GG", "Dataset RLP", ...).
+    pub build_source: Option<String>,
+    /// Modalità della build usata: diversa da quella della partita se si è ripiegato sul ranked.
+    pub build_mode: Option<String>,
+    /// Augment consigliati (solo Arena).
+    pub augments: Vec<String>,
+    /// Abilità livello per livello della build importata.
+    pub skill_plan: Option<SkillPlan>,
+    // ── Tutte le sorgenti (per il cambio sorgente nel frontend) ──────────────
+    pub builds: Vec<SourceBuild>,
+}
+
+/// Build di una sorgente: il frontend la applica con `apply_rune_page`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceBuild {
+    /// Id della sorgente ("opgg", "local", ...).
+    pub source: String,
+    pub label: String,
+    pub build_mode: Option<String>,
+    pub runes: Option<RunePageData>,
+    pub summoner_spells: Vec<String>,
+    pub core_items: Vec<u64>,
+    pub skill_order: String,
+    pub skill_plan: Option<SkillPlan>,
+    pub sample_size: Option<u32>,
+    pub win_rate: Option<f64>,
+    /// È la build importata nel client.
+    pub imported: bool,
+    pub error: Option<String>,
+}
+
+/// Abilità da prendere a un livello.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillLevel {
+    pub level: u32,
+    /// "Q" | "W" | "E" | "R"
+    pub skill: String,
+}
+
+/// Piano abilità livello per livello, dalla `skill_sequence` della build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillPlan {
+    /// Livelli 1..=18 (meno se la sorgente ne fornisce meno).
+    pub levels: Vec<SkillLevel>,
+    /// Primi tre punti ("Q", "E", "W").
+    pub first_three: Vec<String>,
+    /// Ordine di massimizzazione ("Q → E → W").
+    pub max_order: String,
+    /// Livelli in cui si prende la R (di solito 6, 11, 16).
+    pub r_levels: Vec<u32>,
+}
+
+impl SkillPlan {
+    /// `None` se la build non ha la sequenza (es. build locali).
+    pub fn from_build(build: &Value) -> Option<Self> {
+        let sequence: Vec<String> = build.pointer("/data/skill_sequence")?.as_array()?.iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect();
+        if sequence.is_empty() { return None; }
+        let levels: Vec<SkillLevel> = sequence.iter().enumerate()
+            .map(|(i, skill)| SkillLevel { level: i as u32 + 1, skill: skill.clone() })
+            .collect();
+        Some(SkillPlan {
+            first_three: sequence.iter().take(3).cloned().collect(),
+            max_order: build.pointer("/data/skill_order").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            r_levels: levels.iter().filter(|l| l.skill == "R").map(|l| l.level).collect(),
+            levels,
+        })
+    }
+
+    /// "Q W E Q Q R ..." per il titolo del blocco nell'item set.
+    fn sequence_label(&self) -> String {
+        self.levels.iter().map(|l| l.skill.as_str()).collect::<Vec<_>>().join(" ")
+    }
 }
 
 /// Dati completi di una pagina rune per il rendering frontend.
@@ -457,658 +130,65 @@ pub struct RunePageData {
     pub stat_mod_ids: Vec<u32>,       // [offence, flex, defence]
 }
 
-// #[derive(Debug, Serialize, Deserialize, Clone)]
-// pub struct DbBuildResult {
-//     pub primary_path_name: String,
-//     pub secondary_path_name: String,
-//     pub primary_path_id: u32,
-//     pub secondary_path_id: u32,
-//     pub keystone_id: u32,
-//     /// Le 3 rune dello slot primario (slot1, slot2, slot3) più frequenti
-//     pub primary_slot_ids: Vec<u32>,
-//     /// Le 2 rune secondarie più frequenti dal DB
-//     pub secondary_rune_ids: Vec<u32>,
-//     pub items_imported: bool,
-//     pub sample_size: u32,
-// }
-
-/// Cerca il lockfile di League su tutte le lettere di drive possibili (C→Z).
-fn get_lockfile_path() -> Option<PathBuf> {
-    let suffixes = [
-        r"Riot Games\League of Legends\lockfile",
-        r"Program Files\Riot Games\League of Legends\lockfile",
-        r"Program Files (x86)\Riot Games\League of Legends\lockfile",
-        r"Games\League of Legends\lockfile",
-        r"League of Legends\lockfile",
-    ];
-    for drive in 'C'..='Z' {
-        for suffix in &suffixes {
-            let path = PathBuf::from(format!(r"{}:\{}", drive, suffix));
-            if path.exists() {
-                eprintln!("[RLP] lockfile trovato: {:?}", path);
-                return Some(path);
-            }
-        }
-    }
-    eprintln!("[RLP] lockfile non trovato su nessun drive (C:-Z:)");
-    None
-}
-
-/// Cerca la cartella Config\Champions di League su tutte le lettere di drive (C→Z).
-fn get_league_config_path() -> Option<PathBuf> {
-    let suffixes = [
-        r"Riot Games\League of Legends\Config\Champions",
-        r"Program Files\Riot Games\League of Legends\Config\Champions",
-        r"Program Files (x86)\Riot Games\League of Legends\Config\Champions",
-        r"Games\League of Legends\Config\Champions",
-        r"League of Legends\Config\Champions",
-    ];
-    for drive in 'C'..='Z' {
-        for suffix in &suffixes {
-            let path = PathBuf::from(format!(r"{}:\{}", drive, suffix));
-            if path.exists() {
-                eprintln!("[RLP] config path trovato: {:?}", path);
-                return Some(path);
-            }
-        }
-    }
-    eprintln!("[RLP] config path non trovato su nessun drive (C:-Z:)");
-    None
-}
-
-struct LcuClient { client: Client, port: String, auth: String }
-
-impl LcuClient {
-    fn new() -> Option<Self> {
-        let content = fs::read_to_string(get_lockfile_path()?).ok()?;
-        let parts: Vec<&str> = content.split(':').collect();
-        if parts.len() < 4 { return None; }
-        let port = parts[2].to_string();
-        let password = parts[3].to_string();
-        let auth = general_purpose::STANDARD.encode(format!("riot:{}", password));
-        let client = Client::builder().danger_accept_invalid_certs(true).build().ok()?;
-        Some(LcuClient { client, port, auth })
-    }
-    async fn get(&self, path: &str) -> Option<Value> {
-        self.client.get(&format!("https://127.0.0.1:{}{}", self.port, path))
-            .header("Authorization", format!("Basic {}", self.auth))
-            .send().await.ok()?.json().await.ok()
-    }
-    async fn put(&self, path: &str, body: &Value) -> Option<Value> {
-        self.client.put(&format!("https://127.0.0.1:{}{}", self.port, path))
-            .header("Authorization", format!("Basic {}", self.auth))
-            .json(body).send().await.ok()?.json().await.ok()
-    }
-    async fn post(&self, path: &str, body: &Value) -> Option<Value> {
-        self.client.post(&format!("https://127.0.0.1:{}{}", self.port, path))
-            .header("Authorization", format!("Basic {}", self.auth))
-            .json(body).send().await.ok()?.json().await.ok()
-    }
-    async fn post_status(&self, path: &str, body: &Value) -> Result<u16, String> {
-        let resp = self.client.post(&format!("https://127.0.0.1:{}{}", self.port, path))
-            .header("Authorization", format!("Basic {}", self.auth))
-            .json(body).send().await.map_err(|e| e.to_string())?;
-        Ok(resp.status().as_u16())
-    }
-}
-
-async fn mcp_extract_text(text: &str) -> Option<String> {
-    for line in text.lines() {
-        if let Some(data) = line.strip_prefix("data: ") {
-            if let Ok(p) = serde_json::from_str::<Value>(data) {
-                if p.get("error").is_some() { continue; }
-                if let Some(c) = p["result"]["content"].as_array() {
-                    for item in c { if item["type"]=="text" { return item["text"].as_str().map(String::from); } }
-                }
-            }
-        }
-    }
-    if let Ok(p) = serde_json::from_str::<Value>(text) {
-        if p.get("error").is_none() {
-            if let Some(c) = p["result"]["content"].as_array() {
-                for item in c { if item["type"]=="text" { return item["text"].as_str().map(String::from); } }
-            }
-        }
-    }
-    None
-}
-
-async fn opgg_get_champion_build(champion_name: &str, position: &str, game_mode: &str) -> Result<Value, String> {
-    eprintln!("[RLP] opgg_get_champion_build: {} {} (mode={})", champion_name, position, game_mode);
-
-    let client = Client::builder()
-        .danger_accept_invalid_certs(true)
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-        .build().map_err(|e| e.to_string())?;
-
-    // Per ranked usiamo la lane reale. Per ARAM/URF proviamo i valori accettati dall'API in ordine.
-    let pos_ranked = match position.to_uppercase().as_str() {
-        "TOP"                     => "top",
-        "JUNGLE"                  => "jungle",
-        "MIDDLE"|"MID"            => "mid",
-        "BOTTOM"|"BOT"|"ADC"      => "adc",
-        "SUPPORT"|"UTILITY"       => "support",
-        _                         => "mid",
+/// Pagina rune (già validata) dalla sezione `runes` di una build.
+async fn rune_page_from_build(champion: &str, build: &Value) -> Result<RunePageData, String> {
+    let rune_data = build.pointer("/data/runes").or_else(|| build.get("runes"))
+        .filter(|r| !r.is_null()).ok_or("Rune non trovate")?;
+    let ids = |key: &str| -> Vec<u32> {
+        rune_data[key].as_array().map(|a| a.iter().filter_map(|v| v.as_u64().map(|n| n as u32)).collect()).unwrap_or_default()
     };
-    // Per URF/ARAM l'API OP.GG ha un bug: non accetta nessun valore di position
-    // con game_mode=urf/aram. Workaround: usiamo sempre game_mode=ranked + posizione reale.
-    // L'item set sarà comunque utile perché gli item in URF sono gli stessi di ranked.
-    let opgg_mode = "ranked"; // sempre ranked — workaround bug API OP.GG
-    let champ_upper = champion_name.to_uppercase();
-    const MCP_URL: &str = "https://mcp-api.op.gg/mcp";
-
-    let init_res = client.post(MCP_URL)
-        .header("Content-Type","application/json")
-        .header("Accept","application/json, text/event-stream")
-        .json(&json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{
-            "protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"rlp","version":"1.0"}
-        }}))
-        .send().await.map_err(|e| format!("init error: {}", e))?;
-
-    let session_id = init_res.headers().get("mcp-session-id")
-        .and_then(|v| v.to_str().ok()).map(String::from);
-    let _ = init_res.text().await;
-
-    {
-        let mut r = client.post(MCP_URL).header("Content-Type","application/json");
-        if let Some(ref sid) = session_id { r = r.header("mcp-session-id", sid.as_str()); }
-        let _ = r.json(&json!({"jsonrpc":"2.0","method":"notifications/initialized"})).send().await;
-    }
-
-    // Costruisce gli arguments e invia la request, con retry per trovare il valore position corretto
-    let desired_fields = json!([
-        "data.summoner_spells.{ids,ids_names}",
-        "data.runes.{primary_page_id,primary_page_name,primary_rune_ids,secondary_page_id,secondary_page_name,secondary_rune_ids,stat_mod_ids}",
-        "data.starter_items[].{ids,ids_names}",
-        "data.core_items[].{ids,ids_names}",
-        "data.last_items[].{ids,ids_names}",
-        "data.fourth_items[].{ids,ids_names}",
-        "data.fifth_items[].{ids,ids_names}",
-        "data.sixth_items[].{ids,ids_names}",
-        "data.boots[].{ids,ids_names}",
-        "data.skills.{order}",
-        "data.mythic_items[].{ids,ids_names}"
-    ]);
-
-    let mut req = client.post(MCP_URL)
-        .header("Content-Type","application/json")
-        .header("Accept","application/json, text/event-stream");
-    if let Some(ref sid) = session_id { req = req.header("mcp-session-id", sid.as_str()); }
-
-    eprintln!("[RLP] MCP attempt: champion={} game_mode=ranked position={}", champ_upper, pos_ranked);
-
-    let mut args = serde_json::Map::new();
-    args.insert("champion".to_string(), json!(champ_upper));
-    args.insert("game_mode".to_string(), json!(opgg_mode));
-    args.insert("position".to_string(), json!(pos_ranked));
-    args.insert("lang".to_string(), json!("en_US"));
-    args.insert("desired_output_fields".to_string(), desired_fields.clone());
-
-    let res = req.json(&json!({
-        "jsonrpc":"2.0","id":2,"method":"tools/call",
-        "params":{"name":"lol_get_champion_analysis","arguments": Value::Object(args)}
-    })).send().await.map_err(|e| format!("HTTP error: {}", e))?;
-
-    let text = res.text().await.map_err(|e| e.to_string())?;
-
-    if let Ok(p) = serde_json::from_str::<Value>(&text) {
-        if let Some(err) = p.get("error") {
-            return Err(format!("MCP tool error: {}", err));
-        }
-    }
-
-    let mut text_content = String::new();
-    let last_err;
-    match mcp_extract_text(&text).await {
-        Some(tc) => {
-            eprintln!("[RLP] MCP success pos={}", pos_ranked);
-            text_content = tc;
-            last_err = String::new();
-        }
-        None => {
-            last_err = format!("No text in MCP response (pos={})", pos_ranked);
-            eprintln!("[RLP] {}", last_err);
-        }
-    }
-
-    if text_content.is_empty() {
-        return Err(if last_err.is_empty() { "Risposta MCP vuota".to_string() } else { last_err });
-    }
-
-    // Log full text_content in chunks
-    let mut _off = 0;
-    while _off < text_content.len() {
-        let _end = (_off + 500).min(text_content.len());
-        eprintln!("[RLP] tc[{}-{}]: {:?}", _off, _end, &text_content[_off.._end]);
-        _off = _end;
-    }
-    parse_opgg_response(&text_content)
-}
-
-fn extract_numbers(s: &str) -> Vec<u64> {
-    let mut nums = Vec::new();
-    let mut i = 0;
-    let bytes = s.as_bytes();
-    while i < bytes.len() {
-        if bytes[i].is_ascii_digit() {
-            let start = i;
-            while i < bytes.len() && bytes[i].is_ascii_digit() { i += 1; }
-            if let Ok(n) = s[start..i].parse::<u64>() { nums.push(n); }
-        } else { i += 1; }
-    }
-    nums
-}
-
-fn extract_array_content<'a>(s: &'a str, after: &str) -> Option<&'a str> {
-    let pos = s.find(after)?;
-    let rest = &s[pos + after.len()..];
-    let start = rest.find('[')?;
-    let mut depth = 0i32;
-    let chars: Vec<char> = rest[start..].chars().collect();
-    let mut end = 0;
-    for (i, &c) in chars.iter().enumerate() {
-        if c == '[' { depth += 1; } else if c == ']' { depth -= 1; if depth == 0 { end = i; break; } }
-    }
-    if end > 0 { Some(&rest[start..start+end+1]) } else { None }
-}
-
-fn parse_opgg_response(text: &str) -> Result<Value, String> {
-    let (primary_page_id, sub_page_id, primary_rune_ids, sub_rune_ids, stat_mod_ids) = {
-        // Formato attuale: Runes(primary_id,"primary_name",[primary_runes],sub_id,"sub_name",[sub_runes],[stat_mods])
-        // Es: Runes(8000,"Precision",[8008,9111,9103,8017],8300,"Inspiration",[8313,8321],[5005,5008,5011])
-        let runes_start = text.find("Runes(").ok_or("Runes( non trovato")?;
-        let runes_section = &text[runes_start + "Runes(".len()..];
-
-        // Trova tutti gli array [...] dentro Runes(...)
-        // Array[0] = primary_rune_ids, Array[1] = sub_rune_ids, Array[2] = stat_mod_ids
-        let mut arrays: Vec<Vec<u32>> = Vec::new();
-        let mut scalars_before: Vec<Vec<u64>> = Vec::new(); // scalari prima di ogni array
-        let mut pos = 0;
-        let rbytes = runes_section.as_bytes();
-        let mut current_scalars = String::new();
-        while pos < rbytes.len() && arrays.len() < 3 {
-            if rbytes[pos] == b'[' {
-                // Salva gli scalari accumulati prima di questo array
-                scalars_before.push(extract_numbers(&current_scalars));
-                current_scalars.clear();
-                // Leggi l'array
-                let start = pos + 1;
-                pos += 1;
-                while pos < rbytes.len() && rbytes[pos] != b']' { pos += 1; }
-                let arr_str = &runes_section[start..pos];
-                arrays.push(arr_str.split(',').filter_map(|x| x.trim().parse::<u32>().ok()).collect());
-                pos += 1; // salta ]
-            } else if rbytes[pos] == b')' {
-                break; // fine Runes(...)
-            } else {
-                current_scalars.push(rbytes[pos] as char);
-                pos += 1;
-            }
-        }
-
-        // scalars_before[0] = testo prima del primo array → contiene primary_page_id
-        // scalars_before[1] = testo tra primo e secondo array → contiene sub_page_id
-        let primary_page_id = scalars_before.get(0).and_then(|v| v.get(0)).copied().unwrap_or(8000) as u32;
-        let sub_page_id     = scalars_before.get(1).and_then(|v| v.get(0)).copied().unwrap_or(8300) as u32;
-        let primary_ids = arrays.get(0).cloned().unwrap_or_default();
-        let sec_ids     = arrays.get(1).cloned().unwrap_or_default();
-        let stat_ids    = arrays.get(2).cloned().unwrap_or_else(|| vec![5008,5008,5002]);
-
-        eprintln!("[RLP] runes parsed: primary={} sub={} primary_ids={:?} sub_ids={:?} stat={:?}",
-            primary_page_id, sub_page_id, primary_ids, sec_ids, stat_ids);
-
-        (primary_page_id, sub_page_id, primary_ids, sec_ids, stat_ids)
-    };
-
-    let summoner_spells: Value = {
-        fn spell_id_to_name(id: u64) -> &'static str {
-            match id {
-                4=>"Flash", 14=>"Ignite", 12=>"Teleport", 21=>"Barrier", 3=>"Exhaust",
-                6=>"Ghost", 7=>"Heal", 1=>"Cleanse", 11=>"Smite", 13=>"Clarity", 32=>"Mark",
-                _=>"Flash",
-            }
-        }
-        let mut names: Vec<Value> = Vec::new();
-        if let Some(pos) = text.find("SummonerSpells(") {
-            let section = &text[pos+"SummonerSpells(".len()..];
-            if let Some(bracket_start) = section.find('[') {
-                let bracket_section = &section[bracket_start..];
-                if let Some(bracket_end) = bracket_section.find(']') {
-                    let ids_str = &bracket_section[1..bracket_end];
-                    for id_str in ids_str.split(',') {
-                        if let Ok(id) = id_str.trim().parse::<u64>() { names.push(json!(spell_id_to_name(id))); }
-                    }
-                }
-            }
-        }
-        if names.len() >= 2 { json!([names[0].clone(), names[1].clone()]) } else { json!(["Flash","Heal"]) }
-    };
-
-    fn to_items(ids: &[u64]) -> Value {
-        Value::Array(ids.iter().map(|&id| json!({"id": id})).collect())
-    }
-
-    // Estrae tutti gli ID item da una stringa che contiene SummonerSpells(...)
-    fn extract_item_ids_from_group(group: &str) -> Vec<u64> {
-        let mut ids = Vec::new();
-        let mut search = group;
-        while let Some(pos) = search.find("SummonerSpells(") {
-            search = &search[pos + "SummonerSpells(".len()..];
-            if let Some(b_start) = search.find('[') {
-                let s = &search[b_start+1..];
-                if let Some(b_end) = s.find(']') {
-                    for x in s[..b_end].split(',') {
-                        if let Ok(id) = x.trim().parse::<u64>() {
-                            if id > 100 && id < 500000 { ids.push(id); }
-                        }
-                    }
-                }
-            }
-        }
-        ids
-    }
-
-    // ── Parser strutturale ────────────────────────────────────────────────────
-    // La risposta OP.GG ha questa struttura nel testo:
-    //   Data(
-    //     SummonerSpells([spells]),          ← summoner spells
-    //     Runes(...),                        ← rune
-    //     SummonerSpells([starter]),         ← starter items (blocco singolo)
-    //     SummonerSpells([core_1,core_2,..]),← core build principale (blocco singolo con più item)
-    //     [SummonerSpells([c]),..],          ← varianti core (gruppo tra [])
-    //     [SummonerSpells([d]),..],          ← 4th item options (gruppo tra [])
-    //     [SummonerSpells([e]),..],          ← 5th item options
-    //     [SummonerSpells([f]),..],          ← 6th item options
-    //     SummonerSpells([boots]),           ← boots (blocco singolo)
-    //     Skills([...]),                     ← skill order
-    //   )
-    //
-    // Strategia: troviamo la sezione Data(...) e separiamo i token top-level
-    // distinguendo blocchi singoli SummonerSpells(...) da gruppi [...].
-
-    let data_start = text.find("LolGetChampionAnalysis(").unwrap_or(0);
-    let data_section = &text[data_start..];
-
-    // Raccogli i "token" top-level dopo Runes(...):
-    // - "single": un SummonerSpells singolo  → starter, core, boots
-    // - "group":  una lista [...] di SummonerSpells → varianti core, 4th, 5th, 6th
-    #[derive(Debug)]
-    enum Token { Single(Vec<u64>), Group(Vec<u64>) }
-
-    let mut tokens: Vec<Token> = Vec::new();
-    let mut runes_end = 0;
-    // Salta fino a dopo Runes(...)
-    if let Some(rp) = data_section.find("Runes(") {
-        let mut depth = 0i32;
-        let bytes = data_section[rp..].as_bytes();
-        for (i, &b) in bytes.iter().enumerate() {
-            if b == b'(' { depth += 1; }
-            else if b == b')' { depth -= 1; if depth == 0 { runes_end = rp + i + 1; break; } }
-        }
-    }
-    let after_runes = if runes_end > 0 { &data_section[runes_end..] } else { data_section };
-
-    // Scansione carattere per carattere dei token top-level
-    let bytes = after_runes.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        match bytes[i] {
-            b'[' => {
-                // Gruppo: trova il ] di chiusura al livello 0
-                let start = i + 1;
-                let mut depth = 1i32;
-                i += 1;
-                while i < bytes.len() && depth > 0 {
-                    if bytes[i] == b'[' { depth += 1; }
-                    else if bytes[i] == b']' { depth -= 1; }
-                    i += 1;
-                }
-                let group_str = &after_runes[start..i-1];
-                let ids = extract_item_ids_from_group(group_str);
-                if !ids.is_empty() { tokens.push(Token::Group(ids)); }
-            }
-            b'S' if after_runes[i..].starts_with("StarterItems(") || after_runes[i..].starts_with("SummonerSpells(") => {
-                // Blocco singolo: StarterItems( oppure SummonerSpells( fuori da gruppi []
-                // Determina la lunghezza del prefisso
-                let prefix_len = if after_runes[i..].starts_with("StarterItems(") {
-                    "StarterItems(".len()
-                } else {
-                    "SummonerSpells(".len()
-                };
-                let sp_start = i + prefix_len;
-                if let Some(bracket) = after_runes[sp_start..].find('[') {
-                    let s = &after_runes[sp_start + bracket + 1..];
-                    if let Some(end) = s.find(']') {
-                        let ids: Vec<u64> = s[..end].split(',')
-                            .filter_map(|x| x.trim().parse::<u64>().ok())
-                            .filter(|&id| id > 100 && id < 500000)
-                            .collect();
-                        if !ids.is_empty() { tokens.push(Token::Single(ids)); }
-                    }
-                }
-                // Avanza oltre questo blocco
-                while i < bytes.len() && bytes[i] != b')' { i += 1; }
-                i += 1;
-            }
-            b'S' if after_runes[i..].starts_with("Skills(") => { break; } // fine dati item
-            _ => { i += 1; }
-        }
-    }
-
-    eprintln!("[RLP] tokens strutturali: {:?}", tokens);
-
-    // Classifica i token:
-    // Single[0] = starter, Single[1] = core principale, Single[last] = boots
-    // Group[0] = varianti core, Group[1] = 4th, Group[2] = 5th, Group[3] = 6th
-    let singles: Vec<&Vec<u64>> = tokens.iter().filter_map(|t| if let Token::Single(v) = t { Some(v) } else { None }).collect();
-    let groups:  Vec<&Vec<u64>> = tokens.iter().filter_map(|t| if let Token::Group(v)  = t { Some(v) } else { None }).collect();
-
-    let empty: Vec<u64> = vec![];
-    let starter = singles.get(0).copied().unwrap_or(&empty);
-    let core    = singles.get(1).copied().unwrap_or(&empty);
-    let boots   = singles.last().filter(|_| singles.len() > 2).copied().unwrap_or(&empty);
-
-    // Gruppi: [0]=varianti core (mostriamo come "Core Variants"),
-    //         [1]=4th, [2]=5th, [3]=6th
-    // slot_labels: etichette per i gruppi OP.GG
-    // Group[0] = varianti core (3rd item), Group[1] = 4th, Group[2] = 5th, Group[3] = 6th
-    let slot_labels = [("3rd Item Options", 0usize), ("4th Item", 1), ("5th Item", 2), ("6th Item", 3)];
-    let mut slots: Vec<Value> = Vec::new();
-    // Teniamo traccia di tutti gli ID già usati nei blocchi precedenti per evitare duplicati
-    let mut seen_ids: std::collections::HashSet<u64> = std::collections::HashSet::new();
-    // Aggiungi gli ID di starter e core come "già visti"
-    for &id in starter.iter().chain(core.iter()).chain(boots.iter()) { seen_ids.insert(id); }
-
-    for (label, idx) in &slot_labels {
-        if let Some(raw_ids) = groups.get(*idx) {
-            // Filtra gli ID già presenti nei blocchi precedenti
-            let ids: Vec<u64> = raw_ids.iter().copied().filter(|id| !seen_ids.contains(id)).collect();
-            if !ids.is_empty() {
-                for &id in &ids { seen_ids.insert(id); }
-                slots.push(json!({"label": label, "items": to_items(&ids)}));
-            }
-        }
-    }
-
-    eprintln!("[RLP] starter={:?} core={:?} boots={:?} slots={}", starter, core, boots, slots.len());
-
-    // Parse skill order
-    let skill_order = {
-        let mut order = String::new();
-        if let Some(pos) = text.find("Skills(") {
-            let section = &text[pos + "Skills(".len()..];
-            if let Some(end) = section.find(')') {
-                let inner = &section[..end];
-                let mut seen: Vec<String> = Vec::new();
-                for token in inner.split(|c: char| !c.is_alphanumeric()) {
-                    let t = token.trim().to_uppercase();
-                    if (t == "Q" || t == "W" || t == "E") && !seen.contains(&t) {
-                        seen.push(t);
-                        if seen.len() == 3 { break; }
-                    }
-                }
-                if seen.len() == 3 {
-                    order = format!("{} → {} → {}", seen[0], seen[1], seen[2]);
-                }
-                eprintln!("[RLP] skill_order inner={:?} → {:?}", &inner[..inner.len().min(80)], order);
-            }
-        }
-        order
+    let mut rune_page = RunePageData {
+        champion_name: champion.to_string(),
+        primary_path_id: rune_data["primary_page_id"].as_u64().map(|n| n as u32).unwrap_or(8000),
+        primary_path_name: String::new(),
+        primary_rune_ids: ids("primary_rune_ids"),
+        secondary_path_id: rune_data["sub_page_id"].as_u64().map(|n| n as u32).unwrap_or(8100),
+        secondary_path_name: String::new(),
+        secondary_rune_ids: ids("sub_rune_ids"),
+        stat_mod_ids: ids("stat_mod_ids"),
     };
-
-    Ok(json!({
-        "data": {
-            "runes": {
-                "primary_page_id": primary_page_id, "sub_page_id": sub_page_id,
-                "primary_rune_ids": primary_rune_ids, "sub_rune_ids": sub_rune_ids,
-                "stat_mod_ids": stat_mod_ids
-            },
-            "summoner_spells": summoner_spells,
-            "starter_items":   to_items(starter),
-            "core_items":      to_items(core),
-            "situ_slots":      Value::Array(slots),
-            "boots":           to_items(boots),
-            "skill_order":     skill_order
-        }
-    }))
+    let fixes = rune_validator::validate(&mut rune_page, &*static_data().await?)?;
+    if !fixes.is_empty() { eprintln!("[RLP] {} rune corrette: {:?}", champion, fixes); }
+    Ok(rune_page)
 }
 
-async fn import_runes(lcu: &LcuClient, champion: &str, position: &str, build: &Value) -> Result<(String, String, RunePageData), String> {
-    let rune_data = build.pointer("/data/runes").or_else(|| build.get("runes")).ok_or("Rune non trovate")?;
-    let primary_id = rune_data["primary_page_id"].as_u64().map(|n| n as u32).unwrap_or(8000);
-    let sub_id = rune_data["sub_page_id"].as_u64().map(|n| n as u32).unwrap_or(8100);
-    let primary_path_name = path_id_to_name(primary_id).to_string();
-    let sub_path_name = path_id_to_name(sub_id).to_string();
-    let primary_ids: Vec<u32> = rune_data["primary_rune_ids"].as_array().unwrap_or(&vec![]).iter().filter_map(|v| v.as_u64().map(|n| n as u32)).collect();
-    let sub_ids: Vec<u32> = rune_data["sub_rune_ids"].as_array().unwrap_or(&vec![]).iter().filter_map(|v| v.as_u64().map(|n| n as u32)).collect();
-    let stat_ids: Vec<u32> = rune_data["stat_mod_ids"].as_array().map(|a| a.iter().filter_map(|v| v.as_u64().map(|n| n as u32)).collect()).unwrap_or_else(|| vec![5008,5008,5002]);
-    
-    let page_name = format!("RLP {} {}", champion, position);
-    let pages: Vec<Value> = lcu.get("/lol-perks/v1/pages").await.and_then(|v| v.as_array().cloned()).unwrap_or_default();
-    let editable: Vec<&Value> = pages.iter().filter(|p| p["isDeletable"].as_bool().unwrap_or(false)).collect();
-    let del_id = editable.iter().find(|p| p["name"].as_str().unwrap_or("")==page_name).or_else(|| editable.first()).and_then(|p| p["id"].as_u64());
-    
-    if let Some(id) = del_id {
-        let _ = lcu.client.delete(&format!("https://127.0.0.1:{}/lol-perks/v1/pages/{}", lcu.port, id))
-            .header("Authorization", format!("Basic {}", lcu.auth)).send().await;
-    }
-    
-    let mut perks = Vec::new();
-    perks.extend_from_slice(&primary_ids);
-    perks.extend_from_slice(&sub_ids);
-    perks.extend_from_slice(&stat_ids);
-    
-    let status = lcu.post_status("/lol-perks/v1/pages", &json!({
-        "name": page_name, "primaryStyleId": primary_id, "subStyleId": sub_id,
-        "selectedPerkIds": perks, "current": true
-    })).await.map_err(|e| format!("HTTP error rune page: {}", e))?;
+async fn import_runes(lcu: &LcuClient, champion: &str, position: &str, rune_page: RunePageData) -> Result<(String, String, RunePageData), String> {
+    let page_name = format!("{}{} {}", rune_pages::PAGE_PREFIX, champion, position);
+    // Ricicla solo pagine RLP: le pagine dell'utente non si toccano mai in automatico
+    rune_pages::save_page(lcu, &page_name, rune_page.primary_path_id, rune_page.secondary_path_id, &rune_page.perk_ids(), false).await?;
 
-    eprintln!("[RLP] import_runes POST status={}", status);
-    if status < 200 || status >= 300 {
-        return Err(format!("Il client ha rifiutato la pagina rune (HTTP {})", status));
-    }
-
-    let rune_page = RunePageData {
-        champion_name: champion.to_string(),
-        primary_path_id: primary_id,
-        primary_path_name: primary_path_name.clone(),
-        primary_rune_ids: primary_ids,
-        secondary_path_id: sub_id,
-        secondary_path_name: sub_path_name,
-        secondary_rune_ids: sub_ids,
-        stat_mod_ids: stat_ids,
-    };
+    let primary_path_name = rune_page.primary_path_name.clone();
     Ok((page_name, primary_path_name, rune_page))
 }
 
 /// Tauri command: applica una pagina rune già costruita nel client LoL.
 /// Usato dal frontend quando l'utente cambia tab OP.GG ↔ Dataset RLP.
+/// `replace_active`: a limite pagine raggiunto sostituisce la pagina attiva
+/// dell'utente (dopo conferma esplicita nel frontend, con backup automatico).
 #[tauri::command]
-pub async fn apply_rune_page(rune_data: RunePageData) -> Result<(), String> {
-    let lcu = LcuClient::new().ok_or("Client LoL non disponibile")?;
-
-    let page_name = format!("RLP {} {} {}", rune_data.champion_name, rune_data.primary_path_name, rune_data.secondary_path_name);
-
-    // Elimina la pagina esistente con lo stesso nome (o la prima deletable)
-    let pages: Vec<Value> = lcu.get("/lol-perks/v1/pages")
-        .await
-        .and_then(|v| v.as_array().cloned())
-        .unwrap_or_default();
-    let editable: Vec<&Value> = pages.iter()
-        .filter(|p| p["isDeletable"].as_bool().unwrap_or(false))
-        .collect();
-    let del_id = editable.iter()
-        .find(|p| p["name"].as_str().unwrap_or("") == page_name)
-        .or_else(|| editable.first())
-        .and_then(|p| p["id"].as_u64());
-
-    if let Some(id) = del_id {
-        let _ = lcu.client
-            .delete(&format!("https://127.0.0.1:{}/lol-perks/v1/pages/{}", lcu.port, id))
-            .header("Authorization", format!("Basic {}", lcu.auth))
-            .send().await;
-    }
-
-    // Il LCU richiede esattamente 9 selectedPerkIds:
-    //   [keystone, p_slot1, p_slot2, p_slot3,  ← 4 primary
-    //    s_slot1, s_slot2,                      ← 2 secondary
-    //    stat_off, stat_flex, stat_def]          ← 3 stat mods
-    //
-    // Le build DB hanno solo keystone (primary_rune_ids=[keystone]) e 2 secondary.
-    // I 3 slot primari mancanti e i 3 stat mods si paddano con 0 / default.
-
-    // Primary: keystone + 3 slot (0 = non selezionato, accettato dal LCU)
-    let mut primary_perks = rune_data.primary_rune_ids.clone();
-    while primary_perks.len() < 4 { primary_perks.push(0); }
-
-    // Secondary: 2 slot (0 se mancanti)
-    let mut secondary_perks = rune_data.secondary_rune_ids.clone();
-    while secondary_perks.len() < 2 { secondary_perks.push(0); }
-
-    // Stat mods: 3 slot — default Adaptive/Adaptive/Armor se non specificati
-    let stat_perks: Vec<u32> = if rune_data.stat_mod_ids.len() >= 3 {
-        rune_data.stat_mod_ids[..3].to_vec()
-    } else {
-        vec![5008, 5008, 5002] // Adaptive Force, Adaptive Force, Armor
-    };
-
-    let mut perks: Vec<u32> = Vec::with_capacity(9);
-    perks.extend_from_slice(&primary_perks[..4]);
-    perks.extend_from_slice(&secondary_perks[..2]);
-    perks.extend_from_slice(&stat_perks[..3]);
-
+pub async fn apply_rune_page(lcu: State<'_, LcuConnection>, rune_data: RunePageData, replace_active: Option<bool>) -> Result<(), String> {
+    let lcu = lcu.client().ok_or("Client LoL non disponibile")?;
+
+    // Il LCU richiede esattamente 9 selectedPerkIds validi (4 primary, 2 secondary, 3 shard).
+    // Le build locali possono avere righe incomplete: il validatore completa le righe
+    // mancanti con la scelta più comune della patch corrente.
+    let mut rune_data = rune_data;
+    let fixes = rune_validator::validate(&mut rune_data, &*static_data().await?)?;
+    if !fixes.is_empty() { eprintln!("[RLP] apply_rune_page corretta: {:?}", fixes); }
+
+    let page_name = format!("{}{} {} {}", rune_pages::PAGE_PREFIX, rune_data.champion_name, rune_data.primary_path_name, rune_data.secondary_path_name);
+    let perks = rune_data.perk_ids();
     eprintln!("[RLP] apply_rune_page '{}' perks={:?}", page_name, perks);
 
-    let status = lcu.post_status("/lol-perks/v1/pages", &json!({
-        "name": page_name,
-        "primaryStyleId": rune_data.primary_path_id,
-        "subStyleId": rune_data.secondary_path_id,
-        "selectedPerkIds": perks,
-        "current": true,
-    })).await.map_err(|e| format!("HTTP error apply_rune_page: {}", e))?;
-
-    eprintln!("[RLP] apply_rune_page '{}' → HTTP {}", page_name, status);
-    if status < 200 || status >= 300 {
-        return Err(format!("Il client ha rifiutato la pagina rune (HTTP {})", status));
-    }
+    rune_pages::save_page(&lcu, &page_name, rune_data.primary_path_id, rune_data.secondary_path_id,
+        &perks, replace_active.unwrap_or(false)).await?;
     Ok(())
 }
 
-async fn import_summoners(lcu: &LcuClient, build: &Value) -> Result<Vec<String>,String> {
-    let spells_raw = build.pointer("/data/summoner_spells").and_then(|v| v.as_array()).ok_or("Summoner spells non trovate")?;
-    let spell_names: Vec<String> = spells_raw.iter().filter_map(|v| v.as_str().map(String::from)).collect();
-    if spell_names.len() < 2 { return Err(format!("Meno di 2 spell")); }
-    let id1 = spell_name_to_id(&spell_names[0]).unwrap_or(4);
-    let id2 = spell_name_to_id(&spell_names[1]).unwrap_or(7);
-    
-    let resp = lcu.client.patch(&format!("https://127.0.0.1:{}/lol-champ-select/v1/session/my-selection", lcu.port))
-        .header("Authorization", format!("Basic {}", lcu.auth)).header("Content-Type","application/json")
-        .json(&json!({"spell1Id":id1,"spell2Id":id2})).send().await.map_err(|e| e.to_string())?;
-        
-    if resp.status().is_success() { Ok(spell_names) } else { Err("Il client ha rifiutato il cambio spell".to_string()) }
-}
-
-async fn import_item_set(_lcu: &LcuClient, champion: &str, position: &str, build: &Value, _puuid: &str, label_override: Option<&str>) -> Result<usize,String> {
+/// Item set della build: uno per campione e modalità (e lane nel ranked), riscritto
+/// ad ogni import. Va nell'account via LCU, o in Config/ se l'endpoint non risponde
+/// (item_sets.rs). Restituisce i blocchi e dove è stato scritto.
+async fn import_item_set(lcu: &LcuClient, summoner_id: Option<u64>, champion: &str, mode: &str, position: &str, title: String, build: &Value) -> Result<(usize, &'static str),String> {
     let mut blocks: Vec<Value> = Vec::new();
 
     let skill_order = build.pointer("/data/skill_order")
@@ -1134,9 +214,17 @@ async fn import_item_set(_lcu: &LcuClient, champion: &str, position: &str, build
     };
 
     // 1. Starter Items
-    if let Some(arr) = build.pointer("/data/starter_items").and_then(|v| v.as_array()) {
-        let ids: Vec<u64> = arr.iter().filter_map(|v| v["id"].as_u64()).collect();
-        if let Some(b) = make_block("Starter Items", ids) { blocks.push(b); }
+    let starter: Vec<u64> = build.pointer("/data/starter_items").and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v["id"].as_u64()).collect())
+        .unwrap_or_default();
+    if let Some(b) = make_block("Starter Items", starter.clone()) { blocks.push(b); }
+
+    // 1b. Sequenza abilità 1→18 nel titolo di un blocco a parte. Il client non
+    // mostra blocchi senza item: si riusa il primo starter (o la pozione).
+    if let Some(plan) = SkillPlan::from_build(build) {
+        let carrier = starter.first().copied().unwrap_or(2003);
+        let label = format!("Abilità 1→{}: {}", plan.levels.len(), plan.sequence_label());
+        if let Some(b) = make_block(&label, vec![carrier]) { blocks.push(b); }
     }
 
     // 2. Core Build + skill order
@@ -1146,7 +234,7 @@ async fn import_item_set(_lcu: &LcuClient, champion: &str, position: &str, build
     }
 
     // 3. Slot situazionali: Core Variants → 4th → 5th → 6th
-    // Ordine finale: Starter → Core | skill order → Core Variants → 4th → 5th → 6th → Boots
+    // Ordine finale: Starter → Abilità → Core | skill order → Core Variants → 4th → 5th → 6th → Boots
     if let Some(situ_slots) = build.pointer("/data/situ_slots").and_then(|v| v.as_array()) {
         for slot in situ_slots {
             let label = slot["label"].as_str().unwrap_or("Situational");
@@ -1166,135 +254,61 @@ async fn import_item_set(_lcu: &LcuClient, champion: &str, position: &str, build
     if blocks.is_empty() { return Err("Nessun item trovato".to_string()); }
     let count = blocks.len();
 
-    let set_title = label_override.map(String::from).unwrap_or_else(|| format!("RLP {} {}", champion, position));
-    let item_set = json!({
-        "title": set_title,
-        "type": "custom",
-        "map": "any",
-        "mode": "any",
-        "priority": false,
-        "sortrank": 1,
-        "blocks": blocks
-    });
-
-    // Usa il path dinamico invece del path hardcoded
-    let config_path = get_league_config_path()
-        .ok_or("Cartella League of Legends non trovata su nessun drive (C:-Z:)")?
-        .join(champion)
-        .join("Recommended");
-
-    fs::create_dir_all(&config_path).map_err(|e| format!("mkdir error: {}", e))?;
-    let filename = label_override
-        .map(|l| format!("RLP_{}.json", l.replace(' ', "_").replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_")))
-        .unwrap_or_else(|| "RLP.json".to_string());
-    let file_path = config_path.join(&filename);
-    let json_str = serde_json::to_string_pretty(&item_set).map_err(|e| e.to_string())?;
-    fs::write(&file_path, &json_str).map_err(|e| format!("write error: {}", e))?;
-
-    eprintln!("[RLP] item-set written: {:?} ({} blocks)", file_path, count);
-    Ok(count)
+    // Senza chiave il set finirebbe senza associatedChampions, cioè valido per tutti
+    let (champion_id, champion_key) = static_data().await?.champions.find(champion)
+        .map(|c| (c.id.clone(), c.key))
+        .ok_or(format!("Campione sconosciuto: '{}'", champion))?;
+    let set = ItemSet {
+        title,
+        champion: Some(champion_id),
+        associated_champions: Vec::new(),
+        mode: mode.to_string(),
+        position: if mode == "ranked" { position.to_string() } else { String::new() },
+        blocks,
+    };
+    let target = item_sets::write(Some(lcu), summoner_id, &set, Some(champion_key)).await?;
+    Ok((count, target))
 }
 
-/// Costruisce il JSON di un item set dal DB (lista flat di item id).
-// fn build_db_item_set_value(champion: &str, title: &str, item_ids: &[u32]) -> Value {
-//     let _ = champion;
-//     let items: Vec<Value> = item_ids.iter()
-//         .map(|&id| json!({"id": id.to_string(), "count": 1}))
-//         .collect();
-//     json!({
-//         "title": title,
-//         "type": "custom",
-//         "map": "any",
-//         "mode": "any",
-//         "priority": false,
-//         "sortrank": 2,
-//         "blocks": [{
-//             "type": "Build DB (RLP dataset)",
-//             "recMath": false,
-//             "minSummonerLevel": -1,
-//             "maxSummonerLevel": -1,
-//             "showIfSummonerSpell": "",
-//             "hideIfSummonerSpell": "",
-//             "items": items
-//         }]
-//     })
-// }
-
-/// Scrive un item set JSON nella cartella League Config/Champions/<champion>/Recommended/.
-// async fn write_item_set_file(champion: &str, item_set: &Value, label: &str) -> Result<(), String> {
-//     let config_path = get_league_config_path()
-//         .ok_or("Cartella League of Legends non trovata")?
-//         .join(champion)
-//         .join("Recommended");
-//     fs::create_dir_all(&config_path).map_err(|e| format!("mkdir error: {}", e))?;
-//     let filename = format!("RLP_{}.json",
-//         label.replace(' ', "_").replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_"));
-//     let file_path = config_path.join(&filename);
-//     let json_str = serde_json::to_string_pretty(item_set).map_err(|e| e.to_string())?;
-//     fs::write(&file_path, &json_str).map_err(|e| format!("write error: {}", e))?;
-//     eprintln!("[RLP DB] item-set scritto: {:?}", file_path);
-//     Ok(())
-// }
-
-/// Per URF/ARAM: fetch parallelo delle build per le 4 lane principali usando ranked come workaround.
-/// Restituisce un Vec<(lane_label, build_value)> per le lane che hanno risposto con successo.
-async fn opgg_get_builds_multi_lane(champion_name: &str) -> Vec<(String, Value)> {
-    let lanes = [
-        ("MID",     "mid"),
-        ("TOP",     "top"),
-        ("JUNGLE",  "jungle"),
-        ("ADC",     "adc"),
-        ("SUPPORT", "support"),
-    ];
-
-    let mut handles = Vec::new();
-    for (label, pos) in &lanes {
-        let champ = champion_name.to_string();
-        let label = label.to_string();
-        let pos   = pos.to_string();
-        handles.push(tokio::spawn(async move {
-            match opgg_get_champion_build(&champ, &pos, "ranked").await {
-                Ok(build) => Some((label, build)),
-                Err(e) => {
-                    eprintln!("[RLP] multi-lane {} fetch failed: {}", pos, e);
-                    None
-                }
-            }
-        }));
-    }
-
-    let mut results = Vec::new();
-    for h in handles {
-        if let Ok(Some(pair)) = h.await { results.push(pair); }
+impl ChampSelectSession {
+    pub fn none() -> Self {
+        ChampSelectSession { in_progress:false, champion_name:String::new(), assigned_position:String::new(), game_mode:String::new(), locked:false }
     }
-    results
 }
 
 #[tauri::command]
-pub async fn get_champ_select_session() -> Result<ChampSelectSession, String> {
-    let lcu = LcuClient::new().ok_or("CLIENT_CLOSED")?;
+pub async fn get_champ_select_session(lcu: State<'_, LcuConnection>) -> Result<ChampSelectSession, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
     let session: Value = lcu.get("/lol-champ-select/v1/session").await.ok_or("Nessuna champion select attiva")?;
+    summarize_session(&lcu, &session).await
+}
+
+/// Riassume la sessione LCU (`/lol-champ-select/v1/session`, da GET o da evento websocket).
+pub async fn summarize_session(lcu: &LcuClient, session: &Value) -> Result<ChampSelectSession, String> {
     if session.get("errorCode").is_some() {
-        return Ok(ChampSelectSession { in_progress:false, champion_name:String::new(), assigned_position:String::new(), game_mode:String::new() });
+        return Ok(ChampSelectSession::none());
     }
     let my_cell = session["localPlayerCellId"].as_i64().unwrap_or(-1);
     let my_slot = session["myTeam"].as_array().and_then(|team| team.iter().find(|p| p["cellId"].as_i64()==Some(my_cell)));
     let assigned_position = my_slot.and_then(|s| s["assignedPosition"].as_str()).unwrap_or("MIDDLE").to_uppercase();
     let mut champ_id = my_slot.and_then(|s| s["championId"].as_u64().or_else(|| s["championPickIntent"].as_u64())).unwrap_or(0);
-    
+    let my_picks: Vec<&Value> = session["actions"].as_array().map(|phases|
+        phases.iter().filter_map(|p| p.as_array()).flatten()
+            .filter(|a| a["actorCellId"].as_i64()==Some(my_cell) && a["type"].as_str()==Some("pick"))
+            .collect()
+    ).unwrap_or_default();
+
     if champ_id == 0 {
-        if let Some(phases) = session["actions"].as_array() {
-            'outer: for phase in phases {
-                if let Some(actions) = phase.as_array() {
-                    for action in actions {
-                        if action["actorCellId"].as_i64()==Some(my_cell) && action["type"].as_str()==Some("pick") {
-                            if let Some(id) = action["championId"].as_u64() { if id!=0 { champ_id=id; break 'outer; } }
-                        }
-                    }
-                }
-            }
+        if let Some(id) = my_picks.iter().filter_map(|a| a["championId"].as_u64()).find(|&id| id != 0) {
+            champ_id = id;
         }
     }
+    // ARAM/URF random: nessuna azione "pick", il campione è già assegnato (e cambia con reroll/bench)
+    let locked = champ_id != 0 && if my_picks.is_empty() {
+        my_slot.and_then(|s| s["championId"].as_u64()).unwrap_or(0) != 0
+    } else {
+        my_picks.iter().any(|a| a["completed"].as_bool() == Some(true))
+    };
     // Legge il game mode dalla sessione LCU (gameConfig.gameMode)
     let game_config: Value = lcu.get("/lol-gameflow/v1/session").await.unwrap_or(json!({}));
     let raw_mode = game_config
@@ -1308,28 +322,24 @@ pub async fn get_champ_select_session() -> Result<ChampSelectSession, String> {
         "URF" | "ONEFORALL"      => "urf",
         "ARURF"                  => "urf",
         "ULTBOOK"                => "urf",  // Ultimate Spellbook — usa build URF
+        "CHERRY"                 => "arena",
         _                        => "ranked",
     }.to_string();
     eprintln!("[RLP] gameMode LCU raw={} → {}", raw_mode, game_mode);
 
     if champ_id == 0 {
-        return Ok(ChampSelectSession { in_progress:true, champion_name:String::new(), assigned_position, game_mode });
+        return Ok(ChampSelectSession { in_progress:true, champion_name:String::new(), assigned_position, game_mode, locked:false });
     }
     
-    let http = Client::builder().danger_accept_invalid_certs(true).build().unwrap();
-    let champs: Value = http.get(&format!("https://ddragon.leagueoflegends.com/cdn/{}/data/en_US/champion.json", PATCH))
-        .send().await.map_err(|e| e.to_string())?.json().await.map_err(|e| e.to_string())?;
-        
-    let champion_name = champs["data"].as_object()
-        .and_then(|map| map.values().find(|c| c["key"].as_str().map(|k| k==champ_id.to_string()).unwrap_or(false)))
-        .and_then(|c| c["id"].as_str()).unwrap_or("Unknown").to_string();
-        
-    Ok(ChampSelectSession { in_progress:true, champion_name, assigned_position, game_mode })
+    let champion_name = static_data().await?.champions.get(champ_id as u32)
+        .map(|c| c.id.clone()).unwrap_or_else(|| "Unknown".to_string());
+
+    Ok(ChampSelectSession { in_progress:true, champion_name, assigned_position, game_mode, locked })
 }
 
 #[tauri::command]
-pub async fn debug_champ_select_slot() -> Result<Value, String> {
-    let lcu = LcuClient::new().ok_or("CLIENT_CLOSED")?;
+pub async fn debug_champ_select_slot(lcu: State<'_, LcuConnection>) -> Result<Value, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
     let session: Value = lcu.get("/lol-champ-select/v1/session").await.ok_or("Nessuna champion select attiva")?;
     if session.get("errorCode").is_some() { return Err("Non in champion select".to_string()); }
     let my_cell = session["localPlayerCellId"].as_i64().unwrap_or(-1);
@@ -1344,23 +354,42 @@ pub async fn debug_champ_select_slot() -> Result<Value, String> {
 }
 
 #[tauri::command]
-pub async fn auto_import_build(champion_name: String, assigned_position: String, game_mode: String) -> Result<ImportResult, String> {
-    eprintln!("[RLP] auto_import_build: {} {} mode={}", champion_name, assigned_position, game_mode);
-    let mut result = ImportResult {
-        runes_imported:false, summoners_imported:false, items_imported:false,
-        rune_page_name:None, primary_path:None, summoner_spells:Vec::new(), item_blocks:None,
-        errors:Vec::new(), opgg_runes: None, /* db_build: None, */
-    };
-    
-    let lcu = match LcuClient::new() {
+/// `source`: sorgente da importare per questa chiamata ("opgg", "local", "personal"),
+/// altrimenti quella della policy.
+pub async fn auto_import_build(lcu: State<'_, LcuConnection>, champion_name: String, assigned_position: String, game_mode: String, source: Option<String>) -> Result<ImportResult, String> {
+    let lcu = match lcu.client() {
         Some(c) => c,
-        None => { result.errors.push("Client LoL non disponibile".to_string()); return Ok(result); }
+        None => {
+            let mut result = ImportResult::empty();
+            result.errors.push("Client LoL non disponibile".to_string());
+            return Ok(result);
+        }
     };
+    Ok(import_build(&lcu, &champion_name, &assigned_position, &game_mode, source.as_deref()).await)
+}
+
+impl ImportResult {
+    fn empty() -> Self {
+        ImportResult {
+            runes_imported:false, summoners_imported:false, items_imported:false,
+            rune_page_name:None, primary_path:None, summoner_spells:Vec::new(), summoners_skipped: None, spell_fixes: Vec::new(), item_blocks:None,
+            item_set_target: None, errors:Vec::new(), opgg_runes: None, build_source: None, build_mode: None, augments: Vec::new(),
+            skill_plan: None, builds: Vec::new(),
+        }
+    }
+}
+
+/// Import completo (rune, summoner spells, item set) per il campione in champ select.
+/// Usato dal comando `auto_import_build` e dall'import automatico (auto_import.rs).
+pub async fn import_build(lcu: &LcuClient, champion_name: &str, assigned_position: &str, game_mode: &str, source: Option<&str>) -> ImportResult {
+    eprintln!("[RLP] auto_import_build: {} {} mode={}", champion_name, assigned_position, game_mode);
+    let mut result = ImportResult::empty();
 
     // Normalizza game_mode (il frontend manda quello che viene dalla sessione)
     let mode = match game_mode.to_lowercase().as_str() {
         "aram"  => "aram",
         "urf" | "arurf" | "ultbook" | "oneforall" => "urf",
+        "arena" | "cherry" => "arena",
         _       => "ranked",
     };
 
@@ -1368,29 +397,75 @@ pub async fn auto_import_build(champion_name: String, assigned_position: String,
     let mode_label = match mode {
         "aram"   => "ARAM",
         "urf"    => "URF",
-        _        => &assigned_position,
+        "arena"  => "Arena",
+        _        => assigned_position,
     };
     
     let summoner: Value = lcu.get("/lol-summoner/v1/current-summoner").await.unwrap_or(json!({}));
     let puuid = summoner["puuid"].as_str().unwrap_or("").to_string();
-    eprintln!("[RLP] summoner puuid={}", puuid);
-
-    // // ── Fetch build dal DB Turso (in parallelo con OP.GG) ────────────────────
-    // let db_champion_name = champion_name.clone();
-    // let db_position = assigned_position.clone();
-    // let db_handle = tokio::spawn(async move {
-    //     let champ_id = turso_get_champion_id(&db_champion_name).await?;
-    //     turso_get_db_build(champ_id, &db_position).await
-    // });
+    let summoner_id = summoner["summonerId"].as_u64().filter(|&id| id > 0);
+    eprintln!("[RLP] summoner puuid={} id={:?}", puuid, summoner_id);
+
+    // ── Build da tutte le sorgenti (in parallelo), nell'ordine della policy ──
+    let mut policy = build_provider::policy();
+    if let Some(source) = source { policy.primary = source.to_string(); }
+    let req = BuildRequest {
+        champion_name: champion_name.to_string(),
+        position: assigned_position.to_string(),
+        mode,
+        puuid: puuid.clone(),
+    };
+    let fetched = build_provider::fetch_all(&req, &policy).await;
+    // Si importa la primaria; con il fallback attivo la prima sorgente che ha risposto
+    let chosen = fetched.iter().enumerate().find_map(|(i, (provider, r))| match r {
+        Ok(pb) if i == 0 || policy.fallback => Some((i, provider, pb)),
+        _ => None,
+    });
 
-    if mode == "ranked" || mode == "flex" {
-        // ── RANKED / FLEX: build singola per la lane assegnata ────────────────
-        let build = match opgg_get_champion_build(&champion_name, &assigned_position, mode).await {
-            Ok(b) => b,
-            Err(e) => { result.errors.push(format!("OP.GG fetch fallito: {}", e)); return Ok(result); }
+    for (i, (provider, fetched_build)) in fetched.iter().enumerate() {
+        let source = match fetched_build {
+            Ok(pb) => SourceBuild {
+                source: provider.id().to_string(),
+                label: provider.label().to_string(),
+                build_mode: Some(pb.mode.clone()),
+                runes: rune_page_from_build(champion_name, &pb.build).await.ok(),
+                summoner_spells: pb.build.pointer("/data/summoner_spells").and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect()).unwrap_or_default(),
+                core_items: pb.build.pointer("/data/core_items").and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v["id"].as_u64()).collect()).unwrap_or_default(),
+                skill_order: pb.build.pointer("/data/skill_order").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                skill_plan: SkillPlan::from_build(&pb.build),
+                sample_size: pb.sample_size,
+                win_rate: pb.win_rate,
+                imported: chosen.is_some_and(|(c, _, _)| c == i),
+                error: None,
+            },
+            Err(e) => SourceBuild {
+                source: provider.id().to_string(), label: provider.label().to_string(),
+                build_mode: None, runes: None, summoner_spells: Vec::new(), core_items: Vec::new(),
+                skill_order: String::new(), skill_plan: None, sample_size: None, win_rate: None, imported: false,
+                error: Some(e.clone()),
+            },
         };
+        result.builds.push(source);
+    }
 
-        match import_runes(&lcu, &champion_name, mode_label, &build).await {
+    let Some((idx, provider, pb)) = chosen else {
+        let reasons: Vec<String> = result.builds.iter()
+            .filter_map(|b| b.error.as_ref().map(|e| format!("{}: {}", b.label, e)))
+            .collect();
+        result.errors.push(format!("Nessuna build disponibile ({})", reasons.join("; ")));
+        return result;
+    };
+    let (build, build_mode) = (&pb.build, pb.mode.as_str());
+    result.build_source = Some(provider.label().to_string());
+    result.build_mode = Some(build_mode.to_string());
+    // Ripiego sul ranked: si dichiara nel nome pagina/item set da dove viene la build
+    let label = if build_mode == mode { mode_label.to_string() } else { format!("{} (ranked)", mode_label) };
+
+    // ── Rune (l'Arena non ne ha) ─────────────────────────────────────────────
+    match result.builds[idx].runes.clone() {
+        Some(rune_page) => match import_runes(lcu, champion_name, &label, rune_page).await {
             Ok((name, path, rune_page)) => {
                 result.runes_imported = true;
                 result.rune_page_name = Some(name);
@@ -1398,93 +473,45 @@ pub async fn auto_import_build(champion_name: String, assigned_position: String,
                 result.opgg_runes = Some(rune_page);
             }
             Err(e) => result.errors.push(format!("Rune: {}", e)),
+        },
+        None if build.pointer("/data/runes").is_some_and(|r| !r.is_null()) =>
+            result.errors.push("Rune: pagina non valida".to_string()),
+        None => eprintln!("[RLP] Rune skip (mode={})", build_mode),
+    }
+
+    // ── Summoner spells: validate per la coda corrente (summoner_spells.rs) ──
+    match summoner_spells::import(lcu, build, mode, assigned_position).await {
+        Ok(Some(plan)) => {
+            result.summoners_imported = true;
+            result.summoner_spells = plan.names;
+            result.spell_fixes = plan.fixes;
         }
-        match import_summoners(&lcu, &build).await {
-            Ok(spells) => { result.summoners_imported=true; result.summoner_spells=spells; }
-            Err(e) => result.errors.push(format!("Summoners: {}", e)),
+        Ok(None) => {
+            eprintln!("[RLP] Summoner spells skip (nessuna spell nella build {})", build_mode);
+            result.summoners_skipped = Some("La build non indica summoner spell".to_string());
         }
-        match import_item_set(&lcu, &champion_name, mode_label, &build, &puuid, None).await {
-            Ok(blocks) => { result.items_imported=true; result.item_blocks=Some(blocks); }
-            Err(e) => result.errors.push(format!("Item set: {}", e)),
-        }
-
-        // // ── Build alternativa dal DB ──────────────────────────────────────────
-        // if let Ok(Some(db)) = db_handle.await {
-        //     // Crea item set separato "[DB] RLP Champion LANE"
-        //     let db_label = format!("[DB] RLP {} {}", champion_name, assigned_position);
-        //     let db_build_value = build_db_item_set_value(&champion_name, &db_label, &db.items);
-        //     let db_items_ok = match write_item_set_file(&champion_name, &db_build_value, &db_label).await {
-        //         Ok(_) => { eprintln!("[RLP DB] item set scritto: {}", db_label); true }
-        //         Err(e) => { result.errors.push(format!("DB item set: {}", e)); false }
-        //     };
-        //     result.db_build = Some(DbBuildResult {
-        //         primary_path_name: db.primary_path_name,
-        //         secondary_path_name: db.secondary_path_name,
-        //         primary_path_id: db.primary_path_id,
-        //         secondary_path_id: db.secondary_path_id,
-        //         keystone_id: db.keystone_id,
-        //         primary_slot_ids: db.primary_slot_ids,
-        //         secondary_rune_ids: db.secondary_rune_ids,
-        //         items_imported: db_items_ok,
-        //         sample_size: db.sample_size,
-        //     });
-        // } else {
-        //     eprintln!("[RLP DB] Nessuna build DB trovata (sample insufficiente o champion non trovato)");
-        // }
-    } else {
-        // ── URF / ARAM / altre modalità: fetch parallelo per tutte le lane ────
-        // let _ = db_handle; // non usiamo il db_handle in queste modalità
-        eprintln!("[RLP] mode={} → multi-lane fetch", mode);
-        let lane_builds = opgg_get_builds_multi_lane(&champion_name).await;
-
-        if lane_builds.is_empty() {
-            result.errors.push("Nessuna build multi-lane disponibile".to_string());
-            return Ok(result);
-        }
-
-        // Rune: usiamo la build MID (prima dell'array, o la prima disponibile)
-        let rune_build = lane_builds.iter()
-            .find(|(l, _)| l == "MID")
-            .or_else(|| lane_builds.first());
-        if let Some((rune_lane, build)) = rune_build {
-            let rune_label = format!("{} {}", mode_label, rune_lane);
-            match import_runes(&lcu, &champion_name, &rune_label, build).await {
-                Ok((name, path, rune_page)) => {
-                    result.runes_imported = true;
-                    result.rune_page_name = Some(name);
-                    result.primary_path = Some(path);
-                    result.opgg_runes = Some(rune_page);
-                }
-                Err(e) => result.errors.push(format!("Rune: {}", e)),
-            }
-        }
-
-        // Summoner spells: skip in modalità non-ranked (LCU non lo permette)
-        result.summoners_imported = true;
-        eprintln!("[RLP] Summoner spells skip (mode={})", mode);
+        Err(e) => result.errors.push(format!("Summoners: {}", e)),
+    }
 
-        // Item set: uno per ogni lane riuscita
-        let mut total_blocks = 0usize;
-        let mut item_errors = Vec::new();
-        for (lane_label, build) in &lane_builds {
-            let set_label = format!("RLP {} {} {}", champion_name, mode_label, lane_label);
-            match import_item_set(&lcu, &champion_name, lane_label, build, &puuid, Some(&set_label)).await {
-                Ok(blocks) => { total_blocks += blocks; }
-                Err(e) => { item_errors.push(format!("{}: {}", lane_label, e)); }
-            }
-        }
-        if total_blocks > 0 {
-            result.items_imported = true;
-            result.item_blocks = Some(total_blocks);
-        }
-        if !item_errors.is_empty() {
-            result.errors.push(format!("Item set parziale: {}", item_errors.join("; ")));
+    // ── Item set: uno solo, anche in ARAM/URF/Arena ──────────────────────────
+    let set_title = format!("RLP {} {}", champion_name, label);
+    match import_item_set(lcu, summoner_id, champion_name, mode, &label, set_title, build).await {
+        Ok((blocks, target)) => {
+            result.items_imported=true;
+            result.item_blocks=Some(blocks);
+            result.item_set_target=Some(target.to_string());
         }
+        Err(e) => result.errors.push(format!("Item set: {}", e)),
     }
 
+    result.augments = build.pointer("/data/augments").and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v["name"].as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    result.skill_plan = result.builds[idx].skill_plan.clone();
+
     if !result.errors.is_empty() {
         eprintln!("\n[RLP] ⚠️ ERRORI RILEVATI DURANTE L'IMPORTAZIONE:\n{:#?}\n", result.errors);
     }
     
-    Ok(result)
+    result
 }
\ No newline at end of file
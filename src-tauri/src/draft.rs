@@ -0,0 +1,256 @@
+// draft.rs — assistente draft in champ select
+//
+// Dalla sessione LCU (`/lol-champ-select/v1/session`) ricava pick, intent (hover
+// non ancora confermati) e ban delle due squadre, poi per ogni squadra:
+//   • profilo danni fisico/magico/puro dai valori `info` di DDragon (attack/magic)
+//     più una quota fissa di danno puro per i campioni che ne fanno molto;
+//   • indicatori di engage, peel e CC dalle classi DDragon (Tank, Support, ...)
+//     e da una breve lista di engage/peel affidabili;
+//   • lane scoperte e avvisi ("quasi tutto fisico", "nessun frontline", ...).
+// Gli intent contano come pick: l'idea è accorgersi di una comp tutta AD prima
+// del lock. Il binario ricalcola l'analisi a ogni evento della sessione
+// (lcu_events.rs → evento `lcu-draft`).
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::lcu::LcuConnection;
+use crate::static_data::{static_data, Champion, StaticData};
+
+/// Quota di danno puro per i campioni che ne fanno una parte consistente.
+const TRUE_DAMAGE: &[(&str, f64)] = &[
+    ("Vayne", 0.35), ("Fiora", 0.3), ("Camille", 0.25), ("Darius", 0.2), ("Garen", 0.25),
+    ("Chogath", 0.2), ("Olaf", 0.25), ("MasterYi", 0.25), ("Gwen", 0.25), ("Pyke", 0.2),
+    ("Trundle", 0.1), ("Illaoi", 0.1), ("Kayle", 0.15),
+];
+/// Engage affidabile anche fuori dalla classe Tank.
+const HARD_ENGAGE: &[&str] = &[
+    "Malphite", "Amumu", "Leona", "Nautilus", "Sejuani", "Zac", "Ornn", "Rakan", "Alistar",
+    "Rell", "JarvanIV", "Vi", "MonkeyKing", "Kennen", "Diana", "Sion", "Maokai", "Galio",
+    "Hecarim", "Skarner", "Nocturne",
+];
+/// Peel/protezione del carry.
+const PEEL: &[&str] = &[
+    "Janna", "Lulu", "Braum", "Taric", "Thresh", "Soraka", "Nami", "Karma", "Milio",
+    "Renata", "Yuumi", "Zilean", "Sona", "Seraphine", "Poppy", "TahmKench",
+];
+
+const POSITIONS: [&str; 5] = ["TOP", "JUNGLE", "MIDDLE", "BOTTOM", "UTILITY"];
+
+// ── Tipi ─────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftPick {
+    pub cell_id: i64,
+    pub champion_id: u32,
+    /// Id DDragon ("MonkeyKing"), vuoto se nessun campione.
+    pub champion: String,
+    pub champion_name: String,
+    /// Lane assegnata (vuota fuori dalle code con ruoli o per gli avversari).
+    pub position: String,
+    /// Solo hover: pick non ancora confermato.
+    pub intent: bool,
+    pub is_me: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftBan {
+    pub champion_id: u32,
+    pub champion_name: String,
+    /// `false` = ban in hover.
+    pub completed: bool,
+}
+
+/// Percentuali (0-100) del danno stimato della squadra.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DamageProfile {
+    pub physical: f64,
+    pub magic: f64,
+    #[serde(rename = "true")]
+    pub true_damage: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TeamIndicators {
+    /// Campioni che iniziano i fight.
+    pub engage: u32,
+    /// Campioni che proteggono i carry.
+    pub peel: u32,
+    /// CC complessivo, stima 0-10.
+    pub cc: u32,
+    /// Campioni in grado di stare in prima linea.
+    pub frontline: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TeamAnalysis {
+    pub picks: Vec<DraftPick>,
+    pub bans: Vec<DraftBan>,
+    pub damage: DamageProfile,
+    pub indicators: TeamIndicators,
+    /// Lane senza campione (pick o intent).
+    pub missing_roles: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DraftAnalysis {
+    pub in_progress: bool,
+    /// Fase del timer LCU ("PLANNING", "BAN_PICK", "FINALIZATION", ...).
+    pub phase: String,
+    pub my_team: TeamAnalysis,
+    pub their_team: TeamAnalysis,
+}
+
+impl DraftAnalysis {
+    pub fn none() -> Self {
+        DraftAnalysis::default()
+    }
+}
+
+// ── Analisi ──────────────────────────────────────────────────────────────────
+
+fn picks(team: &Value, my_cell: i64, data: &StaticData) -> Vec<DraftPick> {
+    team.as_array().into_iter().flatten().map(|p| {
+        let locked = p["championId"].as_u64().unwrap_or(0) as u32;
+        let hover = p["championPickIntent"].as_u64().unwrap_or(0) as u32;
+        let champion_id = if locked != 0 { locked } else { hover };
+        let champ = data.champions.get(champion_id);
+        let cell_id = p["cellId"].as_i64().unwrap_or(-1);
+        DraftPick {
+            cell_id,
+            champion_id,
+            champion: champ.map(|c| c.id.clone()).unwrap_or_default(),
+            champion_name: champ.map(|c| c.name.clone()).unwrap_or_default(),
+            position: p["assignedPosition"].as_str().unwrap_or("").to_uppercase(),
+            intent: locked == 0 && hover != 0,
+            is_me: cell_id == my_cell,
+        }
+    }).collect()
+}
+
+/// Ban completati o in hover, divisi per squadra (dalle azioni, con ripiego su `bans`).
+fn bans(session: &Value, my_cells: &[i64], data: &StaticData) -> (Vec<DraftBan>, Vec<DraftBan>) {
+    let ban = |id: u32, completed: bool| DraftBan {
+        champion_id: id,
+        champion_name: data.champions.get(id).map(|c| c.name.clone()).unwrap_or_default(),
+        completed,
+    };
+    let (mut mine, mut theirs) = (Vec::new(), Vec::new());
+    let actions = session["actions"].as_array().into_iter().flatten()
+        .filter_map(|phase| phase.as_array()).flatten()
+        .filter(|a| a["type"].as_str() == Some("ban"));
+    for a in actions {
+        let id = a["championId"].as_u64().unwrap_or(0) as u32;
+        if id == 0 { continue; }
+        let b = ban(id, a["completed"].as_bool().unwrap_or(false));
+        let ours = a["isAllyAction"].as_bool()
+            .unwrap_or_else(|| my_cells.contains(&a["actorCellId"].as_i64().unwrap_or(-1)));
+        if ours { mine.push(b) } else { theirs.push(b) }
+    }
+    let from_list = |key: &str| -> Vec<DraftBan> {
+        session["bans"][key].as_array().into_iter().flatten()
+            .filter_map(|v| v.as_u64()).filter(|&id| id != 0)
+            .map(|id| ban(id as u32, true)).collect()
+    };
+    if mine.is_empty() { mine = from_list("myTeamBans"); }
+    if theirs.is_empty() { theirs = from_list("theirTeamBans"); }
+    (mine, theirs)
+}
+
+fn has_tag(c: &Champion, tag: &str) -> bool {
+    c.tags.iter().any(|t| t == tag)
+}
+
+fn damage(champs: &[&Champion]) -> DamageProfile {
+    let (mut physical, mut magic, mut true_damage) = (0.0, 0.0, 0.0);
+    for c in champs {
+        let (a, m) = (c.attack as f64, c.magic as f64);
+        if a + m == 0.0 { continue; }
+        let t = TRUE_DAMAGE.iter().find(|(id, _)| *id == c.id).map(|(_, t)| *t).unwrap_or(0.0);
+        physical += a / (a + m) * (1.0 - t);
+        magic += m / (a + m) * (1.0 - t);
+        true_damage += t;
+    }
+    let total = physical + magic + true_damage;
+    if total == 0.0 { return DamageProfile::default(); }
+    let pct = |v: f64| (v / total * 1000.0).round() / 10.0;
+    DamageProfile { physical: pct(physical), magic: pct(magic), true_damage: pct(true_damage) }
+}
+
+fn indicators(champs: &[&Champion]) -> TeamIndicators {
+    let mut ind = TeamIndicators::default();
+    let mut cc = 0;
+    for c in champs {
+        let tank = has_tag(c, "Tank");
+        if tank || HARD_ENGAGE.contains(&c.id.as_str()) { ind.engage += 1; }
+        if PEEL.contains(&c.id.as_str()) || (has_tag(c, "Support") && !tank) { ind.peel += 1; }
+        if tank || (has_tag(c, "Fighter") && c.defense >= 5) { ind.frontline += 1; }
+        cc += if tank { 3 }
+            else if has_tag(c, "Support") { 2 }
+            else if has_tag(c, "Mage") || has_tag(c, "Fighter") { 1 }
+            else { 0 };
+    }
+    ind.cc = cc.min(10);
+    ind
+}
+
+fn analyze_team(picks: Vec<DraftPick>, bans: Vec<DraftBan>, data: &StaticData) -> TeamAnalysis {
+    let champs: Vec<&Champion> = picks.iter().filter_map(|p| data.champions.get(p.champion_id)).collect();
+    let damage = damage(&champs);
+    let indicators = indicators(&champs);
+
+    // Lane scoperte: solo se la coda assegna i ruoli
+    let missing_roles: Vec<String> = if picks.iter().any(|p| !p.position.is_empty()) {
+        POSITIONS.iter()
+            .filter(|pos| !picks.iter().any(|p| p.position == **pos && p.champion_id != 0))
+            .map(|pos| pos.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut warnings = Vec::new();
+    if champs.len() >= 3 {
+        if damage.physical >= 80.0 { warnings.push(format!("Danni quasi solo fisici ({:.0}% AD)", damage.physical)); }
+        if damage.magic >= 80.0 { warnings.push(format!("Danni quasi solo magici ({:.0}% AP)", damage.magic)); }
+        if indicators.frontline == 0 { warnings.push("Nessun frontline".to_string()); }
+        if indicators.engage == 0 { warnings.push("Nessun engage".to_string()); }
+        if indicators.peel == 0 && champs.iter().any(|c| has_tag(c, "Marksman")) {
+            warnings.push("Nessuna peel per il carry".to_string());
+        }
+    }
+
+    TeamAnalysis { picks, bans, damage, indicators, missing_roles, warnings }
+}
+
+/// Analisi della sessione LCU (da GET o da evento websocket).
+pub async fn analyze(session: &Value) -> Result<DraftAnalysis, String> {
+    if session.get("errorCode").is_some() || !session["myTeam"].is_array() {
+        return Ok(DraftAnalysis::none());
+    }
+    let data = static_data().await?;
+    let my_cell = session["localPlayerCellId"].as_i64().unwrap_or(-1);
+    let my_picks = picks(&session["myTeam"], my_cell, &data);
+    let their_picks = picks(&session["theirTeam"], my_cell, &data);
+    let my_cells: Vec<i64> = my_picks.iter().map(|p| p.cell_id).collect();
+    let (my_bans, their_bans) = bans(session, &my_cells, &data);
+
+    Ok(DraftAnalysis {
+        in_progress: true,
+        phase: session.pointer("/timer/phase").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+        my_team: analyze_team(my_picks, my_bans, &data),
+        their_team: analyze_team(their_picks, their_bans, &data),
+    })
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_draft_analysis(lcu: State<'_, LcuConnection>) -> Result<DraftAnalysis, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
+    let Some(session) = lcu.get("/lol-champ-select/v1/session").await else {
+        return Ok(DraftAnalysis::none());
+    };
+    analyze(&session).await
+}
@@ -0,0 +1,463 @@
+// item_sets.rs — item set scritti da RLP in Config/ di League
+//
+// Un file per campione e modalità (più la lane nel ranked) in
+// Config/Champions/<campione>/Recommended/RLP_<chiave>.json: riscriverlo aggiorna
+// il set invece di accumularne di nuovi. I set globali (tutti i campioni, o una
+// lista in `associatedChampions`) stanno in Config/Global/Recommended/.
+// I set indicano la mappa (`associatedMaps` + `map`/`mode` del formato legacy) così
+// il client mostra quello giusto in Landa, ARAM e Arena.
+// Scrittura atomica (file temporaneo + rename): il client non legge mai un JSON a metà.
+//
+// Con il client aperto i set passano da `/lol-item-sets/v1/item-sets/{summonerId}/sets`:
+// si sincronizzano sull'account e compaiono subito, senza cercare la cartella di
+// installazione. I file in Config/ restano il ripiego quando l'endpoint non risponde.
+// Lì i set RLP si riconoscono dallo `uid` con prefisso `RLP_` (stesso nome del file).
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::lcu::{LcuClient, LcuConnection};
+use crate::static_data::static_data;
+
+/// Prefisso dei file scritti da RLP: solo questi vengono elencati e cancellati.
+pub const FILE_PREFIX: &str = "RLP_";
+/// Nome usato dalle versioni precedenti per il set ranked.
+const LEGACY_FILE: &str = "RLP.json";
+
+/// Set da scrivere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemSet {
+    pub title: String,
+    /// Id DDragon del campione; `None` = set globale.
+    pub champion: Option<String>,
+    /// Per i set globali: campioni a cui si applica (vuoto = tutti).
+    #[serde(default)]
+    pub associated_champions: Vec<u32>,
+    /// "ranked" | "aram" | "urf" | "arena" | "any"
+    pub mode: String,
+    /// Lane nel ranked (entra nel nome file, così ogni lane ha il suo set).
+    #[serde(default)]
+    pub position: String,
+    pub blocks: Vec<Value>,
+}
+
+/// Set RLP trovato nel client o su disco.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemSetInfo {
+    /// `None` per i set globali.
+    pub champion: Option<String>,
+    /// Nome file, oppure `uid` per i set dell'account.
+    pub file: String,
+    /// "lcu" (account) | "file" (Config/)
+    pub source: &'static str,
+    pub title: String,
+    pub maps: Vec<u32>,
+    pub blocks: usize,
+}
+
+/// Id mappa: 11 Landa degli evocatori, 12 Abisso ululante, 30 Arena.
+fn map_ids(mode: &str) -> Vec<u32> {
+    match mode {
+        "ranked" | "urf" => vec![11],
+        "aram"           => vec![12],
+        "arena"          => vec![30],
+        _                => vec![],
+    }
+}
+
+/// `map` / `mode` del formato file legacy.
+fn legacy_map(mode: &str) -> (&'static str, &'static str) {
+    match mode {
+        "ranked" => ("SR", "CLASSIC"),
+        "urf"    => ("SR", "any"),
+        "aram"   => ("HA", "ARAM"),
+        _        => ("any", "any"),
+    }
+}
+
+fn sanitize(s: &str) -> String {
+    s.replace(' ', "_").replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_")
+}
+
+impl ItemSet {
+    /// Nome file: uno per modalità (e lane), oppure dal titolo per i set globali.
+    pub fn file_name(&self) -> String {
+        let key = match (&self.champion, self.position.is_empty()) {
+            (None, _)        => format!("global_{}", self.title),
+            (Some(_), true)  => self.mode.clone(),
+            (Some(_), false) => format!("{}_{}", self.mode, self.position),
+        };
+        format!("{}{}.json", FILE_PREFIX, sanitize(&key))
+    }
+
+    /// Id del set nell'account: il nome file, con il campione davanti (i set dell'account
+    /// non stanno in cartelle per campione).
+    pub fn uid(&self) -> String {
+        let file = self.file_name();
+        let stem = file.trim_end_matches(".json");
+        match &self.champion {
+            Some(c) => format!("{}{}_{}", FILE_PREFIX, sanitize(c), &stem[FILE_PREFIX.len()..]),
+            None => stem.to_string(),
+        }
+    }
+
+    pub fn to_json(&self, champion_key: Option<u32>) -> Value {
+        let (map, mode) = legacy_map(&self.mode);
+        let champions: Vec<u32> = match champion_key {
+            Some(key) => vec![key],
+            None => self.associated_champions.clone(),
+        };
+        json!({
+            "uid": self.uid(),
+            "title": self.title,
+            "type": "custom",
+            "startedFrom": "blank",
+            "map": map,
+            "mode": mode,
+            "associatedMaps": map_ids(&self.mode),
+            "associatedChampions": champions,
+            "priority": false,
+            "sortrank": 1,
+            "blocks": self.blocks,
+        })
+    }
+}
+
+// ── Cartelle ─────────────────────────────────────────────────────────────────
+
+/// Cerca la cartella Config di League su tutte le lettere di drive (C→Z).
+pub fn config_dir() -> Option<PathBuf> {
+    let suffixes = [
+        r"Riot Games\League of Legends\Config",
+        r"Program Files\Riot Games\League of Legends\Config",
+        r"Program Files (x86)\Riot Games\League of Legends\Config",
+        r"Games\League of Legends\Config",
+        r"League of Legends\Config",
+    ];
+    for drive in 'C'..='Z' {
+        for suffix in &suffixes {
+            let path = PathBuf::from(format!(r"{}:\{}", drive, suffix));
+            if path.join("Champions").exists() {
+                eprintln!("[ItemSets] config path trovato: {:?}", path);
+                return Some(path);
+            }
+        }
+    }
+    eprintln!("[ItemSets] config path non trovato su nessun drive (C:-Z:)");
+    None
+}
+
+fn recommended_dir(config: &Path, champion: Option<&str>) -> PathBuf {
+    match champion {
+        Some(c) => config.join("Champions").join(c).join("Recommended"),
+        None => config.join("Global").join("Recommended"),
+    }
+}
+
+/// Nome di cartella campione: solo lettere e cifre, come gli id DDragon ("MonkeyKing").
+/// Esclude separatori, `.`/`..` e prefissi Windows ("C:foo") che in `Path::join`
+/// uscirebbero da Champions/.
+fn is_champion_dir(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_rlp_file(name: &str) -> bool {
+    name.ends_with(".json") && (name.starts_with(FILE_PREFIX) || name == LEGACY_FILE)
+}
+
+// ── Scrittura ────────────────────────────────────────────────────────────────
+
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let dir = path.parent().ok_or("Percorso item set non valido")?;
+    fs::create_dir_all(dir).map_err(|e| format!("mkdir error: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("write error: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("rename error: {}", e)
+    })
+}
+
+/// Scrive (o aggiorna) il set su disco. Per i set di un campione rimuove anche i
+/// file delle versioni precedenti (`RLP.json`, `RLP_RLP_<...>.json`).
+pub fn write_file(set: &ItemSet, champion_key: Option<u32>) -> Result<PathBuf, String> {
+    if let Some(c) = set.champion.as_deref().filter(|c| !is_champion_dir(c)) {
+        return Err(format!("Campione non valido: '{}'", c));
+    }
+    let config = config_dir().ok_or("Cartella League of Legends non trovata su nessun drive (C:-Z:)")?;
+    let dir = recommended_dir(&config, set.champion.as_deref());
+    let path = dir.join(set.file_name());
+    let content = serde_json::to_string_pretty(&set.to_json(champion_key)).map_err(|e| e.to_string())?;
+    write_atomic(&path, &content)?;
+
+    if set.champion.is_some() {
+        let legacy = |name: &str| name == LEGACY_FILE || name.starts_with("RLP_RLP_");
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if legacy(&name) && fs::remove_file(entry.path()).is_ok() {
+                eprintln!("[ItemSets] rimosso set obsoleto {:?}", entry.path());
+            }
+        }
+    }
+    eprintln!("[ItemSets] scritto {:?} ({} blocchi)", path, set.blocks.len());
+    Ok(path)
+}
+
+// ── Account (LCU) ────────────────────────────────────────────────────────────
+
+fn sets_path(summoner_id: u64) -> String {
+    format!("/lol-item-sets/v1/item-sets/{}/sets", summoner_id)
+}
+
+async fn summoner_id(lcu: &LcuClient) -> Option<u64> {
+    lcu.get("/lol-summoner/v1/current-summoner").await?["summonerId"].as_u64().filter(|&id| id > 0)
+}
+
+/// Tutti i set dell'account (`{accountId, itemSets, timestamp}`).
+async fn account_sets(lcu: &LcuClient, summoner_id: u64) -> Result<Value, String> {
+    lcu.get(&sets_path(summoner_id)).await
+        .filter(|v| v["itemSets"].is_array())
+        .ok_or_else(|| "Endpoint item set non disponibile".to_string())
+}
+
+/// Sostituisce i set dell'account con quelli che passano `keep`, più `add`.
+/// L'endpoint accetta solo la lista completa: si rilegge e si riscrive tutto.
+async fn replace_account_sets(lcu: &LcuClient, summoner_id: u64, keep: impl Fn(&Value) -> bool, add: Option<Value>) -> Result<usize, String> {
+    let mut body = account_sets(lcu, summoner_id).await?;
+    let current = body["itemSets"].as_array().cloned().unwrap_or_default();
+    let before = current.len();
+    let mut sets: Vec<Value> = current.into_iter().filter(|s| keep(s)).collect();
+    let removed = before - sets.len();
+    sets.extend(add);
+    body["itemSets"] = Value::Array(sets);
+    body["timestamp"] = json!(chrono::Utc::now().timestamp_millis());
+    let status = lcu.put_status(&sets_path(summoner_id), &body).await?;
+    if (200..300).contains(&status) { Ok(removed) } else { Err(format!("Il client ha rifiutato gli item set (HTTP {})", status)) }
+}
+
+fn is_rlp_uid(set: &Value) -> bool {
+    set["uid"].as_str().is_some_and(|u| u.starts_with(FILE_PREFIX))
+}
+
+/// Scrive (o aggiorna) il set nell'account.
+pub async fn write_lcu(lcu: &LcuClient, summoner_id: u64, set: &ItemSet, champion_key: Option<u32>) -> Result<(), String> {
+    let uid = set.uid();
+    replace_account_sets(lcu, summoner_id, |s| s["uid"].as_str() != Some(uid.as_str()), Some(set.to_json(champion_key))).await?;
+    eprintln!("[ItemSets] account: scritto {} ({} blocchi)", uid, set.blocks.len());
+    Ok(())
+}
+
+/// Scrive il set nell'account; se l'endpoint non risponde (client chiuso, summonerId
+/// assente) ripiega sui file in Config/. Restituisce dove è finito ("lcu" | "file").
+/// Un set di un campione richiede la sua chiave: senza, varrebbe per tutti i campioni.
+pub async fn write(lcu: Option<&LcuClient>, summoner_id: Option<u64>, set: &ItemSet, champion_key: Option<u32>) -> Result<&'static str, String> {
+    if let (Some(c), None) = (&set.champion, champion_key) {
+        return Err(format!("Campione sconosciuto: '{}'", c));
+    }
+    if let (Some(lcu), Some(id)) = (lcu, summoner_id) {
+        match write_lcu(lcu, id, set, champion_key).await {
+            Ok(()) => return Ok("lcu"),
+            Err(e) => eprintln!("[ItemSets] account non disponibile ({}), ripiego su Config/", e),
+        }
+    }
+    write_file(set, champion_key).map(|_| "file")
+}
+
+async fn list_lcu(lcu: &LcuClient, summoner_id: u64, champion: Option<&str>) -> Result<Vec<ItemSetInfo>, String> {
+    let body = account_sets(lcu, summoner_id).await?;
+    let data = static_data().await.ok();
+    let champion_of = |key: u64| data.as_ref()
+        .and_then(|d| d.champions.get(key as u32).map(|c| c.id.clone()));
+    let sets = body["itemSets"].as_array().into_iter().flatten()
+        .filter(|s| is_rlp_uid(s))
+        .map(|s| ItemSetInfo {
+            champion: s["associatedChampions"].as_array()
+                .filter(|a| a.len() == 1)
+                .and_then(|a| a[0].as_u64())
+                .and_then(champion_of),
+            file: s["uid"].as_str().unwrap_or("").to_string(),
+            source: "lcu",
+            title: s["title"].as_str().unwrap_or("").to_string(),
+            maps: s["associatedMaps"].as_array()
+                .map(|a| a.iter().filter_map(|m| m.as_u64().map(|m| m as u32)).collect())
+                .unwrap_or_default(),
+            blocks: s["blocks"].as_array().map(|b| b.len()).unwrap_or(0),
+        })
+        .filter(|s| champion.is_none() || s.champion.as_deref() == champion)
+        .collect();
+    Ok(sets)
+}
+
+// ── Elenco / cancellazione su disco ──────────────────────────────────────────
+
+fn read_dir_sets(dir: &Path, champion: Option<&str>) -> Vec<ItemSetInfo> {
+    let mut sets = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let file = entry.file_name().to_string_lossy().to_string();
+        if !is_rlp_file(&file) { continue; }
+        let json: Value = fs::read_to_string(entry.path()).ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        sets.push(ItemSetInfo {
+            champion: champion.map(String::from),
+            source: "file",
+            title: json["title"].as_str().unwrap_or(&file).to_string(),
+            maps: json["associatedMaps"].as_array()
+                .map(|a| a.iter().filter_map(|m| m.as_u64().map(|m| m as u32)).collect())
+                .unwrap_or_default(),
+            blocks: json["blocks"].as_array().map(|b| b.len()).unwrap_or(0),
+            file,
+        });
+    }
+    sets.sort_by(|a, b| a.file.cmp(&b.file));
+    sets
+}
+
+/// Set RLP su disco di un campione, oppure di tutti i campioni più i globali (`None`).
+pub fn list_files(champion: Option<&str>) -> Result<Vec<ItemSetInfo>, String> {
+    let config = config_dir().ok_or("Cartella League of Legends non trovata")?;
+    if let Some(c) = champion {
+        if !is_champion_dir(c) {
+            return Err(format!("Campione non valido: '{}'", c));
+        }
+        return Ok(read_dir_sets(&recommended_dir(&config, Some(c)), Some(c)));
+    }
+    let mut sets = read_dir_sets(&recommended_dir(&config, None), None);
+    for entry in fs::read_dir(config.join("Champions")).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        sets.extend(read_dir_sets(&entry.path().join("Recommended"), Some(&name)));
+    }
+    Ok(sets)
+}
+
+/// Cancella un set RLP su disco (solo file con il prefisso RLP).
+pub fn delete_file(champion: Option<&str>, file: &str) -> Result<(), String> {
+    if !is_rlp_file(file) || file.contains(['/', '\\']) {
+        return Err(format!("'{}' non è un item set RLP", file));
+    }
+    if let Some(c) = champion.filter(|c| !is_champion_dir(c)) {
+        return Err(format!("Campione non valido: '{}'", c));
+    }
+    let config = config_dir().ok_or("Cartella League of Legends non trovata")?;
+    let path = recommended_dir(&config, champion).join(file);
+    fs::remove_file(&path).map_err(|e| format!("Cancellazione {:?} fallita: {}", path, e))
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Set RLP dell'account (se il client è aperto) e su disco.
+#[tauri::command]
+pub async fn list_item_sets(lcu: State<'_, LcuConnection>, champion: Option<String>) -> Result<Vec<ItemSetInfo>, String> {
+    let mut sets = Vec::new();
+    if let Some(lcu) = lcu.client() {
+        if let Some(id) = summoner_id(&lcu).await {
+            match list_lcu(&lcu, id, champion.as_deref()).await {
+                Ok(s) => sets.extend(s),
+                Err(e) => eprintln!("[ItemSets] {}", e),
+            }
+        }
+    }
+    match list_files(champion.as_deref()) {
+        Ok(s) => sets.extend(s),
+        Err(e) if sets.is_empty() && lcu.client().is_none() => return Err(e),
+        Err(e) => eprintln!("[ItemSets] {}", e),
+    }
+    Ok(sets)
+}
+
+/// Scrive un set; utile per i set globali (`champion: null`). Restituisce "lcu" | "file".
+#[tauri::command]
+pub async fn save_item_set(lcu: State<'_, LcuConnection>, mut set: ItemSet) -> Result<String, String> {
+    // Nome o id del campione → id DDragon (la cartella) e chiave numerica
+    let champion_key = match set.champion.take() {
+        Some(c) => {
+            let data = static_data().await?;
+            let champ = data.champions.find(&c).ok_or(format!("Campione sconosciuto: '{}'", c))?;
+            set.champion = Some(champ.id.clone());
+            Some(champ.key)
+        }
+        None => None,
+    };
+    let client = lcu.client();
+    let id = match &client { Some(c) => summoner_id(c).await, None => None };
+    write(client.as_ref(), id, &set, champion_key).await.map(String::from)
+}
+
+/// `source`: "lcu" per un set dell'account (`file` = uid), altrimenti file su disco.
+#[tauri::command]
+pub async fn delete_item_set(lcu: State<'_, LcuConnection>, champion: Option<String>, file: String, source: Option<String>) -> Result<(), String> {
+    if source.as_deref() != Some("lcu") {
+        return delete_file(champion.as_deref(), &file);
+    }
+    if !file.starts_with(FILE_PREFIX) {
+        return Err(format!("'{}' non è un item set RLP", file));
+    }
+    let lcu = lcu.client().ok_or("Client LoL non disponibile")?;
+    let id = summoner_id(&lcu).await.ok_or("Summoner non disponibile")?;
+    replace_account_sets(&lcu, id, |s| s["uid"].as_str() != Some(file.as_str()), None).await.map(|_| ())
+}
+
+/// Rimuove tutti gli item set scritti da RLP (account, campioni e globali). Restituisce quanti.
+#[tauri::command]
+pub async fn remove_all_item_sets(lcu: State<'_, LcuConnection>) -> Result<usize, String> {
+    let mut removed = 0;
+    if let Some(lcu) = lcu.client() {
+        if let Some(id) = summoner_id(&lcu).await {
+            match replace_account_sets(&lcu, id, |s| !is_rlp_uid(s), None).await {
+                Ok(n) => removed += n,
+                Err(e) => eprintln!("[ItemSets] {}", e),
+            }
+        }
+    }
+    for set in list_files(None).unwrap_or_default() {
+        match delete_file(set.champion.as_deref(), &set.file) {
+            Ok(()) => removed += 1,
+            Err(e) => eprintln!("[ItemSets] {}", e),
+        }
+    }
+    eprintln!("[ItemSets] rimossi {} set RLP", removed);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(champion: Option<&str>, mode: &str, position: &str, title: &str) -> ItemSet {
+        ItemSet {
+            title: title.into(),
+            champion: champion.map(String::from),
+            associated_champions: Vec::new(),
+            mode: mode.into(),
+            position: position.into(),
+            blocks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn file_name_per_mode_and_lane() {
+        assert_eq!(set(Some("Ahri"), "ranked", "MIDDLE", "x").file_name(), "RLP_ranked_MIDDLE.json");
+        assert_eq!(set(Some("Ahri"), "aram", "", "x").file_name(), "RLP_aram.json");
+        assert_eq!(set(None, "any", "", "Tank: AD/AP").file_name(), "RLP_global_Tank__AD_AP.json");
+    }
+
+    #[test]
+    fn uid_carries_the_champion() {
+        assert_eq!(set(Some("MonkeyKing"), "ranked", "TOP", "x").uid(), "RLP_MonkeyKing_ranked_TOP");
+        assert_eq!(set(Some("Ahri"), "arena", "", "x").uid(), "RLP_Ahri_arena");
+        assert_eq!(set(None, "any", "", "Anti heal").uid(), "RLP_global_Anti_heal");
+    }
+
+    #[test]
+    fn champion_dir_stays_inside_champions() {
+        for ok in ["Ahri", "MonkeyKing", "KSante", "Kaisa"] {
+            assert!(is_champion_dir(ok), "{}", ok);
+        }
+        for bad in ["", ".", "..", "../Global", "a/b", "a\\b", "C:foo", "C:", "/etc", "Kai'Sa", "Dr. Mundo"] {
+            assert!(!is_champion_dir(bad), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// lcu.rs — connessione condivisa al client League (LCU)
+//
+// Il client scrive un lockfile "LeagueClient:<pid>:<porta>:<password>:https" quando
+// parte e lo cancella quando si chiude; a ogni riavvio porta e password cambiano.
+// `LcuConnection` vive nello state Tauri: un task controlla il lockfile ogni 2 s,
+// ricrea il client HTTP quando cambiano le credenziali e notifica al frontend
+// Connected/Disconnected con l'evento `lcu-connection`.
+// I comandi chiedono il client con `lcu.client()` invece di rileggere il lockfile.
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+/// Evento Tauri emesso a ogni cambio di stato (payload: `LcuStatus`).
+pub const STATUS_EVENT: &str = "lcu-connection";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+const LOCKFILE_SUFFIXES: [&str; 5] = [
+    r"Riot Games\League of Legends\lockfile",
+    r"Program Files\Riot Games\League of Legends\lockfile",
+    r"Program Files (x86)\Riot Games\League of Legends\lockfile",
+    r"Games\League of Legends\lockfile",
+    r"League of Legends\lockfile",
+];
+
+// ── Lockfile ─────────────────────────────────────────────────────────────────
+
+/// Cerca il lockfile di League su tutte le lettere di drive possibili (C→Z).
+fn find_lockfile() -> Option<PathBuf> {
+    ('C'..='Z')
+        .flat_map(|drive| LOCKFILE_SUFFIXES.iter().map(move |s| PathBuf::from(format!(r"{}:\{}", drive, s))))
+        .find(|p| p.exists())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub pid: u32,
+    pub port: u16,
+    pub password: String,
+}
+
+/// "LeagueClient:1234:54321:abc:https\r\n" → porta 54321, password "abc".
+fn parse_lockfile(content: &str) -> Option<Credentials> {
+    let parts: Vec<&str> = content.trim().split(':').collect();
+    if parts.len() < 4 { return None; }
+    Some(Credentials {
+        pid: parts[1].parse().unwrap_or(0),
+        port: parts[2].parse().ok()?,
+        password: parts[3].to_string(),
+    })
+}
+
+// ── Client ───────────────────────────────────────────────────────────────────
+
+/// Client HTTP verso una specifica istanza del client League (porta + password).
+#[derive(Clone)]
+pub struct LcuClient {
+    client: Client,
+    port: u16,
+    auth: String,
+}
+
+impl LcuClient {
+    fn new(creds: &Credentials) -> Option<Self> {
+        let auth = general_purpose::STANDARD.encode(format!("riot:{}", creds.password));
+        let client = Client::builder().danger_accept_invalid_certs(true).build().ok()?;
+        Some(LcuClient { client, port: creds.port, auth })
+    }
+
+    /// Client reqwest sottostante (accetta il certificato self-signed dell'LCU).
+    #[allow(dead_code)] // solo binario
+    pub fn http(&self) -> &Client {
+        &self.client
+    }
+
+    /// Header `Authorization` (anche per il websocket).
+    pub fn authorization(&self) -> String {
+        format!("Basic {}", self.auth)
+    }
+
+    /// Endpoint WAMP dell'LCU (vedi lcu_events.rs, nel binario).
+    #[allow(dead_code)]
+    pub fn websocket_url(&self) -> String {
+        format!("wss://127.0.0.1:{}/", self.port)
+    }
+
+    /// Richiesta autenticata verso `path` (es. "/lol-summoner/v1/current-summoner").
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client.request(method, format!("https://127.0.0.1:{}{}", self.port, path))
+            .header("Authorization", self.authorization())
+    }
+
+    pub async fn get(&self, path: &str) -> Option<Value> {
+        self.request(Method::GET, path).send().await.ok()?.json().await.ok()
+    }
+    pub async fn put(&self, path: &str, body: &Value) -> Option<Value> {
+        self.request(Method::PUT, path).json(body).send().await.ok()?.json().await.ok()
+    }
+    pub async fn post(&self, path: &str, body: &Value) -> Option<Value> {
+        self.request(Method::POST, path).json(body).send().await.ok()?.json().await.ok()
+    }
+    pub async fn put_status(&self, path: &str, body: &Value) -> Result<u16, String> {
+        let resp = self.request(Method::PUT, path).json(body).send().await.map_err(|e| e.to_string())?;
+        Ok(resp.status().as_u16())
+    }
+    #[allow(dead_code)] // solo binario (auto_actions.rs)
+    pub async fn post_status(&self, path: &str, body: &Value) -> Result<u16, String> {
+        let resp = self.request(Method::POST, path).json(body).send().await.map_err(|e| e.to_string())?;
+        Ok(resp.status().as_u16())
+    }
+    pub async fn patch_status(&self, path: &str, body: &Value) -> Result<u16, String> {
+        let resp = self.request(Method::PATCH, path).json(body).send().await.map_err(|e| e.to_string())?;
+        Ok(resp.status().as_u16())
+    }
+    pub async fn delete(&self, path: &str) -> Result<u16, String> {
+        let resp = self.request(Method::DELETE, path).send().await.map_err(|e| e.to_string())?;
+        Ok(resp.status().as_u16())
+    }
+}
+
+/// Errore di connessione: lockfile presente ma client non (ancora) in ascolto.
+#[allow(dead_code)] // solo binario
+pub fn is_refused(e: &reqwest::Error) -> bool {
+    let msg = e.to_string();
+    e.is_connect() || msg.contains("connection refused") || msg.contains("actively refused")
+        || msg.contains("os error 10061") || msg.contains("os error 111")
+}
+
+// ── Connessione condivisa ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "state")]
+pub enum LcuStatus {
+    Connected { port: u16, pid: u32 },
+    Disconnected,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Ultimo lockfile trovato: al riavvio del client ricompare nello stesso posto.
+    path: Option<PathBuf>,
+    creds: Option<Credentials>,
+    client: Option<LcuClient>,
+}
+
+/// Stato Tauri (`app.manage`) condiviso da comandi e task in background.
+#[derive(Clone)]
+pub struct LcuConnection {
+    inner: Arc<Mutex<Inner>>,
+    app: AppHandle,
+}
+
+impl LcuConnection {
+    pub fn new(app: AppHandle) -> Self {
+        LcuConnection { inner: Arc::default(), app }
+    }
+
+    /// Client per l'istanza corrente; `None` se il client League è chiuso.
+    pub fn client(&self) -> Option<LcuClient> {
+        if let Some(c) = self.inner.lock().unwrap().client.clone() { return Some(c); }
+        // Il watcher potrebbe non essere ancora passato (avvio, client appena aperto)
+        self.refresh();
+        self.inner.lock().unwrap().client.clone()
+    }
+
+    pub fn status(&self) -> LcuStatus {
+        match &self.inner.lock().unwrap().creds {
+            Some(c) => LcuStatus::Connected { port: c.port, pid: c.pid },
+            None    => LcuStatus::Disconnected,
+        }
+    }
+
+    /// Rilegge il lockfile e aggiorna la connessione se è cambiato qualcosa.
+    fn refresh(&self) {
+        let changed = {
+            let mut inner = self.inner.lock().unwrap();
+            let path = inner.path.clone().filter(|p| p.exists()).or_else(find_lockfile);
+            let creds = path.as_ref()
+                .and_then(|p| std::fs::read_to_string(p).ok())
+                .and_then(|c| parse_lockfile(&c));
+            if path.is_some() { inner.path = path; }
+            if creds == inner.creds {
+                false
+            } else {
+                inner.client = creds.as_ref().and_then(LcuClient::new);
+                inner.creds = creds;
+                true
+            }
+        };
+        if changed {
+            let status = self.status();
+            eprintln!("[LCU] {:?}", status);
+            let _ = self.app.emit(STATUS_EVENT, status);
+        }
+    }
+
+    /// Avvia il controllo periodico del lockfile (creazione, cancellazione, riavvio).
+    pub fn spawn_watcher(&self) {
+        let conn = self.clone();
+        tauri::async_runtime::spawn(async move {
+            loop {
+                conn.refresh();
+                tokio::time::sleep(WATCH_INTERVAL).await;
+            }
+        });
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_lcu_status(lcu: tauri::State<'_, LcuConnection>) -> Result<LcuStatus, String> {
+    Ok(lcu.status())
+}
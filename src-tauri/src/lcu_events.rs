@@ -0,0 +1,207 @@
+// lcu_events.rs — eventi LCU via websocket (WAMP 1.0) invece del polling
+//
+// L'LCU espone un websocket sulla stessa porta delle API REST. Dopo la connessione
+// ci si iscrive ai topic con `[5, "OnJsonApiEvent_<path>"]` e ogni cambiamento arriva
+// come `[8, topic, { uri, eventType: "Create"|"Update"|"Delete", data }]`.
+// Qui gli eventi vengono tradotti in payload tipizzati e inoltrati al frontend con
+// `AppHandle::emit`; gameflow e champ select alimentano anche auto_import.rs e
+// auto_actions.rs.
+// Il loop di lettura non aspetta mai I/O di rete: le sessioni di champ select (analisi
+// draft, GET del gameflow per il riepilogo) le elabora un task a parte, che vede solo
+// l'ultima sessione arrivata (canale watch) e salta quelle già superate.
+// Se il client si chiude o si riavvia il socket cade e si ricollega da solo appena
+// `LcuConnection` ha le nuove credenziali.
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async_tls_with_config, Connector};
+
+use crate::auto_actions;
+use crate::auto_import;
+use crate::champ_select::{summarize_session, ChampSelectSession};
+use crate::draft::{self, DraftAnalysis};
+use crate::lcu::{LcuClient, LcuConnection};
+
+/// Eventi Tauri emessi verso il frontend.
+pub const CHAMP_SELECT_EVENT: &str = "lcu-champ-select";
+pub const DRAFT_EVENT: &str = "lcu-draft";
+pub const GAMEFLOW_EVENT: &str = "lcu-gameflow-phase";
+pub const LOBBY_EVENT: &str = "lcu-lobby";
+pub const END_OF_GAME_EVENT: &str = "lcu-end-of-game";
+
+const CHAMP_SELECT_URI: &str = "/lol-champ-select/v1/session";
+const GAMEFLOW_URI: &str = "/lol-gameflow/v1/gameflow-phase";
+const LOBBY_URI: &str = "/lol-lobby/v2/lobby";
+const END_OF_GAME_URI: &str = "/lol-end-of-game/v1/eog-stats-block";
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+// WAMP 1.0
+const WAMP_SUBSCRIBE: u8 = 5;
+const WAMP_EVENT: u8 = 8;
+
+/// "/lol-gameflow/v1/gameflow-phase" → "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase"
+fn topic(uri: &str) -> String {
+    format!("OnJsonApiEvent{}", uri.replace('/', "_"))
+}
+
+// ── Payload ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonApiEvent {
+    uri: String,
+    event_type: String,
+    #[serde(default)]
+    data: Value,
+}
+
+/// `lcu-gameflow-phase`: "None" | "Lobby" | "Matchmaking" | "ReadyCheck" | "ChampSelect"
+/// | "InProgress" | "WaitingForStats" | "EndOfGame" | ...
+#[derive(Debug, Clone, Serialize)]
+pub struct GameflowEvent {
+    pub phase: String,
+}
+
+/// `lcu-lobby`: lobby attuale (`in_lobby: false` quando viene chiusa).
+#[derive(Debug, Clone, Serialize)]
+pub struct LobbyEvent {
+    pub in_lobby: bool,
+    pub queue_id: Option<i64>,
+    pub member_puuids: Vec<String>,
+}
+
+/// `lcu-end-of-game`: statistiche di fine partita disponibili (match da ricaricare).
+#[derive(Debug, Clone, Serialize)]
+pub struct EndOfGameEvent {
+    pub game_id: Option<u64>,
+}
+
+// ── Connessione ──────────────────────────────────────────────────────────────
+
+/// Avvia il subscriber: resta connesso finché il client League è aperto e si
+/// ricollega a ogni riavvio.
+pub fn spawn(app: AppHandle, conn: LcuConnection) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Some(lcu) = conn.client() {
+                match run(&app, &lcu).await {
+                    Ok(())  => eprintln!("[LcuEvents] websocket chiuso"),
+                    Err(e)  => eprintln!("[LcuEvents] websocket non disponibile: {}", e),
+                }
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+async fn run(app: &AppHandle, lcu: &LcuClient) -> Result<(), String> {
+    let mut req = lcu.websocket_url().into_client_request().map_err(|e| e.to_string())?;
+    req.headers_mut().insert("Authorization", lcu.authorization().parse().map_err(|_| "header non valido")?);
+    // Certificato self-signed dell'LCU, come per le chiamate REST
+    let tls = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .build().map_err(|e| e.to_string())?;
+    let (ws, _) = connect_async_tls_with_config(req, None, false, Some(Connector::NativeTls(tls)))
+        .await.map_err(|e| e.to_string())?;
+    let (mut write, mut read) = ws.split();
+
+    for uri in [CHAMP_SELECT_URI, GAMEFLOW_URI, LOBBY_URI, END_OF_GAME_URI] {
+        write.send(Message::Text(json!([WAMP_SUBSCRIBE, topic(uri)]).to_string()))
+            .await.map_err(|e| e.to_string())?;
+    }
+    eprintln!("[LcuEvents] iscritto agli eventi LCU");
+
+    // Chiuso il socket, `sessions` cade e il task termina
+    let (sessions, latest) = watch::channel(None);
+    tauri::async_runtime::spawn(process_sessions(app.clone(), lcu.clone(), latest));
+
+    while let Some(msg) = read.next().await {
+        match msg.map_err(|e| e.to_string())? {
+            Message::Text(text) => {
+                if let Some(event) = parse_message(&text) { dispatch(app, lcu, &sessions, event); }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// `[8, topic, payload]` → payload; gli altri messaggi WAMP (welcome, ...) si ignorano.
+fn parse_message(text: &str) -> Option<JsonApiEvent> {
+    let msg: Value = serde_json::from_str(text).ok()?;
+    let arr = msg.as_array()?;
+    if arr.first()?.as_u64()? != WAMP_EVENT as u64 { return None; }
+    serde_json::from_value(arr.get(2)?.clone()).ok()
+}
+
+/// Sessione champ select da elaborare (`None` = champ select chiusa).
+type SessionUpdate = Option<Option<Value>>;
+
+/// Elabora l'ultima sessione ricevuta: analisi draft, riepilogo e import automatico.
+async fn process_sessions(app: AppHandle, lcu: LcuClient, mut latest: watch::Receiver<SessionUpdate>) {
+    while latest.changed().await.is_ok() {
+        let Some(update) = latest.borrow_and_update().clone() else { continue };
+        let (analysis, session) = match update {
+            None => (DraftAnalysis::none(), ChampSelectSession::none()),
+            Some(data) => {
+                let (analysis, session) = tokio::join!(draft::analyze(&data), summarize_session(&lcu, &data));
+                let analysis = analysis.unwrap_or_else(|e| {
+                    eprintln!("[LcuEvents] analisi draft: {}", e);
+                    DraftAnalysis::none()
+                });
+                match session {
+                    Ok(s) => (analysis, s),
+                    Err(e) => {
+                        eprintln!("[LcuEvents] sessione champ select: {}", e);
+                        let _ = app.emit(DRAFT_EVENT, analysis);
+                        continue;
+                    }
+                }
+            }
+        };
+        let _ = app.emit(DRAFT_EVENT, analysis);
+        auto_import::on_session(&app, &lcu, &session);
+        let _ = app.emit(CHAMP_SELECT_EVENT, session);
+    }
+}
+
+fn dispatch(app: &AppHandle, lcu: &LcuClient, sessions: &watch::Sender<SessionUpdate>, event: JsonApiEvent) {
+    let deleted = event.event_type == "Delete";
+    let _ = match event.uri.as_str() {
+        CHAMP_SELECT_URI => {
+            // Le automazioni leggono la sessione grezza e non fanno I/O qui
+            if deleted { auto_actions::on_session_closed(); } else { auto_actions::on_session(app, lcu, &event.data); }
+            let _ = sessions.send(Some((!deleted).then_some(event.data)));
+            Ok(())
+        }
+        GAMEFLOW_URI => {
+            let phase = event.data.as_str().unwrap_or("None").to_string();
+            eprintln!("[LcuEvents] gameflow → {}", phase);
+            auto_import::on_gameflow_phase(&phase);
+            auto_actions::on_gameflow_phase(app, lcu, &phase);
+            app.emit(GAMEFLOW_EVENT, GameflowEvent { phase })
+        }
+        LOBBY_URI => {
+            let lobby = &event.data;
+            app.emit(LOBBY_EVENT, LobbyEvent {
+                in_lobby: !deleted && lobby.is_object(),
+                queue_id: lobby.pointer("/gameConfig/queueId").and_then(|v| v.as_i64()),
+                member_puuids: lobby["members"].as_array().map(|m| m.iter()
+                    .filter_map(|p| p["puuid"].as_str().map(String::from))
+                    .collect()).unwrap_or_default(),
+            })
+        }
+        END_OF_GAME_URI if !deleted => {
+            app.emit(END_OF_GAME_EVENT, EndOfGameEvent { game_id: event.data["gameId"].as_u64() })
+        }
+        _ => Ok(()),
+    };
+}
@@ -1,4 +1,15 @@
+mod build_provider;
 mod champ_select;
+mod draft;
+mod item_sets;
+mod lcu;
+mod mcp;
+mod opgg;
+mod rune_pages;
+mod rune_validator;
+mod static_data;
+mod summoner_spells;
+use tauri::Manager;
 use champ_select::{get_champ_select_session, auto_import_build,debug_champ_select_slot};
 
 #[tauri::command]
@@ -10,11 +21,37 @@ fn greet(name: &str) -> String {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            if let Ok(dir) = app.path().app_data_dir() {
+                static_data::init(&dir);
+                rune_pages::init(&dir);
+                opgg::init(&dir);
+                build_provider::init(&dir);
+                summoner_spells::init(&dir);
+            }
+            let lcu = lcu::LcuConnection::new(app.handle().clone());
+            lcu.spawn_watcher();
+            app.manage(lcu);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             get_champ_select_session,
             auto_import_build,
             debug_champ_select_slot,   // ← aggiungi questa
+            static_data::get_champion_map,
+            lcu::get_lcu_status,
+            rune_pages::backup_rune_pages,
+            rune_pages::restore_rune_pages,
+            build_provider::get_build_policy,
+            build_provider::set_build_policy,
+            draft::get_draft_analysis,
+            item_sets::list_item_sets,
+            item_sets::save_item_set,
+            item_sets::delete_item_set,
+            item_sets::remove_all_item_sets,
+            summoner_spells::get_spell_prefs,
+            summoner_spells::set_spell_prefs,
 
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,416 @@
+// local_builds.rs — build calcolate dalle partite salvate in locale (match_store.rs)
+//
+// Due sorgenti sullo stesso archivio:
+//   • "Dataset RLP" (`LocalProvider`): tutte le partite in cui compare il campione,
+//     giocato da chiunque, nelle code della modalità (e nella stessa lane per il ranked)
+//   • "Le mie build" (`PersonalProvider`): solo le ultime partite dell'utente con quel
+//     campione — quello che gioca davvero e con cosa vince
+// Per ogni scelta si prende la più frequente:
+//   • keystone; rune primarie, path secondario, secondarie e shard si contano solo
+//     nelle partite con quella keystone, così la pagina resta coerente
+//   • coppia di summoner spell
+//   • item completati (core = i 3 più presenti, poi i situazionali) e stivali;
+//     per le build personali gli item si ordinano per win rate
+// Lo skill order non è nel dettaglio Match-V5 e resta vuoto.
+use futures_util::future::BoxFuture;
+use serde_json::{json, Value};
+
+use crate::build_provider::{BuildProvider, BuildRequest, ProviderBuild};
+use crate::match_store::{blocking, MatchQuery};
+use crate::static_data::{static_data, StaticData};
+
+/// Sotto questa soglia la build non è affidabile.
+const MIN_GAMES: usize = 5;
+/// Partite più recenti considerate.
+const MAX_GAMES: u32 = 200;
+/// Costo minimo di un item "completo" (esclude componenti e consumabili).
+const LEGENDARY_GOLD: u32 = 2000;
+/// Ultime partite dell'utente considerate per la build personale.
+const PERSONAL_GAMES: usize = 30;
+const MIN_PERSONAL_GAMES: usize = 3;
+/// Un item entra nella classifica per win rate solo se comprato almeno in tante partite.
+const MIN_ITEM_GAMES: usize = 2;
+
+/// Ordinamento degli item completati.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemOrder {
+    /// I più comprati.
+    Frequency,
+    /// Win rate più alto (a parità, i più comprati), poi gli altri per frequenza.
+    WinRate,
+}
+
+/// Code Riot per modalità.
+pub fn queue_ids(mode: &str) -> &'static [i64] {
+    match mode {
+        "aram"  => &[450],
+        "urf"   => &[900, 1010, 1900],
+        "arena" => &[1700, 1710],
+        // Draft, Solo/Duo, Blind, Flex, Quickplay
+        _       => &[400, 420, 430, 440, 490],
+    }
+}
+
+/// Lane LCU → `teamPosition` Match-V5 ("" se assente).
+pub fn team_position(position: &str) -> &'static str {
+    match position.to_uppercase().as_str() {
+        "TOP"                => "TOP",
+        "JUNGLE"             => "JUNGLE",
+        "MIDDLE" | "MID"     => "MIDDLE",
+        "BOTTOM" | "BOT" | "ADC" => "BOTTOM",
+        "UTILITY" | "SUPPORT" => "UTILITY",
+        _                    => "",
+    }
+}
+
+// ── Aggregazione ─────────────────────────────────────────────────────────────
+
+/// Valori con il numero di occorrenze, dal più frequente (a parità, il primo visto).
+fn ranked<T: PartialEq + Copy>(values: impl IntoIterator<Item = T>) -> Vec<(T, usize)> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    for v in values {
+        match counts.iter_mut().find(|(k, _)| *k == v) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((v, 1)),
+        }
+    }
+    counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    counts
+}
+
+fn most_common<T: PartialEq + Copy>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    ranked(values).first().map(|&(v, _)| v)
+}
+
+/// Path e rune di uno stile (0 = primario, 1 = secondario) dal campo `perks`.
+fn style(p: &Value, idx: usize) -> Option<(u64, Vec<u64>)> {
+    let s = p.pointer(&format!("/perks/styles/{}", idx))?;
+    let perks = s["selections"].as_array()?.iter().filter_map(|sel| sel["perk"].as_u64()).collect();
+    Some((s["style"].as_u64()?, perks))
+}
+
+fn keystone(p: &Value) -> Option<u64> {
+    style(p, 0).and_then(|(_, perks)| perks.first().copied())
+}
+
+fn items(p: &Value) -> Vec<u64> {
+    let mut ids: Vec<u64> = (0..6).filter_map(|i| p[format!("item{}", i)].as_u64()).filter(|&id| id != 0).collect();
+    // Un item doppio conta una volta sola per partita
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+fn won(p: &Value) -> bool {
+    p["win"].as_bool() == Some(true)
+}
+
+fn win_rate(games: &[&Value]) -> f64 {
+    if games.is_empty() { return 0.0; }
+    games.iter().filter(|p| won(p)).count() as f64 / games.len() as f64
+}
+
+/// Item completati per win rate: (id, partite, vittorie) con almeno `MIN_ITEM_GAMES`
+/// partite, dal win rate più alto; in coda gli altri nell'ordine di frequenza.
+fn by_win_rate(frequency: &[(u64, usize)], games: &[&Value]) -> Vec<u64> {
+    let mut rated: Vec<(u64, usize, usize)> = frequency.iter()
+        .filter(|&&(_, n)| n >= MIN_ITEM_GAMES)
+        .map(|&(id, n)| (id, n, games.iter().filter(|p| won(p) && items(p).contains(&id)).count()))
+        .collect();
+    // wins/n decrescente senza float: a/b > c/d ⇔ a·d > c·b
+    rated.sort_by(|a, b| (b.2 * a.1).cmp(&(a.2 * b.1)).then(b.1.cmp(&a.1)));
+    let mut ids: Vec<u64> = rated.into_iter().map(|(id, _, _)| id).collect();
+    for &(id, _) in frequency {
+        if !ids.contains(&id) { ids.push(id); }
+    }
+    ids
+}
+
+/// Build aggregata dai partecipanti (JSON Match-V5), nel formato di opgg.rs.
+pub fn aggregate(games: &[&Value], data: &StaticData, order: ItemOrder) -> Value {
+    // ── Rune ─────────────────────────────────────────────────────────────────
+    let runes = most_common(games.iter().filter_map(|p| keystone(p))).map(|ks| {
+        let with_ks: Vec<&Value> = games.iter().copied().filter(|p| keystone(p) == Some(ks)).collect();
+        let primary = || with_ks.iter().filter_map(|p| style(p, 0));
+        let primary_page_id = most_common(primary().map(|(id, _)| id)).unwrap_or(0);
+        let mut primary_rune_ids = vec![ks];
+        for row in 1..4 {
+            if let Some(id) = most_common(primary().filter_map(|(_, perks)| perks.get(row).copied())) {
+                primary_rune_ids.push(id);
+            }
+        }
+        let sub_page_id = most_common(with_ks.iter().filter_map(|p| style(p, 1)).map(|(id, _)| id)).unwrap_or(0);
+        // Coppia secondaria più giocata per partita (ordinata): due rune frequenti prese
+        // separatamente possono stare sulla stessa riga, pagina che nessuno ha giocato
+        let sub_rune_ids: Vec<u64> = most_common(with_ks.iter()
+            .filter_map(|p| style(p, 1))
+            .filter(|(id, perks)| *id == sub_page_id && perks.len() >= 2)
+            .map(|(_, perks)| (perks[0].min(perks[1]), perks[0].max(perks[1]))))
+            .map(|(a, b)| vec![a, b])
+            .unwrap_or_default();
+        let stat_mod_ids: Vec<u64> = ["offense", "flex", "defense"].iter()
+            .filter_map(|row| most_common(with_ks.iter().filter_map(|p| p.pointer(&format!("/perks/statPerks/{}", row))?.as_u64())))
+            .collect();
+        json!({
+            "primary_page_id": primary_page_id, "sub_page_id": sub_page_id,
+            "primary_rune_ids": primary_rune_ids, "sub_rune_ids": sub_rune_ids,
+            "stat_mod_ids": stat_mod_ids
+        })
+    });
+
+    // ── Summoner spells ──────────────────────────────────────────────────────
+    let spells = most_common(games.iter().filter_map(|p| Some((p["summoner1Id"].as_u64()?, p["summoner2Id"].as_u64()?))));
+    let summoner_spells: Vec<String> = spells.into_iter()
+        .flat_map(|(a, b)| [a, b])
+        .map(|id| data.spells.get(id as u32).map(|s| s.key.clone()).unwrap_or_else(|| id.to_string()))
+        .collect();
+
+    // ── Item ─────────────────────────────────────────────────────────────────
+    let item = |id: &u64| data.items.get(*id as u32);
+    let frequency = ranked(games.iter().flat_map(|p| items(p)).filter(|id| {
+        item(id).is_some_and(|it| it.completed && !it.boots && it.gold >= LEGENDARY_GOLD)
+    }));
+    let legendary: Vec<u64> = match order {
+        ItemOrder::Frequency => frequency.iter().map(|&(id, _)| id).collect(),
+        ItemOrder::WinRate => by_win_rate(&frequency, games),
+    };
+    let core: Vec<u64> = legendary.iter().take(3).copied().collect();
+    let situational: Vec<u64> = legendary.iter().skip(3).take(5).copied().collect();
+    let boots: Vec<u64> = most_common(games.iter().flat_map(|p| items(p))
+        .filter(|id| item(id).is_some_and(|it| it.boots && it.gold > 300)))
+        .into_iter().collect();
+
+    let to_items = |ids: &[u64]| -> Vec<Value> { ids.iter().map(|id| json!({"id": id})).collect() };
+    let situ_slots = if situational.is_empty() { json!([]) } else {
+        json!([{"label": "Situational", "items": to_items(&situational)}])
+    };
+
+    json!({
+        "data": {
+            "runes":           runes,
+            "summoner_spells": summoner_spells,
+            "augments":        [],
+            "starter_items":   [],
+            "core_items":      to_items(&core),
+            "situ_slots":      situ_slots,
+            "boots":           to_items(&boots),
+            "skill_order":     "",
+            "skill_sequence":  []
+        }
+    })
+}
+
+// ── Provider ─────────────────────────────────────────────────────────────────
+
+/// Partecipanti con il campione richiesto dai match dell'archivio.
+fn champion_games<'a>(matches: &'a [Value], champion_name: &str, position: &str) -> Vec<&'a Value> {
+    matches.iter()
+        .filter_map(|m| m.pointer("/info/participants").and_then(|p| p.as_array()))
+        .flatten()
+        .filter(|p| p["championName"].as_str().is_some_and(|c| c.eq_ignore_ascii_case(champion_name)))
+        .filter(|p| position.is_empty() || p["teamPosition"] == position)
+        .collect()
+}
+
+pub struct LocalProvider;
+
+impl LocalProvider {
+    async fn fetch_build(req: &BuildRequest) -> Result<ProviderBuild, String> {
+        let position = if req.mode == "ranked" { team_position(&req.position) } else { "" };
+        let (champion_name, mode) = (req.champion_name.clone(), req.mode);
+        let matches = blocking(move |store| store.matches_for_champion(&champion_name, queue_ids(mode), position, MAX_GAMES))
+            .await.ok_or("Archivio match non disponibile")?;
+        let games = champion_games(&matches, &req.champion_name, position);
+        if games.len() < MIN_GAMES {
+            return Err(format!("Solo {} partite di {} in archivio (minimo {})", games.len(), req.champion_name, MIN_GAMES));
+        }
+        let data = static_data().await?;
+        eprintln!("[LocalBuilds] {} {} {}: {} partite", req.champion_name, req.mode, position, games.len());
+        Ok(ProviderBuild {
+            build: aggregate(&games, &data, ItemOrder::Frequency),
+            mode: req.mode.to_string(),
+            sample_size: Some(games.len() as u32),
+            win_rate: Some(win_rate(&games)),
+        })
+    }
+}
+
+impl BuildProvider for LocalProvider {
+    fn id(&self) -> &'static str { "local" }
+    fn label(&self) -> &'static str { "Dataset RLP" }
+    fn fetch<'a>(&'a self, req: &'a BuildRequest) -> BoxFuture<'a, Result<ProviderBuild, String>> {
+        Box::pin(Self::fetch_build(req))
+    }
+}
+
+/// Build dell'utente: ultime `PERSONAL_GAMES` partite con il campione nella modalità
+/// (e nella lane, per il ranked), item ordinati per win rate.
+pub struct PersonalProvider;
+
+impl PersonalProvider {
+    async fn fetch_build(req: &BuildRequest) -> Result<ProviderBuild, String> {
+        if req.puuid.is_empty() { return Err("Summoner corrente sconosciuto".to_string()); }
+        let data = static_data().await?;
+        let champion = data.champions.find(&req.champion_name)
+            .ok_or_else(|| format!("Campione {} sconosciuto", req.champion_name))?;
+        let position = if req.mode == "ranked" { team_position(&req.position) } else { "" };
+        let queues = queue_ids(req.mode);
+
+        let query = MatchQuery {
+            champion_id: Some(champion.key as i64),
+            limit: Some(MAX_GAMES),
+            ..Default::default()
+        };
+        let puuid = req.puuid.clone();
+        let matches = blocking(move |store| store.matches_for_puuid(&puuid, &query))
+            .await.ok_or("Archivio match non disponibile")?;
+        let games: Vec<&Value> = matches.iter()
+            .filter(|m| m.pointer("/info/queueId").and_then(|q| q.as_i64()).is_some_and(|q| queues.contains(&q)))
+            .filter_map(|m| m.pointer("/info/participants")?.as_array()?.iter().find(|p| p["puuid"] == req.puuid.as_str()))
+            .filter(|p| position.is_empty() || p["teamPosition"] == position)
+            .take(PERSONAL_GAMES)
+            .collect();
+        if games.len() < MIN_PERSONAL_GAMES {
+            return Err(format!("Solo {} partite tue con {} (minimo {})", games.len(), champion.name, MIN_PERSONAL_GAMES));
+        }
+        eprintln!("[LocalBuilds] personale {} {} {}: {} partite", req.champion_name, req.mode, position, games.len());
+        Ok(ProviderBuild {
+            build: aggregate(&games, &data, ItemOrder::WinRate),
+            mode: req.mode.to_string(),
+            sample_size: Some(games.len() as u32),
+            win_rate: Some(win_rate(&games)),
+        })
+    }
+}
+
+impl BuildProvider for PersonalProvider {
+    fn id(&self) -> &'static str { "personal" }
+    fn label(&self) -> &'static str { "Le mie build" }
+    fn fetch<'a>(&'a self, req: &'a BuildRequest) -> BoxFuture<'a, Result<ProviderBuild, String>> {
+        Box::pin(Self::fetch_build(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::static_data::Item;
+
+    const A: u64 = 3031;
+    const B: u64 = 3072;
+    const C: u64 = 3036;
+    const D: u64 = 6672;
+    const BOOTS: u64 = 3006;
+
+    fn item(id: u64, gold: u32, completed: bool, boots: bool) -> Item {
+        Item { id: id as u32, name: format!("Item {}", id), gold, completed, boots }
+    }
+
+    fn data() -> StaticData {
+        StaticData::for_tests(Vec::new(), vec![
+            item(A, 3400, true, false), item(B, 3300, true, false),
+            item(C, 3000, true, false), item(D, 3100, true, false),
+            // Componente, consumabile, stivali base e stivali completi
+            item(1038, 1300, false, false), item(2003, 50, true, false),
+            item(1001, 300, false, true), item(BOOTS, 1100, true, true),
+        ], Vec::new(), Vec::new())
+    }
+
+    fn player(primary: &[u64], sub: (u64, [u64; 2]), items: &[u64], win: bool) -> Value {
+        let selections = |perks: &[u64]| perks.iter().map(|p| json!({"perk": p})).collect::<Vec<_>>();
+        let mut p = json!({
+            "win": win, "summoner1Id": 4, "summoner2Id": 14,
+            "perks": {
+                "statPerks": {"offense": 5008, "flex": 5008, "defense": 5011},
+                "styles": [
+                    {"style": 8000, "selections": selections(primary)},
+                    {"style": sub.0, "selections": selections(&sub.1)},
+                ],
+            },
+        });
+        for (i, id) in items.iter().enumerate() { p[format!("item{}", i)] = json!(id); }
+        p
+    }
+
+    fn build(games: &[Value], order: ItemOrder) -> Value {
+        let refs: Vec<&Value> = games.iter().collect();
+        aggregate(&refs, &data(), order)["data"].clone()
+    }
+
+    fn ids(v: &Value) -> Vec<u64> {
+        v.as_array().unwrap().iter().map(|i| i["id"].as_u64().unwrap()).collect()
+    }
+
+    #[test]
+    fn runes_follow_the_most_played_keystone() {
+        let dom = (8100, [8139, 8135]);
+        let res = (8400, [8446, 8444]);
+        let games = [
+            // Conqueror: riga 1 in maggioranza 9111
+            player(&[8010, 9111, 9104, 8014], dom, &[], true),
+            player(&[8010, 9111, 9104, 8014], dom, &[], true),
+            player(&[8010, 9101, 9104, 8014], dom, &[], false),
+            player(&[8010, 8009, 9104, 8014], dom, &[], false),
+            // Press the Attack: 8009 è la più frequente in assoluto sulla riga 1
+            player(&[8005, 8009, 9105, 8017], res, &[], true),
+            player(&[8005, 8009, 9105, 8017], res, &[], true),
+            player(&[8005, 8009, 9105, 8017], res, &[], true),
+        ];
+        let runes = &build(&games, ItemOrder::Frequency)["runes"];
+        assert_eq!(runes["primary_rune_ids"], json!([8010, 9111, 9104, 8014]));
+        assert_eq!(runes["sub_page_id"], 8100);
+        assert_eq!(runes["sub_rune_ids"], json!([8135, 8139]));
+        assert_eq!(runes["stat_mod_ids"], json!([5008, 5008, 5011]));
+    }
+
+    #[test]
+    fn secondary_pair_comes_from_a_single_game() {
+        let games = [
+            // Stessa coppia in ordine diverso
+            player(&[8010, 9111, 9104, 8014], (8100, [8126, 8135]), &[], true),
+            player(&[8010, 9111, 9104, 8014], (8100, [8135, 8126]), &[], true),
+            // 8139 è la runa più presente, ma mai insieme a 8126 o 8135
+            player(&[8010, 9111, 9104, 8014], (8100, [8139, 8138]), &[], true),
+            player(&[8010, 9111, 9104, 8014], (8100, [8139, 8106]), &[], true),
+            player(&[8010, 9111, 9104, 8014], (8100, [8143, 8139]), &[], true),
+        ];
+        assert_eq!(build(&games, ItemOrder::Frequency)["runes"]["sub_rune_ids"], json!([8126, 8135]));
+    }
+
+    #[test]
+    fn items_by_frequency_or_win_rate() {
+        let runes = [8010, 9111, 9104, 8014];
+        let sub = (8100, [8139, 8135]);
+        let games = [
+            player(&runes, sub, &[A, B], true),
+            player(&runes, sub, &[A, B], true),
+            player(&runes, sub, &[A, C], false),
+            player(&runes, sub, &[A, C], false),
+            // Una sola partita: sotto MIN_ITEM_GAMES non entra in classifica per win rate
+            player(&runes, sub, &[D], true),
+        ];
+        let by_freq = build(&games, ItemOrder::Frequency);
+        assert_eq!(ids(&by_freq["core_items"]), vec![A, B, C]);
+        assert_eq!(ids(&by_freq["situ_slots"][0]["items"]), vec![D]);
+
+        let by_wr = build(&games, ItemOrder::WinRate);
+        assert_eq!(ids(&by_wr["core_items"]), vec![B, A, C]);
+        assert_eq!(ids(&by_wr["situ_slots"][0]["items"]), vec![D]);
+    }
+
+    #[test]
+    fn only_legendaries_and_real_boots_are_kept() {
+        let runes = [8010, 9111, 9104, 8014];
+        let sub = (8100, [8139, 8135]);
+        let games = [
+            player(&runes, sub, &[A, 1038, 2003, BOOTS], true),
+            player(&runes, sub, &[A, A, 1001, 1038], false),
+            player(&runes, sub, &[B, 1001, 2003, BOOTS], true),
+        ];
+        let data = build(&games, ItemOrder::Frequency);
+        assert_eq!(ids(&data["core_items"]), vec![A, B]);
+        assert_eq!(data["situ_slots"], json!([]));
+        assert_eq!(ids(&data["boots"]), vec![BOOTS]);
+        assert_eq!(data["summoner_spells"], json!(["4", "14"]));
+    }
+}
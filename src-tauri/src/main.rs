@@ -1,11 +1,10 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use std::{fs, path::PathBuf};
 use serde_json::{json, Value};
-use tauri::{AppHandle, Manager};
-use base64::{engine::general_purpose, Engine as _};
-use reqwest::Client;
+use tauri::{AppHandle, Manager, State};
+use reqwest::{Client, Method};
 use tokio::sync::OnceCell;
-use std::collections::{HashSet, HashMap};
+use std::collections::HashMap;
 use tokio::sync::RwLock;
 
 // ── Cargo.toml dependencies needed ──────────────────────────────────────────
@@ -17,14 +16,39 @@ use tokio::sync::RwLock;
 // (Turso via HTTP puro — nessuna dipendenza nativa extra, solo reqwest)
 // ────────────────────────────────────────────────────────────────────────────
 
+mod auto_actions;
+mod auto_import;
+mod build_provider;
 mod champ_select;
+mod draft;
 use champ_select::{get_champ_select_session, auto_import_build, debug_champ_select_slot, apply_rune_page};
 
-const OPGG_MCP_URL: &str = "https://mcp-api.op.gg/mcp";
+mod item_sets;
+mod lcu;
+mod lcu_events;
+mod local_builds;
+mod match_store;
+mod match_sync;
+mod matchups;
+mod mcp;
+mod offline;
+mod opgg;
+mod rate_limit;
+mod riot_api;
+mod rune_pages;
+mod rune_validator;
+mod scouting;
+mod seasons;
+mod static_data;
+mod summoner_spells;
+use lcu::LcuConnection;
+use seasons::{MatchRange, SeasonFilter};
+use riot_api::{
+    account_v1, champion_mastery_v4, league_v4, match_v5, spectator_v5, summoner_v4,
+    CurrentGameInfo, RiotApiError,
+};
 
-fn riot_api_key() -> &'static str {
-    env!("RIOT_API_KEY")
-}
+const OPGG_MCP_URL: &str = "https://mcp-api.op.gg/mcp";
 
 // ── Turso — solo per "recenti" (summoner index minimale) ─────────────────────
 // Non salviamo matches né ranked_entries: solo l'essenziale per l'autocomplete.
@@ -144,17 +168,7 @@ fn db_index_summoner(
 
 // ── In-memory caches ─────────────────────────────────────────────────────────
 
-/// Match detail cache — session-scoped, no TTL (match data non cambia mai).
-static MATCH_CACHE: OnceCell<RwLock<HashMap<String, Value>>> = OnceCell::const_new();
-async fn match_cache() -> &'static RwLock<HashMap<String, Value>> {
-    MATCH_CACHE.get_or_init(|| async { RwLock::new(HashMap::new()) }).await
-}
-
-/// Match ID già identificati come pre-Season 2026: saltati senza chiamare Riot API.
-static PRE2026_SKIP: OnceCell<RwLock<HashSet<String>>> = OnceCell::const_new();
-async fn pre2026_skip() -> &'static RwLock<HashSet<String>> {
-    PRE2026_SKIP.get_or_init(|| async { RwLock::new(HashSet::new()) }).await
-}
+// I dettagli match (che non cambiano mai) stanno su disco: vedi match_store.rs
 
 /// Tier list cache (OP.GG MCP) — TTL 15 minuti.
 static TIER_LIST_CACHE: OnceCell<RwLock<Option<(std::time::Instant, String)>>> = OnceCell::const_new();
@@ -188,19 +202,14 @@ async fn ranked_cache() -> &'static RwLock<HashMap<String, (std::time::Instant,
 }
 
 // ── Season filter ─────────────────────────────────────────────────────────────
+// Il calendario stagioni è in seasons.rs: qui si filtra solo in lettura.
 
-const SEASON_2026_START_MS:   u64 = 1_736_294_400_000; // 2026-01-08 00:00:00 UTC in ms
-
-
-fn filter_season_matches(matches: Value) -> Value {
+fn filter_season_matches(matches: Value, range: &MatchRange) -> Value {
     match matches.as_array() {
         None => matches,
         Some(arr) => {
             let filtered: Vec<Value> = arr.iter().filter(|m| {
-                let gc = m["info"]["gameCreation"].as_u64()
-                    .or_else(|| m["gameCreation"].as_u64())
-                    .unwrap_or(u64::MAX);
-                if gc < SEASON_2026_START_MS { return false; }
+                if !range.contains(m) { return false; }
                 let queue_id = m["info"]["queueId"].as_u64()
                     .or_else(|| m["queueId"].as_u64())
                     .unwrap_or(1);
@@ -211,30 +220,54 @@ fn filter_season_matches(matches: Value) -> Value {
     }
 }
 
-// ── LCU helpers ───────────────────────────────────────────────────────────────
+// ── Offline ───────────────────────────────────────────────────────────────────
+// Risposte servite solo dallo store locale, marcate `stale_since` (vedi offline.rs).
 
-/// Cerca il lockfile di League su tutte le lettere di drive possibili (C→Z).
-fn get_lockfile_path() -> Option<PathBuf> {
-    let suffixes = [
-        r"Riot Games\League of Legends\lockfile",
-        r"Program Files\Riot Games\League of Legends\lockfile",
-        r"Program Files (x86)\Riot Games\League of Legends\lockfile",
-        r"Games\League of Legends\lockfile",
-        r"League of Legends\lockfile",
-    ];
-    for drive in 'C'..='Z' {
-        for suffix in &suffixes {
-            let path = PathBuf::from(format!(r"{}:\{}", drive, suffix));
-            if path.exists() {
-                eprintln!("[LCU] lockfile trovato: {:?}", path);
-                return Some(path);
-            }
-        }
+/// `stale_since` per i match di un puuid: ultimo sync riuscito.
+async fn matches_stale_since(puuid: &str) -> String {
+    let puuid = puuid.to_string();
+    offline::stale_since(match_store::blocking(move |s| s.synced_at(&puuid)).await.flatten())
+}
+
+/// Profilo da cache.json con i match dello store (se ci sono), marcato stale.
+async fn stale_profile(cache: &Value, range: &MatchRange, region: &str) -> Value {
+    let mut c = cache.clone();
+    let puuid = cache["puuid"].as_str().unwrap_or("").to_string();
+    c["matches"] = match match_sync::load_page(&puuid, range, None, 20).await.filter(|m| !m.is_empty()) {
+        Some(stored) => json!(stored),
+        None         => filter_season_matches(cache["matches"].clone(), range),
+    };
+    if !puuid.is_empty() {
+        offline::queue(offline::RefreshJob::Matches { puuid, region: region.to_string() });
     }
-    eprintln!("[LCU] lockfile non trovato su nessun drive (C:-Z:)");
-    None
+    let since = cache["last_update"].as_str().map(String::from)
+        .unwrap_or_else(|| offline::stale_since(None));
+    offline::mark_stale(c, &since)
+}
+
+/// Summoner già cercato in passato, ricostruito dagli snapshot dello store.
+async fn stale_summoner(game_name: &str, tag_line: &str, range: &MatchRange, region: &str) -> Option<Value> {
+    let riot_id = format!("{}#{}", game_name, tag_line).to_lowercase();
+    let (puuid, (mut snap, updated_at)) = match_store::blocking(move |store| {
+        let puuid = store.get_snapshot("riot_id", &riot_id)?.0.as_str()?.to_string();
+        let summoner = store.get_snapshot("summoner", &puuid)?;
+        Some((puuid, summoner))
+    }).await.flatten()?;
+    snap["matches"] = json!(match_sync::load_page(&puuid, range, None, 20).await.unwrap_or_default());
+    offline::queue(offline::RefreshJob::Profile { puuid: puuid.clone(), region: region.to_string() });
+    offline::queue(offline::RefreshJob::Matches { puuid, region: region.to_string() });
+    Some(offline::mark_stale(snap, &offline::stale_since(Some(updated_at))))
+}
+
+async fn stale_masteries(puuid: &str, region: &str) -> Option<Value> {
+    let owner = puuid.to_string();
+    let (masteries, updated_at) = match_store::blocking(move |s| s.get_snapshot("masteries", &owner)).await.flatten()?;
+    offline::queue(offline::RefreshJob::Masteries { puuid: puuid.to_string(), region: region.to_string() });
+    Some(offline::mark_stale(masteries, &offline::stale_since(Some(updated_at))))
 }
 
+// ── LCU helpers ───────────────────────────────────────────────────────────────
+
 fn get_cache_path(handle: &AppHandle) -> PathBuf {
     handle.path().app_cache_dir()
         .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
@@ -243,161 +276,92 @@ fn get_cache_path(handle: &AppHandle) -> PathBuf {
 
 // ── Riot API helpers ──────────────────────────────────────────────────────────
 
-/// Percent-encodes un path segment RFC-3986.
-fn encode_path(s: &str) -> String {
-    s.bytes().map(|b| match b {
-        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
-        | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
-        b' '  => "%20".to_string(),
-        b     => format!("%{:02X}", b),
-    }).collect::<Vec<_>>().join("")
+/// Risolve il puuid da Riot ID (Account-V1).
+/// `NotFound` = il Riot ID non esiste; gli altri errori sono di rete/chiave.
+async fn fetch_puuid(game_name: &str, tag_line: &str, region: &str, client: &Client) -> Result<String, RiotApiError> {
+    let account = account_v1::by_riot_id(region, game_name, tag_line).send(client).await
+        .inspect_err(|e| eprintln!("[fetch_puuid] {}/{}: {}", game_name, tag_line, e))?;
+    Ok(account.puuid)
 }
 
-/// Mappa la stringa regione (lowercase, inviata dal frontend) all'host della piattaforma.
-/// Esempi: "euw" → "euw1", "na" → "na1", "kr" → "kr", "jp" → "jp1"
-fn platform_host(region: &str) -> &'static str {
-    match region.to_lowercase().as_str() {
-        "euw"  | "euw1"  => "euw1",
-        "eune" | "eun1"  => "eun1",
-        "na"   | "na1"   => "na1",
-        "br"   | "br1"   => "br1",
-        "lan"  | "la1"   => "la1",
-        "las"  | "la2"   => "la2",
-        "oce"  | "oc1"   => "oc1",
-        "tr"   | "tr1"   => "tr1",
-        "ru"             => "ru",
-        "kr"             => "kr",
-        "jp"   | "jp1"   => "jp1",
-        "sg"   | "sg2"   => "sg2",
-        "tw"   | "tw2"   => "tw2",
-        "vn"   | "vn2"   => "vn2",
-        _                => "euw1", // fallback sicuro
-    }
+async fn fetch_match_ids_since(puuid: &str, start: u32, count: u32, start_time: Option<u64>, end_time: Option<u64>, region: &str, client: &Client) -> Result<Vec<String>, RiotApiError> {
+    let mut req = match_v5::ids_by_puuid(region, puuid)
+        .query("start", start)
+        .query("count", count);
+    if let Some(ts) = start_time { req = req.query("startTime", ts); }
+    if let Some(ts) = end_time   { req = req.query("endTime", ts); }
+    let ids = req.send(client).await
+        .inspect_err(|e| eprintln!("[fetch_match_ids_since] {}", e))?;
+    eprintln!("[fetch_match_ids_since] start={} count={} → {} id", start, count, ids.len());
+    Ok(ids)
 }
 
-/// Mappa la stringa regione all'host di routing (cluster regionale).
-/// EUW/EUNE/TR/RU → europe | NA/BR/LAN/LAS → americas | KR/JP → asia | OCE/SG/TW/VN → sea
-fn routing_host(region: &str) -> &'static str {
-    match region.to_lowercase().as_str() {
-        "euw" | "euw1" | "eune" | "eun1" | "tr" | "tr1" | "ru" => "europe",
-        "na"  | "na1"  | "br"   | "br1"  | "lan" | "la1" | "las" | "la2" => "americas",
-        "kr"  | "jp"   | "jp1"  => "asia",
-        "oce" | "oc1"  | "sg"   | "sg2"  | "tw"  | "tw2" | "vn" | "vn2" => "sea",
-        _ => "europe",
+/// Dettaglio match (Match-V5), letto prima dallo store. `None` se non recuperabile.
+/// Nessun filtro stagione qui: si filtra in lettura con `MatchRange`.
+async fn fetch_match_detail(match_id: &str, region: &str, client: &Client) -> Option<Value> {
+    // Store su disco (sopravvive ai riavvii)
+    let id = match_id.to_string();
+    if let Some(cached) = match_store::blocking(move |s| s.get_match(&id)).await.flatten() {
+        eprintln!("✓ match store hit: {}", match_id);
+        return Some(cached);
     }
-}
 
-async fn fetch_puuid(game_name: &str, tag_line: &str, region: &str, client: &Client) -> Option<String> {
-    let url = format!(
-        "https://{}.api.riotgames.com/riot/account/v1/accounts/by-riot-id/{}/{}",
-        routing_host(region), encode_path(game_name), encode_path(tag_line)
-    );
-    // Retry fino a 3 volte: gestisce rate limit momentanei e timeout di rete
-    for attempt in 0..3u32 {
-        match client.get(&url).header("X-Riot-Token", riot_api_key()).send().await {
-            Ok(res) => {
-                let status = res.status().as_u16();
-                if status == 429 {
-                    // Rate limit — aspetta e riprova
-                    tokio::time::sleep(std::time::Duration::from_millis(1500 * (attempt + 1) as u64)).await;
-                    continue;
-                }
-                if status != 200 {
-                    eprintln!("[fetch_puuid] HTTP {} per {}/{}", status, game_name, tag_line);
-                    return None; // 404 = summoner non esiste, inutile riprovare
-                }
-                let data: Value = match res.json().await {
-                    Ok(d) => d,
-                    Err(e) => { eprintln!("[fetch_puuid] JSON error: {}", e); return None; }
-                };
-                return data["puuid"].as_str().map(|s| s.to_string());
-            }
-            Err(e) => {
-                eprintln!("[fetch_puuid] attempt {}: {}", attempt + 1, e);
-                if attempt < 2 {
-                    tokio::time::sleep(std::time::Duration::from_millis(800)).await;
-                }
-            }
-        }
-    }
-    None
+    let detail = match match_v5::by_id(region, match_id).send(client).await {
+        Ok(d) => d,
+        Err(e) => { eprintln!("[fetch_match_detail] {}: {}", match_id, e); return None; }
+    };
+    let value = json!(detail);
+    match_store::blocking(move |s| s.put_match(&detail)).await;
+    Some(value)
 }
 
-async fn fetch_match_ids_since(puuid: &str, start: u32, count: u32, start_time: Option<u64>, end_time: Option<u64>, region: &str, client: &Client) -> Vec<String> {
-    let mut url = format!(
-        "https://{}.api.riotgames.com/lol/match/v5/matches/by-puuid/{}/ids?start={}&count={}",
-        routing_host(region), puuid, start, count
-    );
-    if let Some(ts) = start_time {
-        url.push_str(&format!("&startTime={}", ts));
-    }
-    if let Some(ts) = end_time {
-        url.push_str(&format!("&endTime={}", ts));
-    }
-    eprintln!("[fetch_match_ids_since] URL: {}", url);
-    for attempt in 0..3u32 {
-        match client.get(&url).header("X-Riot-Token", riot_api_key()).send().await {
-            Ok(res) => {
-                if res.status().as_u16() == 429 {
-                    tokio::time::sleep(std::time::Duration::from_millis(2000 * (attempt + 1) as u64)).await;
-                    continue;
-                }
-                let status = res.status().as_u16();
-                let body = res.text().await.unwrap_or_default();
-                eprintln!("[fetch_match_ids_since] status={} body={}", status, &body[..body.len().min(300)]);
-                return serde_json::from_str::<Vec<String>>(&body).unwrap_or_default();
-            }
-            Err(_) => return vec![],
+/// Ultimi `count` match dentro `range` (custom esclusi), dal più recente.
+/// Con lo store e un intervallo che arriva ad oggi: sync incrementale dei soli match
+/// nuovi + lettura locale, e backfill dello storico in background fino all'inizio
+/// dell'intervallo ("all" = tutto lo storico). Finché il backfill non ci arriva, una
+/// pagina locale incompleta si chiede direttamente a Riot.
+/// Altrimenti (intervallo storico o store assente): fetch diretto per startTime/endTime.
+async fn load_recent_matches(puuid: &str, count: u32, range: &MatchRange, region: &str, client: &Client) -> Result<Vec<Value>, RiotApiError> {
+    if match_store::match_store().is_some() && range.is_open() {
+        if let Err(e) = match_sync::sync_new(puuid, region, client).await {
+            // Riot non raggiungibile: meglio lo storico locale che niente
+            let local = match_sync::load_page(puuid, range, None, count).await.unwrap_or_default();
+            if local.is_empty() { return Err(e); }
+            eprintln!("[load_recent_matches] sync fallito ({}), uso lo store", e);
+            return Ok(local);
         }
-    }
-    vec![]
-}
-
-async fn fetch_match_detail(match_id: &str, region: &str, client: &Client) -> Value {
-    // Fast-path: già identificato come pre-2026 in questa sessione
-    {
-        let skip = pre2026_skip().await.read().await;
-        if skip.contains(match_id) { return json!({}); }
-    }
-
-    // In-memory cache (session)
-    {
-        let cache = match_cache().await.read().await;
-        if let Some(cached) = cache.get(match_id) {
-            let gc = cached["info"]["gameCreation"].as_u64().unwrap_or(u64::MAX);
-            if gc < SEASON_2026_START_MS {
-                drop(cache);
-                pre2026_skip().await.write().await.insert(match_id.to_string());
-                return json!({});
-            }
-            eprintln!("✓ match cache hit: {}", match_id);
-            return cached.clone();
+        match_sync::spawn_backfill(puuid, region, client.clone(), Some(range.since_ms.unwrap_or(0)));
+        let local = match_sync::load_page(puuid, range, None, count).await.unwrap_or_default();
+        if local.len() as u32 >= count || match_sync::covers(puuid, range.since_ms).await {
+            return Ok(local);
         }
+        eprintln!("[load_recent_matches] store fermo prima di {:?}, fetch diretto", range.since_ms);
     }
 
-    let url = format!("https://{}.api.riotgames.com/lol/match/v5/matches/{}", routing_host(&region), match_id);
-    for attempt in 0..3u32 {
-        match client.get(&url).header("X-Riot-Token", riot_api_key()).send().await {
-            Ok(res) => {
-                if res.status().as_u16() == 429 {
-                    tokio::time::sleep(std::time::Duration::from_millis(2000 * (attempt + 1) as u64)).await;
-                    continue;
-                }
-                let data = res.json::<Value>().await.unwrap_or(json!({}));
-                if data.get("metadata").is_some() {
-                    let gc = data["info"]["gameCreation"].as_u64().unwrap_or(u64::MAX);
-                    if gc < SEASON_2026_START_MS {
-                        pre2026_skip().await.write().await.insert(match_id.to_string());
-                        return json!({});
-                    }
-                    match_cache().await.write().await.insert(match_id.to_string(), data.clone());
-                }
-                return data;
-            }
-            Err(_) => return json!({}),
+    // Riot vuole startTime/endTime in secondi
+    let match_ids = fetch_match_ids_since(
+        puuid, 0, count, range.since_ms.map(|ms| ms / 1000), range.until_ms.map(|ms| ms / 1000), region, client,
+    ).await?;
+    let tasks: Vec<_> = match_ids.iter().map(|id| {
+        let id = id.clone();
+        let region = region.to_string();
+        let client = client.clone();
+        tokio::spawn(async move { fetch_match_detail(&id, &region, &client).await })
+    }).collect();
+    let mut match_details: Vec<Value> = vec![];
+    for task in tasks {
+        if let Ok(Some(detail)) = task.await {
+            let queue_id = detail["info"]["queueId"].as_u64().unwrap_or(1);
+            if queue_id != 0 && range.contains(&detail) { match_details.push(detail); }
         }
     }
-    json!({})
+    // Riordina per data decrescente (il fetch parallelo non garantisce ordine)
+    match_details.sort_by(|a, b| {
+        let ga = a["info"]["gameCreation"].as_u64().unwrap_or(0);
+        let gb = b["info"]["gameCreation"].as_u64().unwrap_or(0);
+        gb.cmp(&ga)
+    });
+    Ok(match_details)
 }
 
 /// Recupera ranked SoloQ (fallback Flex) per un puuid via League-V4.
@@ -413,22 +377,20 @@ async fn fetch_ranked_entry(puuid: String, region: String, client: Client) -> (S
             }
         }
     }
-    let url = format!("https://{}.api.riotgames.com/lol/league/v4/entries/by-puuid/{}", platform_host(&region), puuid);
-    let entries: Vec<Value> = match client.get(&url)
-        .header("X-Riot-Token", riot_api_key())
+    let entries = match league_v4::entries_by_puuid(&region, &puuid)
         .timeout(std::time::Duration::from_secs(10))
-        .send().await
+        .send(&client).await
     {
-        Ok(r) if r.status().as_u16() == 200 => r.json().await.unwrap_or_default(),
-        _ => return (String::new(), String::new(), 0), // non cachare fallimenti
+        Ok(e) => e,
+        Err(_) => return (String::new(), String::new(), 0), // non cachare fallimenti
     };
     let result = {
         let mut found = (String::new(), String::new(), 0i64);
         'outer: for queue in &["RANKED_SOLO_5x5", "RANKED_FLEX_SR"] {
-            if let Some(e) = entries.iter().find(|e| e["queueType"].as_str() == Some(queue)) {
-                let tier = e["tier"].as_str().unwrap_or("").to_uppercase();
-                let rank = e["rank"].as_str().unwrap_or("").to_uppercase();
-                let lp   = e["leaguePoints"].as_i64().unwrap_or(0);
+            if let Some(e) = entries.iter().find(|e| e.queue_type == *queue) {
+                let tier = e.tier.as_deref().unwrap_or("").to_uppercase();
+                let rank = e.rank.as_deref().unwrap_or("").to_uppercase();
+                let lp   = e.league_points;
                 if !tier.is_empty() && tier != "NONE" {
                     found = (tier, rank, lp);
                     break 'outer;
@@ -458,27 +420,14 @@ async fn fetch_smart_data_live(puuid: String, region: String, client: Client) ->
     let r2 = region.clone();
 
     let (summoner_res, masteries_res) = tokio::join!(
-        async move {
-            let url = format!("https://{}.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/{}", platform_host(&r1), p1);
-            let r = c1.get(&url).header("X-Riot-Token", riot_api_key()).send().await.ok()?;
-            if !r.status().is_success() { return None; }
-            r.json::<Value>().await.ok()
-        },
-        async move {
-            let url = format!(
-                "https://{}.api.riotgames.com/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/top?count=1",
-                platform_host(&r2), p2
-            );
-            let r = c2.get(&url).header("X-Riot-Token", riot_api_key()).send().await.ok()?;
-            if !r.status().is_success() { return None; }
-            r.json::<Value>().await.ok()
-        }
+        async move { summoner_v4::by_puuid(&r1, &p1).send(&c1).await.ok() },
+        async move { champion_mastery_v4::top_by_puuid(&r2, &p2, 1).send(&c2).await.ok() }
     );
 
-    let summoner_level   = summoner_res.as_ref().and_then(|s| s["summonerLevel"].as_u64());
+    let summoner_level   = summoner_res.as_ref().map(|s| s.summoner_level);
     let main_champion_id = masteries_res.as_ref()
-        .and_then(|v| v.as_array()).and_then(|a| a.first())
-        .and_then(|m| m["championId"].as_u64());
+        .and_then(|a| a.first())
+        .map(|m| m.champion_id);
 
     Some(json!({
         "summoner_level":      summoner_level,
@@ -491,38 +440,20 @@ async fn fetch_smart_data_live(puuid: String, region: String, client: Client) ->
 }
 
 /// Recupera profilo via Riot API (usato quando LCU non è disponibile).
-async fn fetch_profile_from_riot_api(game_name: &str, tag_line: &str, region: &str, client: &Client) -> Option<Value> {
+async fn fetch_profile_from_riot_api(game_name: &str, tag_line: &str, range: &MatchRange, region: &str, client: &Client) -> Option<Value> {
     eprintln!("[RLP] fetch Riot API per {}", game_name);
-    let puuid = fetch_puuid(game_name, tag_line, region, client).await?;
-
-    let ranked_text = client
-        .get(&format!("https://{}.api.riotgames.com/lol/league/v4/entries/by-puuid/{}", platform_host(region), puuid))
-        .header("X-Riot-Token", riot_api_key())
-        .send().await.ok()?
-        .text().await.unwrap_or_default();
-    let ranked_entries: Value = serde_json::from_str(&ranked_text).unwrap_or(json!([]));
-
-    let summoner: Value = client
-        .get(&format!("https://{}.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/{}", platform_host(region), puuid))
-        .header("X-Riot-Token", riot_api_key())
-        .send().await.ok()?
-        .json().await.unwrap_or(json!({}));
+    let puuid = fetch_puuid(game_name, tag_line, region, client).await.ok()?;
 
-    let match_ids = fetch_match_ids_since(&puuid, 0, 20, None, None, region, client).await;
-    let mut match_details: Vec<Value> = vec![];
-    for id in match_ids.iter() {
-        let detail = fetch_match_detail(id, region, client).await;
-        if detail.get("metadata").is_none() { continue; }
-        let queue_id = detail["info"]["queueId"].as_u64().unwrap_or(1);
-        if queue_id != 0 { match_details.push(detail); }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    let ranked_entries = json!(league_v4::entries_by_puuid(region, &puuid).send(client).await.ok()?);
+    let summoner       = summoner_v4::by_puuid(region, &puuid).send(client).await.ok()?;
+
+    let match_details = load_recent_matches(&puuid, 20, range, region, client).await.unwrap_or_default();
 
     let profile = json!({
         "gameName":       game_name,
         "tagLine":        tag_line,
-        "summonerLevel":  summoner["summonerLevel"],
-        "profileIconId":  summoner["profileIconId"],
+        "summonerLevel":  summoner.summoner_level,
+        "profileIconId":  summoner.profile_icon_id,
         "xpSinceLastLevel":  0,
         "xpUntilNextLevel":  1,
     });
@@ -551,11 +482,11 @@ async fn fetch_profile_from_riot_api(game_name: &str, tag_line: &str, region: &s
     }))
 }
 
-/// Fallback quando LCU non è raggiungibile:
-/// 1. Riot API pubblica (dati freschi)
-/// 2. Cache locale JSON (ultimo fallback)
-async fn offline_fallback(cached_data: &Option<Value>, region: &str) -> Result<Value, String> {
-    if let Some(cache) = cached_data {
+/// Fallback quando LCU non è raggiungibile (o siamo offline):
+/// 1. Riot API pubblica (dati freschi, saltata in modalità offline)
+/// 2. Cache locale JSON + match dello store, marcati `stale_since`
+async fn offline_fallback(cached_data: &Option<Value>, range: &MatchRange, region: &str) -> Result<Value, String> {
+    if let (Some(cache), false) = (cached_data, offline::is_offline()) {
         let game_name = cache["profile"]["gameName"].as_str()
             .or_else(|| cache["profile"]["game_name"].as_str())
             .unwrap_or("");
@@ -569,7 +500,7 @@ async fn offline_fallback(cached_data: &Option<Value>, region: &str) -> Result<V
                 .timeout(std::time::Duration::from_secs(15))
                 .build().unwrap();
 
-            if let Some(fresh) = fetch_profile_from_riot_api(game_name, tag_line, region, &client).await {
+            if let Some(fresh) = fetch_profile_from_riot_api(game_name, tag_line, range, region, &client).await {
                 eprintln!("[RLP] Dati freschi da Riot API (client chiuso).");
                 return Ok(fresh);
             }
@@ -580,12 +511,7 @@ async fn offline_fallback(cached_data: &Option<Value>, region: &str) -> Result<V
     match cached_data {
         Some(cache) => {
             eprintln!("[RLP] Uso cache locale come fallback.");
-            // Applica il filtro stagione anche alla cache su disco
-            let mut c = cache.clone();
-            if let Some(matches) = cache.get("matches").cloned() {
-                c["matches"] = filter_season_matches(matches);
-            }
-            Ok(c)
+            Ok(stale_profile(cache, range, region).await)
         }
         None => Err("CLIENT_CLOSED".into()),
     }
@@ -593,17 +519,6 @@ async fn offline_fallback(cached_data: &Option<Value>, region: &str) -> Result<V
 
 // ── Live Game helpers ─────────────────────────────────────────────────────────
 
-fn spell_id_to_ddragon(id: u64) -> &'static str {
-    match id {
-        4  => "SummonerFlash",    14 => "SummonerDot",
-        12 => "SummonerTeleport", 21 => "SummonerBarrier",
-        3  => "SummonerExhaust",  6  => "SummonerHaste",
-        7  => "SummonerHeal",     1  => "SummonerBoost",
-        11 => "SummonerSmite",    13 => "SummonerMana",
-        32 => "SummonerSnowball", _  => "SummonerFlash",
-    }
-}
-
 /// Chiama la Live Client Data API locale (porta 2999).
 /// Disponibile ONLY quando sei personalmente in partita.
 async fn fetch_live_client_data(client: &Client) -> Option<Value> {
@@ -706,31 +621,25 @@ fn build_live_game_from_lcd(lcd: &Value, my_summoner_name: &str) -> Value {
 }
 
 /// Chiama Spectator-V5 per un puuid.
-async fn fetch_spectator(puuid: &str, region: &str, client: &Client) -> Option<Value> {
-    let url = format!(
-        "https://{}.api.riotgames.com/lol/spectator/v5/active-games/by-summoner/{}",
-        platform_host(region), puuid
-    );
-    let res = match client.get(&url)
-        .header("X-Riot-Token", riot_api_key())
+/// `None` se il giocatore non è in partita (404) o se la chiamata fallisce.
+async fn fetch_spectator(puuid: &str, region: &str, client: &Client) -> Option<CurrentGameInfo> {
+    match spectator_v5::active_game_by_puuid(region, puuid)
         .timeout(std::time::Duration::from_secs(8))
-        .send().await
+        .send(client).await
     {
-        Ok(r)  => r,
-        Err(e) => { eprintln!("[Spectator] Errore: {}", e); return None; }
-    };
-    let code = res.status().as_u16();
-    eprintln!("[Spectator] HTTP {} per puuid={:.20}", code, puuid);
-    if code != 200 { return None; }
-    let data: Value = res.json().await.ok()?;
-    if data.get("status").is_some() { return None; }
-    Some(data)
+        Ok(game) => Some(game),
+        Err(RiotApiError::NotFound) => {
+            eprintln!("[Spectator] nessuna partita per puuid={:.20}", puuid);
+            None
+        }
+        Err(e) => { eprintln!("[Spectator] Errore: {}", e); None }
+    }
 }
 
 /// Normalizza la risposta Spectator V5 nel formato interno.
 /// Fetcha ranked + smart data (summoner_level + mastery) in parallelo per ogni player.
-async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, client: &Client) -> Value {
-    let queue_id   = raw["gameQueueConfigId"].as_u64().unwrap_or(0);
+async fn build_live_game_response(raw: &CurrentGameInfo, my_puuid: &str, region: &str, client: &Client) -> Value {
+    let queue_id   = raw.game_queue_config_id;
     let queue_type = match queue_id {
         420  => "Ranked Solo/Duo", 440 => "Ranked Flex",
         400  => "Normal Draft",    430 => "Normal Blind",
@@ -747,20 +656,24 @@ async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, cli
         _    => "Other",
     }.to_string();
 
-    let game_length     = raw["gameLength"].as_u64().unwrap_or(0);
-    let game_start_time = raw["gameStartTime"].as_i64().unwrap_or(0);
-    let empty_arr = vec![];
-
-    let banned: Vec<Value> = raw["bannedChampions"].as_array().unwrap_or(&empty_arr)
-        .iter().map(|b| json!({
-            "champion_id": b["championId"].as_i64().unwrap_or(-1),
-            "team":        if b["teamId"].as_u64().unwrap_or(100) == 100 { "ORDER" } else { "CHAOS" },
-            "pick_turn":   b["pickTurn"].as_u64().unwrap_or(0),
+    let game_length     = raw.game_length;
+    let game_start_time = raw.game_start_time;
+    let ddragon = static_data::static_data().await.ok();
+    // Chiave immagine DDragon della spell ("SummonerFlash" se sconosciuta)
+    let spell_key = |id: u64| ddragon.as_ref()
+        .and_then(|d| d.spells.get(id as u32))
+        .map(|sp| sp.key.clone())
+        .unwrap_or_else(|| "SummonerFlash".to_string());
+
+    let banned: Vec<Value> = raw.banned_champions.iter().map(|b| json!({
+            "champion_id": b.champion_id,
+            "team":        if b.team_id == 100 { "ORDER" } else { "CHAOS" },
+            "pick_turn":   b.pick_turn,
         })).collect();
 
-    let participants = raw["participants"].as_array().unwrap_or(&empty_arr);
+    let participants = &raw.participants;
     let puuids: Vec<String> = participants.iter()
-        .map(|p| p["puuid"].as_str().unwrap_or("").to_string())
+        .map(|p| p.puuid.clone().unwrap_or_default())
         .collect();
 
     // Ranked + smart data: tutti in parallelo (2 API call per player)
@@ -793,13 +706,12 @@ async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, cli
     let players: Vec<Value> = participants.iter()
         .zip(ranks.iter())
         .map(|(p, (tier, rank, lp))| {
-            let puuid_p      = p["puuid"].as_str().unwrap_or("");
-            let champ_id     = p["championId"].as_u64().unwrap_or(0);
-            let team_id      = p["teamId"].as_u64().unwrap_or(100);
-            let profile_icon = p["profileIconId"].as_u64().unwrap_or(0);
-            let riot_id      = p["riotId"].as_str().unwrap_or("");
-            let name         = if !riot_id.is_empty() { riot_id.to_string() }
-                               else { p["summonerName"].as_str().unwrap_or("").to_string() };
+            let puuid_p      = p.puuid.as_deref().unwrap_or("");
+            let champ_id     = p.champion_id;
+            let team_id      = p.team_id;
+            let profile_icon = p.profile_icon_id;
+            let name         = if !p.riot_id.is_empty() { p.riot_id.clone() }
+                               else { p.summoner_name.clone() };
             let is_me        = !my_puuid.is_empty() && puuid_p == my_puuid;
             let smart        = smart_map.get(puuid_p);
 
@@ -817,8 +729,8 @@ async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, cli
                 "champion_name":     "",
                 "profile_icon_id":   profile_icon,
                 "team":              if team_id == 100 { "ORDER" } else { "CHAOS" },
-                "spell1":            spell_id_to_ddragon(p["spell1Id"].as_u64().unwrap_or(0)),
-                "spell2":            spell_id_to_ddragon(p["spell2Id"].as_u64().unwrap_or(0)),
+                "spell1":            spell_key(p.spell1_id),
+                "spell2":            spell_key(p.spell2_id),
                 "tier":              tier,
                 "rank":              rank,
                 "lp":                lp,
@@ -838,7 +750,7 @@ async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, cli
         "game_time":        game_length,
         "game_start_time":  game_start_time,
         "queue_type":       queue_type,
-        "game_id":          raw["gameId"],
+        "game_id":          raw.game_id,
         "banned_champions": banned,
         "players":          players,
         "duo_pairs":        [],
@@ -849,48 +761,35 @@ async fn build_live_game_response(raw: &Value, my_puuid: &str, region: &str, cli
 
 /// Carica il profilo del giocatore loggato via LCU + Riot API.
 /// Fallback: Riot API diretta → cache locale.
+/// `range` opzionale (stagione/split/patch/date): default = stagione corrente.
 #[tauri::command]
-async fn get_profiles(handle: AppHandle, region: String) -> Result<Value, String> {
+async fn get_profiles(handle: AppHandle, lcu: State<'_, LcuConnection>, region: String, range: Option<SeasonFilter>) -> Result<Value, String> {
+    let filter = range.unwrap_or_default();
+    let range  = seasons::calendar().resolve(&filter)?;
     let cache_p    = get_cache_path(&handle);
     let cached_data: Option<Value> = fs::read_to_string(&cache_p).ok()
         .and_then(|s| serde_json::from_str(&s).ok());
 
-    let lock_path = match get_lockfile_path() {
-        Some(p) => p,
+    if offline::is_offline() {
+        return offline_fallback(&cached_data, &range, &region).await;
+    }
+
+    let lcu = match lcu.client() {
+        Some(c) => c,
         None    => {
             eprintln!("[RLP] Client chiuso (no lockfile), offline fallback.");
-            return offline_fallback(&cached_data, &region).await;
+            return offline_fallback(&cached_data, &range, &region).await;
         }
     };
+    let client = lcu.http().clone();
 
-    if !lock_path.exists() {
-        return offline_fallback(&cached_data, &region).await;
-    }
-
-    let content  = fs::read_to_string(&lock_path).map_err(|_| "Errore lockfile")?;
-    let parts: Vec<&str> = content.split(':').collect();
-    let port     = parts[2];
-    let password = parts[3];
-    let auth     = general_purpose::STANDARD.encode(format!("riot:{}", password));
-    let client   = Client::builder().danger_accept_invalid_certs(true).build().unwrap();
-
-    let lcu_resp = match client
-        .get(&format!("https://127.0.0.1:{}/lol-summoner/v1/current-summoner", port))
-        .header("Authorization", format!("Basic {}", auth))
-        .send().await
-    {
+    let lcu_resp = match lcu.request(Method::GET, "/lol-summoner/v1/current-summoner").send().await {
         Ok(r) => r,
-        Err(e) => {
-            let msg = e.to_string();
-            if msg.contains("connection refused") || msg.contains("actively refused")
-                || msg.contains("tcp connect") || msg.contains("os error 10061")
-                || msg.contains("os error 111")
-            {
-                eprintln!("[RLP] LCU non raggiungibile, offline fallback.");
-                return offline_fallback(&cached_data, &region).await;
-            }
-            return Err(msg);
+        Err(e) if lcu::is_refused(&e) => {
+            eprintln!("[RLP] LCU non raggiungibile, offline fallback.");
+            return offline_fallback(&cached_data, &range, &region).await;
         }
+        Err(e) => return Err(e.to_string()),
     };
 
     let current_profile: Value = lcu_resp.json().await.map_err(|_| "Errore JSON profilo")?;
@@ -905,8 +804,14 @@ async fn get_profiles(handle: AppHandle, region: String) -> Result<Value, String
         return Err("Impossibile leggere tagLine dal client".into());
     }
 
-    let puuid = fetch_puuid(&game_name, &tag_line, &region, &client).await
-        .ok_or("Impossibile recuperare PUUID da Riot API")?;
+    let puuid = match fetch_puuid(&game_name, &tag_line, &region, &client).await {
+        Ok(p) => p,
+        Err(e) if e.is_unreachable() && cached_data.is_some() => {
+            eprintln!("[RLP] Riot API non raggiungibile ({}), uso cache locale.", e);
+            return offline_fallback(&cached_data, &range, &region).await;
+        }
+        Err(e) => return Err(format!("Impossibile recuperare PUUID da Riot API: {}", e)),
+    };
 
     // Cache locale valida (< 30 min, stesso puuid, match con oggetti)
     if let Some(cache) = &cached_data {
@@ -918,11 +823,12 @@ async fn get_profiles(handle: AppHandle, region: String) -> Result<Value, String
             .map(|dt| (chrono::Utc::now() - dt.with_timezone(&chrono::Utc)).num_minutes() < 30)
             .unwrap_or(false);
 
-        if cached_puuid == puuid && !puuid.is_empty() && has_match_objects && is_fresh {
+        // La cache su disco contiene solo le ultime partite: vale solo senza filtri espliciti
+        if filter.is_default() && cached_puuid == puuid && !puuid.is_empty() && has_match_objects && is_fresh {
             if let Some(matches) = cache.get("matches").cloned() {
-                let filtered = filter_season_matches(matches);
+                let filtered = filter_season_matches(matches, &range);
                 // Restituisce SEMPRE la cache filtrata, anche se vuota:
-                // in questo modo non trapelano mai partite pre-Season 2026.
+                // in questo modo non trapelano mai partite fuori stagione.
                 let mut c = cache.clone();
                 c["matches"] = filtered;
                 eprintln!("[RLP] Cache locale valida (< 30 min).");
@@ -933,21 +839,11 @@ async fn get_profiles(handle: AppHandle, region: String) -> Result<Value, String
         }
     }
 
-    let ranked: Value = client
-        .get(&format!("https://127.0.0.1:{}/lol-ranked/v1/current-ranked-stats", port))
-        .header("Authorization", format!("Basic {}", auth))
+    let ranked: Value = lcu.request(Method::GET, "/lol-ranked/v1/current-ranked-stats")
         .send().await.map_err(|e| e.to_string())?
         .json().await.map_err(|_| "Errore JSON Rank")?;
 
-    let match_ids = fetch_match_ids_since(&puuid, 0, 20, None, None, &region, &client).await;
-    let mut match_details: Vec<Value> = vec![];
-    for id in match_ids.iter() {
-        let detail = fetch_match_detail(id, &region, &client).await;
-        if detail.get("metadata").is_none() { continue; }
-        let queue_id = detail["info"]["queueId"].as_u64().unwrap_or(1);
-        if queue_id != 0 { match_details.push(detail); }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    let match_details = load_recent_matches(&puuid, 20, &range, &region, &client).await?;
 
     let final_data = json!({
         "puuid":        puuid,
@@ -973,17 +869,48 @@ async fn get_profiles(handle: AppHandle, region: String) -> Result<Value, String
         );
     }
 
-    let _ = fs::write(cache_p, final_data.to_string());
+    if filter.is_default() {
+        let _ = fs::write(cache_p, final_data.to_string());
+    }
     Ok(final_data)
 }
 
-/// Carica altri match per il summoner (paginazione offset-based).
-/// `start` = numero di partite già caricate → Riot le skippa e torna le successive.
+/// Carica altri match per il summoner.
+/// `before` = gameCreation (ms) del match più vecchio già mostrato: cursore stabile anche
+/// se nel frattempo arrivano partite nuove. Senza cursore e con `start == 0` è un refresh
+/// (sync incrementale, ultime 10). `start` (offset Riot) resta solo come fallback legacy.
+/// `range` come in `get_profiles`.
 #[tauri::command]
-async fn get_more_matches(puuid: String, start: u32, region: String) -> Result<Value, String> {
+async fn get_more_matches(puuid: String, start: u32, before: Option<u64>, region: String, range: Option<SeasonFilter>) -> Result<Value, String> {
+    let range  = seasons::calendar().resolve(&range.unwrap_or_default())?;
     let client = Client::builder().danger_accept_invalid_certs(true).build().unwrap();
-    eprintln!("[get_more_matches] start offset: {}", start);
-    let match_ids = fetch_match_ids_since(&puuid, start, 10, None, None, &region, &client).await;
+
+    // Offline: solo lo store (lo sync riparte al ritorno della rete)
+    if offline::is_offline() {
+        let page = match_sync::load_page(&puuid, &range, before, 10).await.unwrap_or_default();
+        offline::queue(offline::RefreshJob::Matches { puuid: puuid.clone(), region });
+        return Ok(offline::mark_stale(json!(page), &matches_stale_since(&puuid).await));
+    }
+
+    if start == 0 && before.is_none() {
+        return Ok(json!(load_recent_matches(&puuid, 10, &range, &region, &client).await?));
+    }
+
+    // Prima lo store locale; se non arriva così indietro (backfill in corso) chiediamo a Riot
+    if let Some(before) = before {
+        if let Some(page) = match_sync::load_page(&puuid, &range, Some(before), 10).await {
+            if page.len() == 10 { return Ok(json!(page)); }
+        }
+    }
+
+    // Riot vuole startTime/endTime in secondi
+    let start_time = range.since_ms.map(|ms| ms / 1000);
+    let (offset, end_time) = match before {
+        Some(b) => (0, Some(range.until_ms.map_or(b, |u| u.min(b)) / 1000)),
+        None    => (start, range.until_ms.map(|ms| ms / 1000)),
+    };
+    eprintln!("[get_more_matches] offset={} startTime={:?} endTime={:?}", offset, start_time, end_time);
+    let match_ids = fetch_match_ids_since(&puuid, offset, 10, start_time, end_time, &region, &client).await?;
     eprintln!("[get_more_matches] IDs ricevuti da Riot: {} -> {:?}", match_ids.len(), match_ids);
 
     // Fetch parallelo — elimina i 150ms * 10 = 1.5s di sleep artificiale
@@ -996,18 +923,16 @@ async fn get_more_matches(puuid: String, start: u32, region: String) -> Result<V
 
     let mut details: Vec<Value> = vec![];
     for task in tasks {
-        if let Ok(detail) = task.await {
+        if let Ok(Some(detail)) = task.await {
             let mid = detail["metadata"]["matchId"].as_str().unwrap_or("?");
             let gc  = detail["info"]["gameCreation"].as_u64().unwrap_or(0);
             let qid = detail["info"]["queueId"].as_u64().unwrap_or(999);
-            if detail.get("metadata").is_none() {
-                eprintln!("[get_more_matches] SCARTATO (no metadata): {}", mid);
-                continue;
-            }
             if qid == 0 {
                 eprintln!("[get_more_matches] SCARTATO (queueId=0 custom): {} gc={}", mid, gc);
                 continue;
             }
+            // endTime è in secondi: può tornare anche il match del cursore
+            if before.is_some_and(|b| gc >= b) || !range.contains(&detail) { continue; }
             eprintln!("[get_more_matches] OK: {} gc={} queueId={}", mid, gc, qid);
             details.push(detail);
         }
@@ -1022,80 +947,54 @@ async fn get_more_matches(puuid: String, start: u32, region: String) -> Result<V
 }
 
 /// Cerca un summoner per nome#tag via Riot API.
-/// Cache in-memory TTL 10 minuti.
+/// Cache in-memory TTL 10 minuti (solo senza `range`, default = stagione corrente).
 #[tauri::command]
-async fn search_summoner(game_name: String, tag_line: String, region: String) -> Result<Value, String> {
+async fn search_summoner(game_name: String, tag_line: String, region: String, range: Option<SeasonFilter>) -> Result<Value, String> {
+    let filter = range.unwrap_or_default();
+    let range  = seasons::calendar().resolve(&filter)?;
     let client = Client::builder().danger_accept_invalid_certs(true).build().unwrap();
 
-    let puuid = fetch_puuid(&game_name, &tag_line, &region, &client).await
-        .ok_or("Summoner non trovato. Controlla nome e tag.")?;
+    let puuid = match fetch_puuid(&game_name, &tag_line, &region, &client).await {
+        Ok(p) => p,
+        Err(RiotApiError::NotFound) => return Err("Summoner non trovato. Controlla nome e tag.".into()),
+        // Offline / rete assente: l'ultimo risultato salvato, se l'abbiamo già cercato
+        Err(e) if e.is_unreachable() => return stale_summoner(&game_name, &tag_line, &range, &region).await
+            .ok_or(format!("{} (summoner non presente nell'archivio locale)", e)),
+        Err(e) => return Err(e.to_string()),
+    };
 
     // Cache check (TTL 10 min)
-    {
+    if filter.is_default() {
         let cache = summoner_cache().await.read().await;
         if let Some((ts, cached)) = cache.get(&puuid) {
             if ts.elapsed() < std::time::Duration::from_secs(600) {
                 eprintln!("[search] cache HIT puuid={:.20}", puuid);
                 let mut c = cached.clone();
                 if let Some(matches) = cached.get("matches").cloned() {
-                    c["matches"] = filter_season_matches(matches);
+                    c["matches"] = filter_season_matches(matches, &range);
                 }
                 return Ok(c);
             }
         }
     }
 
-    let account: Value = client
-        .get(&format!("https://{}.api.riotgames.com/riot/account/v1/accounts/by-puuid/{}", routing_host(&region), puuid))
-        .header("X-Riot-Token", riot_api_key())
-        .send().await.map_err(|e| e.to_string())?
-        .json().await.map_err(|_| "Errore JSON account")?;
-
-    let ranked_text = client
-        .get(&format!("https://{}.api.riotgames.com/lol/league/v4/entries/by-puuid/{}", platform_host(&region), puuid))
-        .header("X-Riot-Token", riot_api_key())
-        .send().await.map_err(|e| e.to_string())?
-        .text().await.unwrap_or_default();
-    let ranked_entries: Value = serde_json::from_str(&ranked_text).unwrap_or(json!([]));
+    let account = account_v1::by_puuid(&region, &puuid).send(&client).await?;
 
-    let summoner_res = client
-        .get(&format!("https://{}.api.riotgames.com/lol/summoner/v4/summoners/by-puuid/{}", platform_host(&region), puuid))
-        .header("X-Riot-Token", riot_api_key())
-        .send().await.map_err(|e| e.to_string())?;
+    let ranked_entries = json!(league_v4::entries_by_puuid(&region, &puuid).send(&client).await
+        .unwrap_or_default());
 
     // ✅ FIX: se il PUUID non esiste su questa platform (es. giocatore EUNE cercato su EUW)
     // Riot restituisce 404 — lo intercettiamo e mostriamo un errore chiaro all'utente.
-    if summoner_res.status().as_u16() == 404 {
-        return Err(format!(
+    let summoner = match summoner_v4::by_puuid(&region, &puuid).send(&client).await {
+        Ok(s) => s,
+        Err(RiotApiError::NotFound) => return Err(format!(
             "Summoner non trovato su {}. Prova a cambiare region.",
             region.to_uppercase()
-        ));
-    }
+        )),
+        Err(e) => return Err(e.to_string()),
+    };
 
-    let summoner: Value = summoner_res.json().await.unwrap_or(json!({}));
-
-    let match_ids = fetch_match_ids_since(&puuid, 0, 20, None, None, &region, &client).await;
-    // Fetch parallelo — 20 richieste concorrenti, molto più veloce del loop sequenziale
-    let tasks: Vec<_> = match_ids.iter().map(|id| {
-        let id = id.clone();
-        let region = region.clone();
-        let client = client.clone();
-        tokio::spawn(async move { fetch_match_detail(&id, &region, &client).await })
-    }).collect();
-    let mut match_details: Vec<Value> = vec![];
-    for task in tasks {
-        if let Ok(detail) = task.await {
-            if detail.get("metadata").is_none() { continue; }
-            let queue_id = detail["info"]["queueId"].as_u64().unwrap_or(1);
-            if queue_id != 0 { match_details.push(detail); }
-        }
-    }
-    // Riordina per data decrescente (il fetch parallelo non garantisce ordine)
-    match_details.sort_by(|a, b| {
-        let ga = a["info"]["gameCreation"].as_u64().unwrap_or(0);
-        let gb = b["info"]["gameCreation"].as_u64().unwrap_or(0);
-        gb.cmp(&ga)
-    });
+    let match_details = load_recent_matches(&puuid, 20, &range, &region, &client).await?;
 
     let normalized_entries: Vec<Value> = ranked_entries.as_array().unwrap_or(&vec![])
         .iter().map(|e| {
@@ -1107,10 +1006,10 @@ async fn search_summoner(game_name: String, tag_line: String, region: String) ->
         }).collect();
 
     let profile = json!({
-        "gameName":          account["gameName"],
-        "tagLine":           account["tagLine"],
-        "summonerLevel":     summoner["summonerLevel"],
-        "profileIconId":     summoner["profileIconId"],
+        "gameName":          account.game_name,
+        "tagLine":           account.tag_line,
+        "summonerLevel":     summoner.summoner_level,
+        "profileIconId":     summoner.profile_icon_id,
         "xpSinceLastLevel":  0,
         "xpUntilNextLevel":  1,
     });
@@ -1135,15 +1034,28 @@ async fn search_summoner(game_name: String, tag_line: String, region: String) ->
         let solo_lp   = solo.and_then(|e| e["leaguePoints"].as_i64()).unwrap_or(0);
         db_index_summoner(
             puuid.clone(),
-            account["gameName"].as_str().unwrap_or("").to_string(),
-            account["tagLine"].as_str().unwrap_or("").to_string(),
+            account.game_name.clone().unwrap_or_default(),
+            account.tag_line.clone().unwrap_or_default(),
             icon_id, level, solo_tier, solo_rank, solo_lp,
         );
     }
 
+    // Snapshot per l'offline (i match sono già nello store)
+    {
+        let mut snap = result.clone();
+        snap["matches"] = json!([]);
+        let (owner, riot_id) = (puuid.clone(), format!("{}#{}", game_name, tag_line).to_lowercase());
+        match_store::blocking(move |store| {
+            store.put_snapshot("summoner", &owner, &snap);
+            store.put_snapshot("riot_id", &riot_id, &json!(owner));
+        }).await;
+    }
+
     // Salva in cache
-    summoner_cache().await.write().await
-        .insert(puuid, (std::time::Instant::now(), result.clone()));
+    if filter.is_default() {
+        summoner_cache().await.write().await
+            .insert(puuid, (std::time::Instant::now(), result.clone()));
+    }
 
     Ok(result)
 }
@@ -1241,7 +1153,7 @@ fn index_live_players(players: &Value) {
 
 /// Live game per il giocatore loggato: LCD (porta 2999) + Spectator V5 in parallelo.
 #[tauri::command]
-async fn get_live_game(region: String) -> Result<Value, String> {
+async fn get_live_game(lcu: State<'_, LcuConnection>, region: String) -> Result<Value, String> {
     // Cache hit — non serviamo se dati ranked incompleti (timeout al primo caricamento).
     {
         let cache = live_game_cache().await.read().await;
@@ -1260,18 +1172,10 @@ async fn get_live_game(region: String) -> Result<Value, String> {
             }
         }
     }
-    let lock_path = get_lockfile_path().ok_or("CLIENT_CLOSED")?;
-    let content   = fs::read_to_string(&lock_path).map_err(|_| "CLIENT_CLOSED")?;
-    let parts: Vec<&str> = content.split(':').collect();
-    if parts.len() < 4 { return Err("CLIENT_CLOSED".into()); }
-    let port     = parts[2];
-    let password = parts[3].trim_end_matches('\n').trim_end_matches('\r');
-    let auth     = general_purpose::STANDARD.encode(format!("riot:{}", password));
-    let client   = Client::builder().danger_accept_invalid_certs(true).build().unwrap();
-
-    let me: Value = client
-        .get(&format!("https://127.0.0.1:{}/lol-summoner/v1/current-summoner", port))
-        .header("Authorization", format!("Basic {}", auth))
+    let lcu    = lcu.client().ok_or("CLIENT_CLOSED")?;
+    let client = lcu.http().clone();
+
+    let me: Value = lcu.request(Method::GET, "/lol-summoner/v1/current-summoner")
         .send().await.map_err(|_| "CLIENT_CLOSED")?
         .json().await.unwrap_or(json!({}));
 
@@ -1308,11 +1212,10 @@ async fn get_live_game(region: String) -> Result<Value, String> {
         // così evitiamo fetch_puuid x10 e usiamo i puuid già presenti.
         let mut puuid_map: HashMap<String, String> = HashMap::new();
         if let Some(ref raw_spec) = spec_raw {
-            let empty = vec![];
-            for p in raw_spec["participants"].as_array().unwrap_or(&empty) {
-                let puuid   = p["puuid"].as_str().unwrap_or("").to_string();
-                let riot_id = p["riotId"].as_str().unwrap_or("").to_string();
-                let name    = p["summonerName"].as_str().unwrap_or("").to_string();
+            for p in &raw_spec.participants {
+                let puuid   = p.puuid.clone().unwrap_or_default();
+                let riot_id = p.riot_id.clone();
+                let name    = p.summoner_name.clone();
                 if !puuid.is_empty() {
                     if !riot_id.is_empty() { puuid_map.insert(riot_id, puuid.clone()); }
                     if !name.is_empty()    { puuid_map.insert(name,    puuid); }
@@ -1453,6 +1356,9 @@ async fn check_live_game(puuid: String, region: String) -> Result<Value, String>
 /// Recupera le maestrie del summoner — cache in-memory TTL 10 minuti.
 #[tauri::command]
 async fn get_summoner_masteries(puuid: String, region: String) -> Result<Value, String> {
+    if offline::is_offline() {
+        return stale_masteries(&puuid, &region).await.ok_or(RiotApiError::Offline.to_string());
+    }
     {
         let cache = masteries_cache().await.read().await;
         if let Some((ts, cached)) = cache.get(&puuid) {
@@ -1467,18 +1373,14 @@ async fn get_summoner_masteries(puuid: String, region: String) -> Result<Value,
         .timeout(std::time::Duration::from_secs(15))
         .build().unwrap();
 
-    let url = format!(
-        "https://{}.api.riotgames.com/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/top?count=20",
-        platform_host(&region), puuid
-    );
-    let res = client.get(&url).header("X-Riot-Token", riot_api_key())
-        .send().await.map_err(|e| e.to_string())?;
-
-    let status = res.status().as_u16();
-    if status == 404 { return Ok(json!([])); }
-    if status != 200 { return Err(format!("Riot API errore {}", status)); }
-
-    let masteries: Value = res.json().await.map_err(|_| "Errore JSON masteries")?;
+    let masteries = match champion_mastery_v4::top_by_puuid(&region, &puuid, 20).send(&client).await {
+        Ok(m) => json!(m),
+        Err(RiotApiError::NotFound) => return Ok(json!([])),
+        Err(e) if e.is_unreachable() => return stale_masteries(&puuid, &region).await.ok_or(e.to_string()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let (owner, snap) = (puuid.clone(), masteries.clone());
+    match_store::blocking(move |store| store.put_snapshot("masteries", &owner, &snap)).await;
     masteries_cache().await.write().await
         .insert(puuid, (std::time::Instant::now(), masteries.clone()));
     Ok(masteries)
@@ -1492,22 +1394,14 @@ async fn get_match_timeline(match_id: String, region: String) -> Result<Value, S
         .timeout(std::time::Duration::from_secs(15))
         .build().unwrap();
 
-    let url = format!(
-        "https://{}.api.riotgames.com/lol/match/v5/matches/{}/timeline",
-        routing_host(&region), match_id
-    );
-    for attempt in 0..3u32 {
-        let res = client.get(&url).header("X-Riot-Token", riot_api_key())
-            .send().await.map_err(|e| e.to_string())?;
-        let status = res.status().as_u16();
-        if status == 429 {
-            tokio::time::sleep(std::time::Duration::from_millis(2000 * (attempt + 1) as u64)).await;
-            continue;
-        }
-        if status != 200 { return Err(format!("Riot API timeline error {}", status)); }
-        return res.json().await.map_err(|_| "Errore JSON timeline".into());
+    let id = match_id.clone();
+    if let Some(cached) = match_store::blocking(move |s| s.get_timeline(&id)).await.flatten() {
+        return Ok(cached);
     }
-    Err("Timeline fetch fallita dopo 3 tentativi".into())
+    let timeline = match_v5::timeline(&region, &match_id).send(&client).await?;
+    let value = json!(timeline);
+    match_store::blocking(move |s| s.put_timeline(&match_id, &timeline)).await;
+    Ok(value)
 }
 
 /// Suggerisce summoner per l'autocomplete a partire da quelli già cercati/visti.
@@ -1572,30 +1466,14 @@ async fn search_summoner_suggestions(query: String) -> Result<Value, String> {
 /// Restituisce la regione normalizzata in minuscolo (es. "euw", "na", "kr")
 /// oppure None se il client non è aperto.
 #[tauri::command]
-async fn get_client_region() -> Result<String, String> {
-    let lock_path = get_lockfile_path().ok_or("CLIENT_CLOSED")?;
-    let content = fs::read_to_string(&lock_path).map_err(|_| "CLIENT_CLOSED")?;
-    let parts: Vec<&str> = content.split(':').collect();
-    if parts.len() < 4 { return Err("CLIENT_CLOSED".into()); }
-    let port     = parts[2];
-    let password = parts[3].trim_end_matches('\n').trim_end_matches('\r');
-    let auth     = general_purpose::STANDARD.encode(format!("riot:{}", password));
-    let client   = Client::builder().danger_accept_invalid_certs(true)
-        .timeout(std::time::Duration::from_secs(5))
-        .build().unwrap();
+async fn get_client_region(lcu: State<'_, LcuConnection>) -> Result<String, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
 
     // /riotclient/region-locale restituisce { "region": "EUW1", "locale": "it_IT", ... }
-    let resp = client
-        .get(&format!("https://127.0.0.1:{}/riotclient/region-locale", port))
-        .header("Authorization", format!("Basic {}", auth))
+    let resp = lcu.request(Method::GET, "/riotclient/region-locale")
+        .timeout(std::time::Duration::from_secs(5))
         .send().await
-        .map_err(|e| {
-            let m = e.to_string();
-            if m.contains("connection refused") || m.contains("actively refused")
-                || m.contains("os error 10061") || m.contains("os error 111") {
-                "CLIENT_CLOSED".to_string()
-            } else { m }
-        })?;
+        .map_err(|e| if lcu::is_refused(&e) { "CLIENT_CLOSED".to_string() } else { e.to_string() })?;
 
     if !resp.status().is_success() { return Err("CLIENT_CLOSED".into()); }
 
@@ -1651,19 +1529,42 @@ async fn get_recent_stats(puuid: String, region: String) -> Result<Value, String
     // Soglia in millisecondi per confrontare con gameCreation (che è in ms)
     let seven_days_ago_ms = seven_days_ago * 1000;
 
-    let ids_7d = fetch_match_ids_since(&puuid, 0, 100, Some(seven_days_ago), None, &region, &client).await;
-    let ids_recent = fetch_match_ids_since(&puuid, 0, 20, None, None, &region, &client).await;
+    let ids = if offline::is_offline() {
+        Err(RiotApiError::Offline)
+    } else {
+        async { Ok::<_, RiotApiError>((
+            fetch_match_ids_since(&puuid, 0, 100, Some(seven_days_ago), None, &region, &client).await?,
+            fetch_match_ids_since(&puuid, 0, 20, None, None, &region, &client).await?,
+        )) }.await
+    };
+    let (ids_7d, ids_recent) = match ids {
+        Ok(ids) => ids,
+        // Offline (o Riot irraggiungibile): stesse statistiche calcolate sullo store
+        Err(e) if e.is_unreachable() => {
+            let owner = puuid.clone();
+            let (matches_7d, matches_recent) = match_store::blocking(move |store| (
+                store.matches_for_puuid(&owner, &match_store::MatchQuery {
+                    since_ms: Some(seven_days_ago_ms), limit: Some(100), ..Default::default()
+                }),
+                store.matches_for_puuid(&owner, &match_store::MatchQuery {
+                    limit: Some(20), ..Default::default()
+                }),
+            )).await.ok_or(e.to_string())?;
+            offline::queue(offline::RefreshJob::Matches { puuid: puuid.clone(), region });
+            let stats = recent_stats(&puuid, &matches_7d, &matches_recent);
+            return Ok(offline::mark_stale(stats, &matches_stale_since(&puuid).await));
+        }
+        Err(e) => return Err(e.to_string()),
+    };
     eprintln!("[get_recent_stats] 7d={} recent={} cutoff_ms={}", ids_7d.len(), ids_recent.len(), seven_days_ago_ms);
 
     let tasks_7d: Vec<_> = ids_7d.iter().map(|id| {
         let id = id.clone(); let r = region.clone(); let c = client.clone();
         tokio::spawn(async move { fetch_match_detail(&id, &r, &c).await })
     }).collect();
-    let empty_arr: Vec<Value> = vec![];
     let mut matches_7d: Vec<Value> = vec![];
     for t in tasks_7d {
-        if let Ok(m) = t.await {
-            if m.get("metadata").is_none() { continue; }
+        if let Ok(Some(m)) = t.await {
             if m["info"]["queueId"].as_u64().unwrap_or(0) == 0 { continue; }
             // Filtro esplicito per data: la cache in-memory potrebbe restituire partite
             // precedenti ai 7 giorni se già erano state fetchate da altre chiamate.
@@ -1676,11 +1577,32 @@ async fn get_recent_stats(puuid: String, region: String) -> Result<Value, String
         }
     }
 
+    // Fetch ultimi 20 match per compagni
+    let tasks_r: Vec<_> = ids_recent.iter().map(|id| {
+        let id = id.clone(); let r = region.clone(); let c = client.clone();
+        tokio::spawn(async move { fetch_match_detail(&id, &r, &c).await })
+    }).collect();
+    let mut matches_recent: Vec<Value> = vec![];
+    for t in tasks_r {
+        if let Ok(Some(m)) = t.await {
+            if m["info"]["queueId"].as_u64().unwrap_or(0) != 0 {
+                matches_recent.push(m);
+            }
+        }
+    }
+
+    Ok(recent_stats(&puuid, &matches_7d, &matches_recent))
+}
+
+/// Winrate per campione (ultimi 7 giorni) e compagni più frequenti (ultimi 20 match).
+fn recent_stats(puuid: &str, matches_7d: &[Value], matches_recent: &[Value]) -> Value {
+    let empty_arr: Vec<Value> = vec![];
+
     // Winrate per campione (ultimi 7 giorni)
     let mut champ_map: std::collections::HashMap<String, (u32, u32)> = std::collections::HashMap::new();
-    for m in &matches_7d {
+    for m in matches_7d {
         let parts = m["info"]["participants"].as_array().unwrap_or(&empty_arr);
-        if let Some(me) = parts.iter().find(|p| p["puuid"].as_str() == Some(&puuid)) {
+        if let Some(me) = parts.iter().find(|p| p["puuid"].as_str() == Some(puuid)) {
             let champ = me["championName"].as_str().unwrap_or("").to_string();
             if champ.is_empty() { continue; }
             let win = me["win"].as_bool().unwrap_or(false);
@@ -1695,30 +1617,16 @@ async fn get_recent_stats(puuid: String, region: String) -> Result<Value, String
     }).collect();
     champ_wr.sort_by(|a, b| b["games"].as_u64().cmp(&a["games"].as_u64()));
 
-    // Fetch ultimi 20 match per compagni
-    let tasks_r: Vec<_> = ids_recent.iter().map(|id| {
-        let id = id.clone(); let r = region.clone(); let c = client.clone();
-        tokio::spawn(async move { fetch_match_detail(&id, &r, &c).await })
-    }).collect();
-    let mut matches_recent: Vec<Value> = vec![];
-    for t in tasks_r {
-        if let Ok(m) = t.await {
-            if m.get("metadata").is_some() && m["info"]["queueId"].as_u64().unwrap_or(0) != 0 {
-                matches_recent.push(m);
-            }
-        }
-    }
-
     // ✅ FIX: keyed per puuid, supporta riotIdGameName, fix profileIconId
     let mut ally_map: std::collections::HashMap<String, serde_json::Map<String, Value>> = std::collections::HashMap::new();
-    for m in &matches_recent {
+    for m in matches_recent {
         let parts = m["info"]["participants"].as_array().unwrap_or(&empty_arr);
-        let me = parts.iter().find(|p| p["puuid"].as_str() == Some(&puuid));
+        let me = parts.iter().find(|p| p["puuid"].as_str() == Some(puuid));
         let my_team = me.and_then(|p| p["teamId"].as_u64()).unwrap_or(0);
         if my_team == 0 { continue; }
         let my_win = me.and_then(|p| p["win"].as_bool()).unwrap_or(false);
         for p in parts {
-            if p["puuid"].as_str() == Some(&puuid) { continue; }
+            if p["puuid"].as_str() == Some(puuid) { continue; }
             if p["teamId"].as_u64().unwrap_or(0) != my_team { continue; }
             let ally_puuid = p["puuid"].as_str().unwrap_or("").to_string();
             let name = p["riotIdGameName"].as_str()
@@ -1758,13 +1666,39 @@ async fn get_recent_stats(puuid: String, region: String) -> Result<Value, String
         }
     }
 
-    Ok(json!({ "champWr7d": champ_wr, "recentAllies": allies }))
+    json!({ "champWr7d": champ_wr, "recentAllies": allies })
 }
 
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .setup(|app| {
+            // Il rate limiter Riot notifica la profondità della coda al frontend
+            rate_limit::set_app_handle(app.handle().clone());
+            offline::set_app_handle(app.handle().clone());
+            // Connessione LCU condivisa: il watcher segue apertura/chiusura del client
+            let lcu = LcuConnection::new(app.handle().clone());
+            lcu.spawn_watcher();
+            lcu_events::spawn(app.handle().clone(), lcu.clone());
+            app.manage(lcu);
+            match app.path().app_data_dir() {
+                Ok(dir) => {
+                    seasons::init(&dir);
+                    static_data::init(&dir);
+                    rune_pages::init(&dir);
+                    opgg::init(&dir);
+                    match_store::init(&dir.join("matches.sqlite"));
+                    build_provider::init(&dir);
+                    summoner_spells::init(&dir);
+                    auto_actions::init(&dir);
+                    build_provider::register(std::sync::Arc::new(local_builds::LocalProvider));
+                    build_provider::register(std::sync::Arc::new(local_builds::PersonalProvider));
+                }
+                Err(e)  => eprintln!("[MatchStore] app_data_dir non disponibile: {}", e),
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_profiles,
             get_more_matches,
@@ -1776,6 +1710,30 @@ fn main() {
             get_match_timeline,
             get_client_region,
             search_summoner_suggestions,
+            match_sync::get_sync_settings,
+            match_sync::set_sync_settings,
+            seasons::get_season_calendar,
+            static_data::get_champion_map,
+            offline::get_offline_status,
+            offline::set_offline_mode,
+            lcu::get_lcu_status,
+            auto_import::get_auto_import_state,
+            rune_pages::backup_rune_pages,
+            rune_pages::restore_rune_pages,
+            build_provider::get_build_policy,
+            build_provider::set_build_policy,
+            draft::get_draft_analysis,
+            item_sets::list_item_sets,
+            matchups::get_matchup_suggestions,
+            scouting::get_teammate_scouting,
+            auto_actions::get_automation_settings,
+            auto_actions::set_automation_settings,
+            auto_actions::get_automation_log,
+            item_sets::save_item_set,
+            item_sets::delete_item_set,
+            item_sets::remove_all_item_sets,
+            summoner_spells::get_spell_prefs,
+            summoner_spells::set_spell_prefs,
             get_champ_select_session,
             auto_import_build,
             apply_rune_page,
@@ -0,0 +1,393 @@
+// match_store.rs — archivio locale (SQLite) di match, timeline e partecipanti
+//
+// I dati Match-V5 non cambiano mai: una volta scaricati restano su disco
+// (app data dir / matches.sqlite) e sopravvivono ai riavvii. Il JSON completo
+// viene salvato così com'è, mentre la tabella `participants` tiene una riga per
+// giocatore indicizzata per puuid, coda, campione e gameCreation.
+// `snapshots` tiene l'ultima risposta buona di profili e maestrie per la modalità offline.
+// rusqlite è sincrono e la connessione sta dietro un Mutex: dai task async si passa
+// da `blocking`, così lock e I/O su disco non occupano i worker di tokio.
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use crate::riot_api::{MatchDto, MatchTimelineDto};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS matches (
+        match_id       TEXT PRIMARY KEY,
+        game_creation  INTEGER NOT NULL,
+        queue_id       INTEGER NOT NULL,
+        game_version   TEXT NOT NULL,
+        data           TEXT NOT NULL,
+        fetched_at     INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_matches_creation ON matches(game_creation);
+
+    CREATE TABLE IF NOT EXISTS timelines (
+        match_id    TEXT PRIMARY KEY,
+        data        TEXT NOT NULL,
+        fetched_at  INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS participants (
+        match_id       TEXT NOT NULL,
+        puuid          TEXT NOT NULL,
+        champion_id    INTEGER NOT NULL,
+        champion_name  TEXT NOT NULL,
+        team_position  TEXT NOT NULL,
+        queue_id       INTEGER NOT NULL,
+        game_creation  INTEGER NOT NULL,
+        win            INTEGER NOT NULL,
+        PRIMARY KEY (match_id, puuid)
+    );
+    CREATE INDEX IF NOT EXISTS idx_participants_puuid_creation ON participants(puuid, game_creation DESC);
+    CREATE INDEX IF NOT EXISTS idx_participants_puuid_queue    ON participants(puuid, queue_id);
+    CREATE INDEX IF NOT EXISTS idx_participants_puuid_champion ON participants(puuid, champion_id);
+    CREATE INDEX IF NOT EXISTS idx_participants_champion       ON participants(champion_name, queue_id);
+
+    CREATE TABLE IF NOT EXISTS sync_state (
+        puuid          TEXT PRIMARY KEY,
+        newest_ms      INTEGER,
+        oldest_ms      INTEGER,
+        backfill_floor INTEGER,
+        updated_at     INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS sync_failures (
+        puuid     TEXT NOT NULL,
+        match_id  TEXT NOT NULL,
+        PRIMARY KEY (puuid, match_id)
+    );
+
+    CREATE TABLE IF NOT EXISTS settings (
+        key    TEXT PRIMARY KEY,
+        value  TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS snapshots (
+        kind        TEXT NOT NULL,
+        key         TEXT NOT NULL,
+        data        TEXT NOT NULL,
+        updated_at  INTEGER NOT NULL,
+        PRIMARY KEY (kind, key)
+    );
+";
+
+pub struct MatchStore {
+    conn: Mutex<Connection>,
+}
+
+static MATCH_STORE: OnceLock<MatchStore> = OnceLock::new();
+
+/// Apre (o crea) il database. Chiamato una volta in `setup()`.
+/// Se fallisce l'app continua a funzionare scaricando tutto da Riot.
+pub fn init(path: &Path) {
+    match MatchStore::open(path) {
+        Ok(store) => {
+            eprintln!("[MatchStore] aperto: {:?}", path);
+            let _ = MATCH_STORE.set(store);
+        }
+        Err(e) => eprintln!("[MatchStore] ❌ apertura fallita ({:?}): {}", path, e),
+    }
+}
+
+/// `None` se il database non è disponibile.
+pub fn match_store() -> Option<&'static MatchStore> {
+    MATCH_STORE.get()
+}
+
+/// Esegue `f` sullo store in un thread bloccante (`spawn_blocking`).
+/// `None` se il database non è disponibile.
+pub async fn blocking<R, F>(f: F) -> Option<R>
+where
+    F: FnOnce(&'static MatchStore) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let store = match_store()?;
+    tokio::task::spawn_blocking(move || f(store)).await
+        .inspect_err(|e| eprintln!("[MatchStore] task bloccante fallito: {}", e))
+        .ok()
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+impl MatchStore {
+    fn open(path: &Path) -> rusqlite::Result<Self> {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(MatchStore { conn: Mutex::new(conn) })
+    }
+
+    /// Dettaglio match salvato (JSON Match-V5 completo).
+    pub fn get_match(&self, match_id: &str) -> Option<Value> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM matches WHERE match_id = ?1", params![match_id], |r| r.get(0))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] get_match {}: {}", match_id, e); None });
+        data.and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// Salva match + una riga per partecipante (transazione unica).
+    pub fn put_match(&self, detail: &MatchDto) {
+        let data = match serde_json::to_string(detail) {
+            Ok(s) => s,
+            Err(e) => { eprintln!("[MatchStore] serialize {}: {}", detail.metadata.match_id, e); return; }
+        };
+        let info = &detail.info;
+        let mut conn = self.conn.lock().unwrap();
+        let res = (|| -> rusqlite::Result<()> {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT OR REPLACE INTO matches (match_id, game_creation, queue_id, game_version, data, fetched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![detail.metadata.match_id, info.game_creation as i64, info.queue_id as i64,
+                        info.game_version, data, now_ms()],
+            )?;
+            for p in &info.participants {
+                if p.puuid.is_empty() { continue; }
+                tx.execute(
+                    "INSERT OR REPLACE INTO participants
+                       (match_id, puuid, champion_id, champion_name, team_position, queue_id, game_creation, win)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![detail.metadata.match_id, p.puuid, p.champion_id as i64, p.champion_name,
+                            p.team_position, info.queue_id as i64, info.game_creation as i64, p.win],
+                )?;
+            }
+            tx.commit()
+        })();
+        if let Err(e) = res {
+            eprintln!("[MatchStore] put_match {}: {}", detail.metadata.match_id, e);
+        }
+    }
+
+    /// `gameCreation` di un match già salvato.
+    pub fn game_creation(&self, match_id: &str) -> Option<u64> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT game_creation FROM matches WHERE match_id = ?1", params![match_id], |r| r.get::<_, i64>(0))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] game_creation {}: {}", match_id, e); None })
+            .map(|gc| gc as u64)
+    }
+
+    pub fn get_timeline(&self, match_id: &str) -> Option<Value> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM timelines WHERE match_id = ?1", params![match_id], |r| r.get(0))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] get_timeline {}: {}", match_id, e); None });
+        data.and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    pub fn put_timeline(&self, match_id: &str, timeline: &MatchTimelineDto) {
+        let Ok(data) = serde_json::to_string(timeline) else { return };
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO timelines (match_id, data, fetched_at) VALUES (?1, ?2, ?3)",
+            params![match_id, data, now_ms()],
+        ) {
+            eprintln!("[MatchStore] put_timeline {}: {}", match_id, e);
+        }
+    }
+
+    /// Match salvati di un puuid (custom esclusi), dal più recente, con filtri opzionali.
+    pub fn matches_for_puuid(&self, puuid: &str, query: &MatchQuery) -> Vec<Value> {
+        let conn = self.conn.lock().unwrap();
+        let res = (|| -> rusqlite::Result<Vec<String>> {
+            let mut stmt = conn.prepare(
+                "SELECT m.data FROM participants p
+                 JOIN matches m ON m.match_id = p.match_id
+                 WHERE p.puuid = ?1 AND p.queue_id != 0
+                   AND (?2 IS NULL OR p.queue_id = ?2)
+                   AND (?3 IS NULL OR p.champion_id = ?3)
+                   AND (?4 IS NULL OR p.game_creation >= ?4)
+                   AND (?5 IS NULL OR p.game_creation < ?5)
+                   AND (?8 IS NULL OR m.game_version = ?8 OR m.game_version LIKE ?8 || '.%')
+                 ORDER BY p.game_creation DESC
+                 LIMIT ?6 OFFSET ?7",
+            )?;
+            let rows = stmt.query_map(
+                params![puuid, query.queue_id, query.champion_id,
+                        query.since_ms.map(|v| v as i64), query.until_ms.map(|v| v as i64),
+                        query.limit.map(|l| l as i64).unwrap_or(-1), query.offset as i64, query.patch],
+                |r| r.get::<_, String>(0),
+            )?;
+            rows.collect()
+        })();
+        match res {
+            Ok(rows) => rows.iter().filter_map(|s| serde_json::from_str(s).ok()).collect(),
+            Err(e) => { eprintln!("[MatchStore] matches_for_puuid: {}", e); vec![] }
+        }
+    }
+
+    /// Match salvati in cui compare il campione (giocato da chiunque), dal più recente.
+    /// `champion_name` è il `championName` Riot ("MonkeyKing"); `team_position` vuoto = qualsiasi lane.
+    pub fn matches_for_champion(&self, champion_name: &str, queue_ids: &[i64], team_position: &str, limit: u32) -> Vec<Value> {
+        let conn = self.conn.lock().unwrap();
+        // Gli id coda sono interi: si possono mettere direttamente nella IN (...)
+        let queues = queue_ids.iter().map(|q| q.to_string()).collect::<Vec<_>>().join(",");
+        let res = (|| -> rusqlite::Result<Vec<String>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT m.data FROM matches m
+                 WHERE m.match_id IN (
+                     SELECT p.match_id FROM participants p
+                     WHERE p.champion_name = ?1 COLLATE NOCASE AND p.queue_id IN ({})
+                       AND (?2 = '' OR p.team_position = ?2))
+                 ORDER BY m.game_creation DESC
+                 LIMIT ?3", queues),
+            )?;
+            let rows = stmt.query_map(params![champion_name, team_position, limit as i64], |r| r.get::<_, String>(0))?;
+            rows.collect()
+        })();
+        match res {
+            Ok(rows) => rows.iter().filter_map(|s| serde_json::from_str(s).ok()).collect(),
+            Err(e) => { eprintln!("[MatchStore] matches_for_champion: {}", e); vec![] }
+        }
+    }
+
+    // ── Sync state ───────────────────────────────────────────────────────────
+
+    pub fn sync_state(&self, puuid: &str) -> SyncState {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT newest_ms, oldest_ms, backfill_floor FROM sync_state WHERE puuid = ?1",
+            params![puuid],
+            |r| Ok(SyncState {
+                newest_ms:      r.get::<_, Option<i64>>(0)?.map(|v| v as u64),
+                oldest_ms:      r.get::<_, Option<i64>>(1)?.map(|v| v as u64),
+                backfill_floor: r.get::<_, Option<i64>>(2)?.map(|v| v as u64),
+            }),
+        )
+        .optional()
+        .unwrap_or_else(|e| { eprintln!("[MatchStore] sync_state {:.20}: {}", puuid, e); None })
+        .unwrap_or_default()
+    }
+
+    pub fn put_sync_state(&self, puuid: &str, state: &SyncState) {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO sync_state (puuid, newest_ms, oldest_ms, backfill_floor, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![puuid, state.newest_ms.map(|v| v as i64), state.oldest_ms.map(|v| v as i64),
+                    state.backfill_floor.map(|v| v as i64), now_ms()],
+        ) {
+            eprintln!("[MatchStore] put_sync_state {:.20}: {}", puuid, e);
+        }
+    }
+
+    /// Match dentro la finestra sincronizzata il cui download è fallito (da ritentare).
+    pub fn sync_failures(&self, puuid: &str) -> Vec<String> {
+        let conn = self.conn.lock().unwrap();
+        let res = (|| -> rusqlite::Result<Vec<String>> {
+            let mut stmt = conn.prepare("SELECT match_id FROM sync_failures WHERE puuid = ?1")?;
+            let rows = stmt.query_map(params![puuid], |r| r.get::<_, String>(0))?;
+            rows.collect()
+        })();
+        res.unwrap_or_else(|e| { eprintln!("[MatchStore] sync_failures {:.20}: {}", puuid, e); vec![] })
+    }
+
+    pub fn add_sync_failure(&self, puuid: &str, match_id: &str) {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "INSERT OR IGNORE INTO sync_failures (puuid, match_id) VALUES (?1, ?2)",
+            params![puuid, match_id],
+        ) {
+            eprintln!("[MatchStore] add_sync_failure {}: {}", match_id, e);
+        }
+    }
+
+    pub fn remove_sync_failure(&self, puuid: &str, match_id: &str) {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "DELETE FROM sync_failures WHERE puuid = ?1 AND match_id = ?2",
+            params![puuid, match_id],
+        ) {
+            eprintln!("[MatchStore] remove_sync_failure {}: {}", match_id, e);
+        }
+    }
+
+    /// Ultimo sync riuscito (ms) per il puuid, usato come `stale_since` offline.
+    pub fn synced_at(&self, puuid: &str) -> Option<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row("SELECT updated_at FROM sync_state WHERE puuid = ?1", params![puuid], |r| r.get(0))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] synced_at {:.20}: {}", puuid, e); None })
+    }
+
+    // ── Settings (chiave → JSON) ─────────────────────────────────────────────
+
+    pub fn get_setting(&self, key: &str) -> Option<Value> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |r| r.get(0))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] get_setting {}: {}", key, e); None });
+        data.and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    pub fn set_setting(&self, key: &str, value: &Value) {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value.to_string()],
+        ) {
+            eprintln!("[MatchStore] set_setting {}: {}", key, e);
+        }
+    }
+
+    // ── Snapshot (ultima risposta valida, per l'offline) ────────────────────
+
+    /// Snapshot salvato + istante di salvataggio (ms).
+    pub fn get_snapshot(&self, kind: &str, key: &str) -> Option<(Value, i64)> {
+        let conn = self.conn.lock().unwrap();
+        let row: Option<(String, i64)> = conn
+            .query_row("SELECT data, updated_at FROM snapshots WHERE kind = ?1 AND key = ?2",
+                params![kind, key], |r| Ok((r.get(0)?, r.get(1)?)))
+            .optional()
+            .unwrap_or_else(|e| { eprintln!("[MatchStore] get_snapshot {}/{}: {}", kind, key, e); None });
+        row.and_then(|(s, ts)| serde_json::from_str(&s).ok().map(|v| (v, ts)))
+    }
+
+    pub fn put_snapshot(&self, kind: &str, key: &str, data: &Value) {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO snapshots (kind, key, data, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, key, data.to_string(), now_ms()],
+        ) {
+            eprintln!("[MatchStore] put_snapshot {}/{}: {}", kind, key, e);
+        }
+    }
+}
+
+/// Filtri per `matches_for_puuid` (tutti opzionali, `gameCreation` in ms).
+#[derive(Debug, Clone, Default)]
+pub struct MatchQuery {
+    pub queue_id: Option<i64>,
+    pub champion_id: Option<i64>,
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+    /// Prefisso di `gameVersion` (es. "16.4").
+    pub patch: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: u32,
+}
+
+/// Finestra di storico già sincronizzata per un puuid (`gameCreation` in ms).
+/// Tutti i match tra `oldest_ms` e `newest_ms` sono nello store, tranne quelli in
+/// `sync_failures` (download fallito, ritentati a ogni sync/backfill); `backfill_floor`
+/// è il limite inferiore fino a cui il backfill è arrivato in fondo.
+#[derive(Debug, Clone, Default)]
+pub struct SyncState {
+    pub newest_ms: Option<u64>,
+    pub oldest_ms: Option<u64>,
+    pub backfill_floor: Option<u64>,
+}
@@ -0,0 +1,323 @@
+// match_sync.rs — sincronizzazione incrementale dello storico match per puuid
+//
+// Per ogni puuid lo store ricorda la finestra già scaricata (newest/oldest gameCreation):
+//   • sync_new   → chiede a Riot solo i match con startTime >= newest (quelli nuovi)
+//   • backfill   → in background scende con endTime <= oldest fino alla profondità
+//                  configurata (default: intera stagione), o più giù se il frontend
+//                  chiede un intervallo più vecchio ("all", stagioni passate)
+// I match il cui download fallisce restano in `sync_failures` e si ritentano
+// all'inizio di ogni sync/backfill, così la finestra non ha buchi permanenti.
+// Le liste mostrate al frontend vengono poi lette dallo store, paginando per
+// gameCreation (cursore stabile anche se nel frattempo arrivano nuove partite)
+// e filtrando per stagione/patch solo in lettura (vedi seasons.rs).
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::match_store::{blocking, match_store, MatchQuery, SyncState};
+use crate::riot_api::{match_v5, RiotApiError};
+use crate::seasons::{calendar, MatchRange};
+
+/// Match-V5 accetta al massimo 100 ID per pagina.
+const IDS_PAGE: u32 = 100;
+/// Al primo sync scarichiamo solo l'ultima pagina visibile; il resto lo fa il backfill.
+const FIRST_SYNC_COUNT: u32 = 20;
+/// Pagine massime di ID nuovi per singolo sync (evita loop se Riot risponde male).
+const MAX_NEW_PAGES: u32 = 5;
+
+const SETTINGS_KEY: &str = "match_sync";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSettings {
+    /// Giorni di storico da mantenere sincronizzati. `None` = intera stagione corrente;
+    /// valori più grandi scendono nelle stagioni precedenti (confronti anno su anno).
+    pub backfill_days: Option<u32>,
+}
+
+pub async fn settings() -> SyncSettings {
+    blocking(|store| store.get_setting(SETTINGS_KEY)).await.flatten()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn season_start_ms() -> u64 {
+    calendar().current_season().start_ms
+}
+
+/// Limite inferiore (ms) dello storico da sincronizzare.
+pub async fn depth_floor_ms() -> u64 {
+    match settings().await.backfill_days {
+        Some(days) => {
+            let now = chrono::Utc::now().timestamp_millis().max(0) as u64;
+            now.saturating_sub(days as u64 * 86_400_000)
+        }
+        None => season_start_ms(),
+    }
+}
+
+/// Scarica (in parallelo) i match non ancora nello store.
+/// Restituisce il `gameCreation` di ogni ID disponibile, vecchi e nuovi.
+/// I download falliti finiscono in `sync_failures`: i cursori della finestra li
+/// superano comunque, quindi vanno ritentati (vedi `retry_failures`) per non lasciare buchi.
+async fn download(puuid: &str, ids: &[String], region: &str, client: &Client) -> Vec<u64> {
+    let lookup = ids.to_vec();
+    let known: Vec<Option<u64>> = blocking(move |store| lookup.iter().map(|id| store.game_creation(id)).collect())
+        .await.unwrap_or_default();
+    let mut creations = Vec::with_capacity(ids.len());
+    let mut tasks = Vec::new();
+    for (i, id) in ids.iter().enumerate() {
+        if let Some(gc) = known.get(i).copied().flatten() {
+            creations.push(gc);
+            continue;
+        }
+        let (id, region, client) = (id.clone(), region.to_string(), client.clone());
+        tasks.push(tokio::spawn(async move {
+            let result = match_v5::by_id(&region, &id).send(&client).await;
+            (id, result)
+        }));
+    }
+
+    let (mut saved, mut resolved, mut failed) = (Vec::new(), Vec::new(), Vec::new());
+    for task in tasks {
+        let Ok((id, result)) = task.await else { continue };
+        match result {
+            Ok(detail) => {
+                creations.push(detail.info.game_creation);
+                resolved.push(id);
+                saved.push(detail);
+            }
+            // Match inesistente per Riot: inutile ritentarlo
+            Err(RiotApiError::NotFound) => {
+                eprintln!("[MatchSync] {}: non trovato", id);
+                resolved.push(id);
+            }
+            Err(e) => {
+                eprintln!("[MatchSync] {}: {} (da ritentare)", id, e);
+                failed.push(id);
+            }
+        }
+    }
+    let puuid = puuid.to_string();
+    blocking(move |store| {
+        for detail in &saved { store.put_match(detail); }
+        for id in &resolved { store.remove_sync_failure(&puuid, id); }
+        for id in &failed { store.add_sync_failure(&puuid, id); }
+    }).await;
+    creations
+}
+
+/// Ritenta i download falliti nei sync precedenti.
+async fn retry_failures(puuid: &str, region: &str, client: &Client) {
+    let owner = puuid.to_string();
+    let failed = blocking(move |store| store.sync_failures(&owner)).await.unwrap_or_default();
+    if failed.is_empty() { return; }
+    let recovered = download(puuid, &failed, region, client).await.len();
+    eprintln!("[MatchSync] {:.20} → {}/{} match recuperati", puuid, recovered, failed.len());
+}
+
+async fn sync_state(puuid: &str) -> Option<SyncState> {
+    let puuid = puuid.to_string();
+    blocking(move |store| store.sync_state(&puuid)).await
+}
+
+async fn put_sync_state(puuid: &str, state: &SyncState) {
+    let (puuid, state) = (puuid.to_string(), state.clone());
+    blocking(move |store| store.put_sync_state(&puuid, &state)).await;
+}
+
+async fn fetch_ids(puuid: &str, start: u32, count: u32, start_ms: Option<u64>, end_ms: Option<u64>, region: &str, client: &Client) -> Result<Vec<String>, RiotApiError> {
+    // Riot vuole startTime/endTime in secondi
+    let mut req = match_v5::ids_by_puuid(region, puuid)
+        .query("start", start)
+        .query("count", count);
+    if let Some(ms) = start_ms { req = req.query("startTime", ms / 1000); }
+    if let Some(ms) = end_ms   { req = req.query("endTime", ms / 1000); }
+    req.send(client).await
+}
+
+/// Scarica solo i match più recenti di quelli già sincronizzati.
+/// Restituisce quanti match nuovi sono stati trovati.
+pub async fn sync_new(puuid: &str, region: &str, client: &Client) -> Result<usize, RiotApiError> {
+    let Some(mut state) = sync_state(puuid).await else { return Ok(0) };
+    retry_failures(puuid, region, client).await;
+
+    let mut creations = Vec::new();
+    match state.newest_ms {
+        None => {
+            let season_start = season_start_ms();
+            let ids = fetch_ids(puuid, 0, FIRST_SYNC_COUNT, Some(season_start), None, region, client).await?;
+            creations = download(puuid, &ids, region, client).await;
+            // Prima pagina incompleta = non c'è altro storico in stagione
+            // (solo se il backfill non deve scendere nelle stagioni precedenti)
+            if (ids.len() as u32) < FIRST_SYNC_COUNT && depth_floor_ms().await >= season_start {
+                state.backfill_floor = Some(season_start);
+            }
+            if creations.is_empty() {
+                // Nessuna partita in stagione: finestra vuota che parte dall'inizio stagione
+                state.newest_ms = Some(season_start);
+                state.oldest_ms = Some(season_start);
+            }
+        }
+        Some(newest) => {
+            for page in 0..MAX_NEW_PAGES {
+                let ids = fetch_ids(puuid, page * IDS_PAGE, IDS_PAGE, Some(newest), None, region, client).await?;
+                creations.extend(download(puuid, &ids, region, client).await);
+                if (ids.len() as u32) < IDS_PAGE { break; }
+            }
+        }
+    }
+
+    let found = creations.iter().filter(|&&gc| state.newest_ms.is_none_or(|n| gc > n)).count();
+    if let Some(&max) = creations.iter().max() {
+        state.newest_ms = Some(state.newest_ms.map_or(max, |n| n.max(max)));
+    }
+    if let Some(&min) = creations.iter().min() {
+        state.oldest_ms = Some(state.oldest_ms.map_or(min, |o| o.min(min)));
+    }
+    put_sync_state(puuid, &state).await;
+    eprintln!("[MatchSync] {:.20} → {} match nuovi", puuid, found);
+    Ok(found)
+}
+
+/// puuid con un backfill in corso → limite richiesto (ms). Una richiesta più profonda
+/// mentre il task gira abbassa il limite invece di avviare un secondo task.
+static BACKFILLING: Mutex<Option<HashMap<String, u64>>> = Mutex::new(None);
+/// puuid sincronizzati in questa sessione, per riavviare il backfill quando
+/// `set_sync_settings` aumenta la profondità.
+static SYNCED: Mutex<Option<HashMap<String, (String, Client)>>> = Mutex::new(None);
+
+/// Avvia in background il recupero dello storico più vecchio fino a `depth_floor_ms()`,
+/// o fino a `since_ms` se più vecchio (`Some(0)` = tutto lo storico disponibile).
+pub fn spawn_backfill(puuid: &str, region: &str, client: Client, since_ms: Option<u64>) {
+    if match_store().is_none() { return; }
+    SYNCED.lock().unwrap().get_or_insert_with(HashMap::new)
+        .insert(puuid.to_string(), (region.to_string(), client.clone()));
+    let (puuid, region) = (puuid.to_string(), region.to_string());
+    tokio::spawn(async move {
+        let depth = depth_floor_ms().await;
+        let floor = since_ms.map_or(depth, |s| s.min(depth));
+        {
+            let mut running = BACKFILLING.lock().unwrap();
+            let running = running.get_or_insert_with(HashMap::new);
+            if let Some(target) = running.get_mut(&puuid) {
+                *target = (*target).min(floor);
+                return;
+            }
+            running.insert(puuid.clone(), floor);
+        }
+        // Se finisce bene il task si toglie da solo (vedi `finish_backfill`)
+        if let Err(e) = backfill(&puuid, &region, &client).await {
+            eprintln!("[MatchSync] backfill {:.20} interrotto: {}", puuid, e);
+            if let Some(running) = BACKFILLING.lock().unwrap().as_mut() { running.remove(&puuid); }
+        }
+    });
+}
+
+/// Limite corrente del backfill in corso per `puuid`.
+fn backfill_target(puuid: &str) -> Option<u64> {
+    BACKFILLING.lock().unwrap().as_ref()?.get(puuid).copied()
+}
+
+/// Chiude il backfill arrivato a `floor`, a meno che nel frattempo non sia stato
+/// chiesto un limite più profondo (`false`: si continua a scendere).
+fn finish_backfill(puuid: &str, floor: u64) -> bool {
+    let mut running = BACKFILLING.lock().unwrap();
+    let Some(running) = running.as_mut() else { return true };
+    if running.get(puuid).is_some_and(|&target| target < floor) { return false; }
+    running.remove(puuid);
+    true
+}
+
+/// Lo store ha tutto lo storico di `puuid` da `since_ms` (`None` = dall'inizio) in poi.
+pub async fn covers(puuid: &str, since_ms: Option<u64>) -> bool {
+    let since = since_ms.unwrap_or(0);
+    sync_state(puuid).await.is_some_and(|s| {
+        s.backfill_floor.is_some_and(|f| f <= since) || s.oldest_ms.is_some_and(|o| o <= since)
+    })
+}
+
+async fn backfill(puuid: &str, region: &str, client: &Client) -> Result<(), RiotApiError> {
+    let Some(mut state) = sync_state(puuid).await else {
+        finish_backfill(puuid, 0);
+        return Ok(());
+    };
+    retry_failures(puuid, region, client).await;
+
+    while let Some(floor) = backfill_target(puuid) {
+        if state.oldest_ms.is_some_and(|o| o <= floor) && state.backfill_floor.is_none_or(|f| f > floor) {
+            state.backfill_floor = Some(floor);
+        }
+        let reached = state.backfill_floor.is_some_and(|f| f <= floor);
+        // Senza un primo sync non sappiamo da dove scendere
+        let Some(oldest) = state.oldest_ms.filter(|_| !reached) else {
+            if finish_backfill(puuid, floor) { break; }
+            continue;
+        };
+
+        let ids = fetch_ids(puuid, 0, IDS_PAGE, Some(floor), Some(oldest), region, client).await?;
+        let creations = download(puuid, &ids, region, client).await;
+        let page_min = creations.iter().copied().min();
+        match page_min {
+            Some(min) if min < oldest && (ids.len() as u32) == IDS_PAGE => {
+                state.oldest_ms = Some(min);
+            }
+            _ => {
+                // Pagina vuota o incompleta: arrivati in fondo alla finestra
+                if let Some(min) = page_min { state.oldest_ms = Some(min.min(oldest)); }
+                state.backfill_floor = Some(floor);
+            }
+        }
+        put_sync_state(puuid, &state).await;
+        eprintln!("[MatchSync] backfill {:.20} → {} match, oldest={:?}", puuid, ids.len(), state.oldest_ms);
+    }
+    put_sync_state(puuid, &state).await;
+    Ok(())
+}
+
+/// Pagina di match dallo store dentro `range`, più vecchi di `before_ms` (esclusivo)
+/// se indicato. `None` se lo store non è disponibile.
+pub async fn load_page(puuid: &str, range: &MatchRange, before_ms: Option<u64>, limit: u32) -> Option<Vec<Value>> {
+    let until_ms = match (range.until_ms, before_ms) {
+        (Some(u), Some(b)) => Some(u.min(b)),
+        (u, b) => u.or(b),
+    };
+    let query = MatchQuery {
+        since_ms: range.since_ms,
+        until_ms,
+        patch: range.patch.clone(),
+        limit: Some(limit),
+        ..Default::default()
+    };
+    let puuid = puuid.to_string();
+    blocking(move |store| store.matches_for_puuid(&puuid, &query)).await
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_sync_settings() -> Result<SyncSettings, String> {
+    Ok(settings().await)
+}
+
+/// Aggiorna la profondità del backfill. Se aumenta, i puuid già sincronizzati in
+/// questa sessione riprendono subito il backfill da dove era arrivato fino al nuovo limite.
+#[tauri::command]
+pub async fn set_sync_settings(settings: SyncSettings) -> Result<(), String> {
+    let previous = depth_floor_ms().await;
+    let value = json!(settings);
+    blocking(move |store| store.set_setting(SETTINGS_KEY, &value)).await
+        .ok_or("Archivio match non disponibile")?;
+    if depth_floor_ms().await < previous {
+        let synced: Vec<(String, (String, Client))> = SYNCED.lock().unwrap().iter().flatten()
+            .map(|(p, r)| (p.clone(), r.clone())).collect();
+        eprintln!("[MatchSync] profondità aumentata: backfill di {} puuid", synced.len());
+        for (puuid, (region, client)) in synced {
+            spawn_backfill(&puuid, &region, client, None);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+// matchups.rs — counter pick suggeriti in champ select
+//
+// Per ogni avversario già visibile in `theirTeam` si chiedono a OP.GG i counter
+// nella nostra lane (opgg::champion_counters): i campioni contro cui l'avversario
+// perde sono i pick consigliati, con il nostro win rate di lane = 1 - il suo.
+// Se un avversario ha la nostra stessa lane assegnata (assignedPosition, spesso vuota
+// per la squadra nemica) è lui l'avversario diretto: il win rate di lane è quello
+// contro di lui e gli altri restano solo in `versus`. Altrimenti, con più avversari,
+// il win rate è la media pesata sulle partite; gli avversari che OP.GG non ha in
+// quella lane (un jungler se siamo mid) restano fuori da soli.
+// La lista si incrocia poi con le maestrie (get_summoner_masteries) e con il win
+// rate personale dallo store locale, così in cima finiscono i campioni che l'utente
+// gioca davvero. Esclusi i campioni già scelti o bannati.
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::lcu::LcuConnection;
+use crate::match_store::{blocking, MatchQuery};
+use crate::opgg;
+use crate::static_data::static_data;
+
+/// Suggerimenti restituiti.
+const MAX_SUGGESTIONS: usize = 10;
+/// Partite recenti dell'utente su cui calcolare il win rate personale.
+const PERSONAL_GAMES: u32 = 300;
+/// Sotto questa soglia il win rate personale non pesa.
+const MIN_PERSONAL_GAMES: u32 = 3;
+/// Punti maestria oltre cui il campione conta come "main".
+const MAIN_MASTERY_POINTS: f64 = 100_000.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersusEnemy {
+    pub champion_name: String,
+    /// Nostro win rate di lane (0-1).
+    pub win_rate: f64,
+    pub games: u32,
+    /// Avversario diretto (stessa lane assegnata).
+    pub lane_opponent: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchupSuggestion {
+    pub champion_id: u32,
+    /// Id DDragon, per le immagini.
+    pub champion: String,
+    pub champion_name: String,
+    /// Win rate di lane contro l'avversario diretto, se noto, altrimenti medio
+    /// contro gli avversari visibili (0-1).
+    pub lane_win_rate: f64,
+    pub games: u32,
+    pub versus: Vec<VersusEnemy>,
+    pub mastery_level: Option<u32>,
+    pub mastery_points: Option<u64>,
+    pub personal_games: u32,
+    pub personal_win_rate: Option<f64>,
+    /// Ordinamento: win rate di lane corretto per maestria ed esperienza personale.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MatchupSuggestions {
+    /// Lane LCU usata per i counter.
+    pub position: String,
+    /// Avversari considerati (con dati OP.GG nella nostra lane).
+    pub enemies: Vec<String>,
+    /// Avversario con la nostra stessa lane assegnata, se il client la espone.
+    pub lane_opponent: Option<String>,
+    pub suggestions: Vec<MatchupSuggestion>,
+}
+
+/// Partite e vittorie per campione dalle ultime partite dell'utente.
+async fn personal_stats(puuid: &str) -> HashMap<u32, (u32, u32)> {
+    let mut stats: HashMap<u32, (u32, u32)> = HashMap::new();
+    let owner = puuid.to_string();
+    let query = MatchQuery { limit: Some(PERSONAL_GAMES), ..Default::default() };
+    let Some(matches) = blocking(move |store| store.matches_for_puuid(&owner, &query)).await else { return stats };
+    for m in &matches {
+        let Some(me) = m.pointer("/info/participants").and_then(Value::as_array)
+            .and_then(|ps| ps.iter().find(|p| p["puuid"] == puuid)) else { continue };
+        let Some(id) = me["championId"].as_u64() else { continue };
+        let entry = stats.entry(id as u32).or_default();
+        entry.0 += 1;
+        if me["win"].as_bool() == Some(true) { entry.1 += 1; }
+    }
+    stats
+}
+
+/// championId → (livello, punti), dalle maestrie Riot.
+async fn masteries(puuid: &str, region: &str) -> HashMap<u32, (u32, u64)> {
+    match crate::get_summoner_masteries(puuid.to_string(), region.to_string()).await {
+        Ok(list) => list.as_array().into_iter().flatten()
+            .filter_map(|m| Some((
+                m["championId"].as_u64()? as u32,
+                (m["championLevel"].as_u64().unwrap_or(0) as u32, m["championPoints"].as_u64().unwrap_or(0)),
+            )))
+            .collect(),
+        Err(e) => {
+            eprintln!("[Matchups] maestrie non disponibili: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+fn score(lane_win_rate: f64, mastery_points: Option<u64>, personal: Option<(u32, f64)>) -> f64 {
+    let comfort = mastery_points.map(|p| (p as f64 / MAIN_MASTERY_POINTS).min(1.0)).unwrap_or(0.0);
+    let experience = personal.filter(|(games, _)| *games >= MIN_PERSONAL_GAMES)
+        .map(|(_, wr)| wr - 0.5).unwrap_or(0.0);
+    // Punti percentuali: fino a +5 per la maestria, ±10 per il win rate personale
+    lane_win_rate * 100.0 + comfort * 5.0 + experience * 20.0
+}
+
+#[tauri::command]
+pub async fn get_matchup_suggestions(lcu: State<'_, LcuConnection>, region: String) -> Result<MatchupSuggestions, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
+    let session: Value = lcu.get("/lol-champ-select/v1/session").await
+        .filter(|s| s.get("errorCode").is_none())
+        .ok_or("Nessuna champion select attiva")?;
+    let data = static_data().await?;
+
+    let my_cell = session["localPlayerCellId"].as_i64().unwrap_or(-1);
+    let position = session["myTeam"].as_array().into_iter().flatten()
+        .find(|p| p["cellId"].as_i64() == Some(my_cell))
+        .and_then(|p| p["assignedPosition"].as_str())
+        .filter(|p| !p.is_empty())
+        .unwrap_or("MIDDLE")
+        .to_uppercase();
+    // (championId, stessa lane assegnata)
+    let enemies: Vec<(u32, bool)> = session["theirTeam"].as_array().into_iter().flatten()
+        .filter_map(|p| {
+            let id = p["championId"].as_u64().filter(|&id| id != 0)? as u32;
+            Some((id, p["assignedPosition"].as_str().is_some_and(|a| a.eq_ignore_ascii_case(&position))))
+        })
+        .collect();
+    if enemies.is_empty() {
+        return Ok(MatchupSuggestions { position, ..Default::default() });
+    }
+
+    // Già scelti (anche in hover) o bannati: non suggeribili
+    let mut taken: Vec<u32> = enemies.iter().map(|&(id, _)| id).collect();
+    for p in session["myTeam"].as_array().into_iter().flatten() {
+        for key in ["championId", "championPickIntent"] {
+            if let Some(id) = p[key].as_u64().filter(|&id| id != 0) { taken.push(id as u32); }
+        }
+    }
+    for phase in session["actions"].as_array().into_iter().flatten().filter_map(Value::as_array) {
+        for a in phase.iter().filter(|a| a["type"] == "ban" && a["completed"] == true) {
+            if let Some(id) = a["championId"].as_u64() { taken.push(id as u32); }
+        }
+    }
+
+    // ── Counter OP.GG per ogni avversario ───────────────────────────────────
+    let lane = opgg::position(&position);
+    let ddragon = &*data;
+    let fetches = enemies.iter()
+        .filter_map(|&(id, same_lane)| Some((ddragon.champions.get(id)?, same_lane)))
+        .map(|(enemy, same_lane)| async move {
+            (enemy.name.clone(), same_lane, opgg::champion_counters(&enemy.id, lane, Some(ddragon)).await)
+        });
+    let results = futures_util::future::join_all(fetches).await;
+
+    // championId → (partite, vittorie nostre pesate, contro chi)
+    let mut candidates: HashMap<u32, (u32, f64, Vec<VersusEnemy>)> = HashMap::new();
+    let mut considered = Vec::new();
+    let mut lane_opponent = None;
+    for (enemy, same_lane, result) in results {
+        let counters = match result {
+            Ok(c) if !c.is_empty() => c,
+            Ok(_) => continue,
+            Err(e) => { eprintln!("[Matchups] {} in {}: {}", enemy, lane, e); continue; }
+        };
+        considered.push(enemy.clone());
+        if same_lane { lane_opponent = Some(enemy.clone()); }
+        for c in counters.iter().filter(|c| !taken.contains(&c.champion_id)) {
+            let ours = 1.0 - c.win_rate;
+            let entry = candidates.entry(c.champion_id).or_default();
+            entry.0 += c.games;
+            entry.1 += ours * c.games.max(1) as f64;
+            entry.2.push(VersusEnemy { champion_name: enemy.clone(), win_rate: ours, games: c.games, lane_opponent: same_lane });
+        }
+    }
+
+    // ── Maestria e storico personale ────────────────────────────────────────
+    let summoner = lcu.get("/lol-summoner/v1/current-summoner").await.unwrap_or_default();
+    let puuid = summoner["puuid"].as_str().unwrap_or("").to_string();
+    let (mastery, personal) = if puuid.is_empty() {
+        (HashMap::new(), HashMap::new())
+    } else {
+        tokio::join!(masteries(&puuid, &region), personal_stats(&puuid))
+    };
+
+    let mut suggestions: Vec<MatchupSuggestion> = candidates.into_iter().filter_map(|(id, (games, weighted, mut versus))| {
+        let champ = data.champions.get(id)?;
+        // Con l'avversario diretto noto contano solo i counter contro di lui
+        let (lane_win_rate, games) = if lane_opponent.is_some() {
+            let direct = versus.iter().find(|v| v.lane_opponent)?;
+            (direct.win_rate, direct.games)
+        } else {
+            let weight: u32 = versus.iter().map(|v| v.games.max(1)).sum();
+            (weighted / weight as f64, games)
+        };
+        versus.sort_by_key(|v| !v.lane_opponent);
+        let (mastery_level, mastery_points) = mastery.get(&id).map(|&(l, p)| (Some(l), Some(p))).unwrap_or((None, None));
+        let (personal_games, wins) = personal.get(&id).copied().unwrap_or((0, 0));
+        let personal_win_rate = (personal_games > 0).then(|| wins as f64 / personal_games as f64);
+        Some(MatchupSuggestion {
+            champion_id: id,
+            champion: champ.id.clone(),
+            champion_name: champ.name.clone(),
+            lane_win_rate,
+            games,
+            versus,
+            mastery_level,
+            mastery_points,
+            personal_games,
+            personal_win_rate,
+            score: score(lane_win_rate, mastery_points, personal_win_rate.map(|wr| (personal_games, wr))),
+        })
+    }).collect();
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    suggestions.truncate(MAX_SUGGESTIONS);
+    eprintln!("[Matchups] {} suggerimenti per {} contro {:?} (diretto: {:?})", suggestions.len(), position, considered, lane_opponent);
+
+    Ok(MatchupSuggestions { position, enemies: considered, lane_opponent, suggestions })
+}
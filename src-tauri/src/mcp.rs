@@ -0,0 +1,239 @@
+// mcp.rs — client MCP (Model Context Protocol) su HTTP "streamable"
+//
+// JSON-RPC 2.0 verso un server MCP: `initialize` + `notifications/initialized`,
+// poi `tools/list` e `tools/call` sulla stessa sessione (header `mcp-session-id`).
+// Il server può rispondere con JSON semplice o con uno stream SSE (`data: {...}`).
+// Dalla protocol version 2025-06-18 i tool possono restituire `structuredContent`
+// (JSON già tipizzato): se c'è lo si preferisce al testo.
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::opgg::ParseError;
+
+const PROTOCOL_VERSION: &str = "2025-06-18";
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone)]
+pub enum McpError {
+    /// Errore di rete o timeout.
+    Transport(String),
+    /// Status HTTP inatteso.
+    Status(u16),
+    /// Sessione scaduta o sconosciuta al server (HTTP 404 con `mcp-session-id`).
+    SessionExpired,
+    /// Errore JSON-RPC (`error: { code, message }`).
+    Rpc { code: i64, message: String },
+    /// Il tool richiesto non è tra quelli esposti dal server.
+    UnknownTool(String),
+    /// Il tool ha risposto con `isError: true`.
+    Tool(String),
+    /// Risposta senza `result` utilizzabile.
+    EmptyResponse,
+    /// Contenuto del tool non interpretabile.
+    Parse(ParseError),
+}
+
+impl fmt::Display for McpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpError::Transport(e) => write!(f, "Errore di rete MCP: {}", e),
+            McpError::Status(s) => write!(f, "Server MCP: HTTP {}", s),
+            McpError::SessionExpired => write!(f, "Sessione MCP scaduta"),
+            McpError::Rpc { code, message } => write!(f, "Errore MCP {}: {}", code, message),
+            McpError::UnknownTool(name) => write!(f, "Tool MCP '{}' non disponibile", name),
+            McpError::Tool(e) => write!(f, "MCP tool error: {}", e),
+            McpError::EmptyResponse => write!(f, "Risposta MCP vuota"),
+            McpError::Parse(e) => write!(f, "Risposta MCP non valida: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for McpError {}
+
+impl From<ParseError> for McpError {
+    fn from(e: ParseError) -> Self {
+        McpError::Parse(e)
+    }
+}
+
+impl From<McpError> for String {
+    fn from(e: McpError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Contenuto restituito da `tools/call`.
+#[derive(Debug, Clone)]
+pub enum ToolOutput {
+    /// `structuredContent` (JSON conforme all'`outputSchema` del tool).
+    Structured(Value),
+    /// Blocchi `text` concatenati.
+    Text(String),
+}
+
+/// Sessione MCP: `connect` esegue l'handshake, le chiamate successive la riusano.
+/// Per una sessione condivisa tra chiamate usare `McpSession`.
+pub struct McpClient {
+    http: Client,
+    url: String,
+    session_id: Option<String>,
+    next_id: AtomicU64,
+}
+
+impl McpClient {
+    pub async fn connect(url: &str) -> Result<Self, McpError> {
+        let http = Client::builder()
+            .danger_accept_invalid_certs(true)
+            .timeout(TIMEOUT)
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .build().map_err(|e| McpError::Transport(e.to_string()))?;
+        let mut client = McpClient { http, url: url.to_string(), session_id: None, next_id: AtomicU64::new(1) };
+
+        let (result, session_id) = client.send(&json!({
+            "jsonrpc": "2.0", "id": client.id(), "method": "initialize",
+            "params": {
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "rlp", "version": env!("CARGO_PKG_VERSION") },
+            },
+        })).await?;
+        client.session_id = session_id;
+        eprintln!("[MCP] sessione aperta: server={} protocol={} session={:?}",
+            result.pointer("/serverInfo/name").and_then(|v| v.as_str()).unwrap_or("?"),
+            result["protocolVersion"].as_str().unwrap_or("?"),
+            client.session_id);
+
+        // Notifica senza id: nessuna risposta attesa
+        let _ = client.post(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+        Ok(client)
+    }
+
+    fn id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn post(&self, body: &Value) -> Result<reqwest::Response, McpError> {
+        let mut req = self.http.post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .header("mcp-protocol-version", PROTOCOL_VERSION);
+        if let Some(sid) = &self.session_id { req = req.header("mcp-session-id", sid.as_str()); }
+        req.json(body).send().await.map_err(|e| McpError::Transport(e.to_string()))
+    }
+
+    /// Invia una richiesta e restituisce `result` (più l'eventuale id di sessione).
+    async fn send(&self, body: &Value) -> Result<(Value, Option<String>), McpError> {
+        let res = self.post(body).await?;
+        let status = res.status().as_u16();
+        if status == 404 && self.session_id.is_some() { return Err(McpError::SessionExpired); }
+        if !(200..300).contains(&status) { return Err(McpError::Status(status)); }
+        let session_id = res.headers().get("mcp-session-id")
+            .and_then(|v| v.to_str().ok()).map(String::from);
+        let text = res.text().await.map_err(|e| McpError::Transport(e.to_string()))?;
+        Ok((rpc_result(&text, body["id"].as_u64())?, session_id))
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, McpError> {
+        let body = json!({ "jsonrpc": "2.0", "id": self.id(), "method": method, "params": params });
+        Ok(self.send(&body).await?.0)
+    }
+
+    /// `tools/list`: descrittori dei tool (name, description, inputSchema, outputSchema).
+    pub async fn list_tools(&self) -> Result<Vec<Value>, McpError> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(result["tools"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// `tools/call`: preferisce `structuredContent`, altrimenti il testo.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<ToolOutput, McpError> {
+        let result = match self.request("tools/call", json!({ "name": name, "arguments": arguments })).await {
+            Ok(r) => r,
+            // Nome del tool cambiato lato server: errore esplicito invece del generico -32602
+            Err(McpError::Rpc { code, message }) => {
+                let exists = self.list_tools().await.map(|tools| tools.iter().any(|t| t["name"] == name));
+                return Err(if matches!(exists, Ok(false)) { McpError::UnknownTool(name.to_string()) } else { McpError::Rpc { code, message } });
+            }
+            Err(e) => return Err(e),
+        };
+        let text = content_text(&result);
+        if result["isError"].as_bool() == Some(true) {
+            return Err(McpError::Tool(text.unwrap_or_else(|| "errore sconosciuto".to_string())));
+        }
+        if let Some(structured) = result.get("structuredContent").filter(|v| !v.is_null()) {
+            return Ok(ToolOutput::Structured(structured.clone()));
+        }
+        text.map(ToolOutput::Text).ok_or(McpError::EmptyResponse)
+    }
+}
+
+/// Sessione condivisa e di lunga durata: si apre alla prima chiamata e si
+/// riapre da sola quando il server la dichiara scaduta.
+pub struct McpSession {
+    url: &'static str,
+    client: tokio::sync::Mutex<Option<Arc<McpClient>>>,
+}
+
+impl McpSession {
+    pub const fn new(url: &'static str) -> Self {
+        McpSession { url, client: tokio::sync::Mutex::const_new(None) }
+    }
+
+    /// Client corrente; `expired` è quello che ha appena trovato la sessione scaduta
+    /// (se un altro task l'ha già sostituito si riusa il nuovo, senza rifare l'handshake).
+    async fn client(&self, expired: Option<&Arc<McpClient>>) -> Result<Arc<McpClient>, McpError> {
+        let mut guard = self.client.lock().await;
+        if let Some(c) = guard.as_ref() {
+            if !expired.is_some_and(|e| Arc::ptr_eq(e, c)) { return Ok(c.clone()); }
+        }
+        let c = Arc::new(McpClient::connect(self.url).await?);
+        *guard = Some(c.clone());
+        Ok(c)
+    }
+
+    /// `tools/call` sulla sessione condivisa, con un nuovo handshake se è scaduta.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<ToolOutput, McpError> {
+        let client = self.client(None).await?;
+        match client.call_tool(name, arguments.clone()).await {
+            Err(McpError::SessionExpired) => {
+                eprintln!("[MCP] sessione scaduta, nuovo handshake");
+                self.client(Some(&client)).await?.call_tool(name, arguments).await
+            }
+            other => other,
+        }
+    }
+}
+
+/// Estrae `result` dalla risposta (JSON o SSE) con l'id atteso.
+fn rpc_result(text: &str, id: Option<u64>) -> Result<Value, McpError> {
+    let messages: Vec<Value> = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).into_iter().collect()
+    } else {
+        text.lines()
+            .filter_map(|l| l.strip_prefix("data:"))
+            .filter_map(|d| serde_json::from_str(d.trim()).ok())
+            .collect()
+    };
+    let msg = messages.into_iter()
+        .find(|m| id.is_none() || m["id"].as_u64() == id)
+        .ok_or(McpError::EmptyResponse)?;
+    if let Some(err) = msg.get("error") {
+        return Err(McpError::Rpc {
+            code: err["code"].as_i64().unwrap_or(0),
+            message: err["message"].as_str().unwrap_or("").to_string(),
+        });
+    }
+    msg.get("result").cloned().ok_or(McpError::EmptyResponse)
+}
+
+fn content_text(result: &Value) -> Option<String> {
+    let parts: Vec<&str> = result["content"].as_array()?.iter()
+        .filter(|c| c["type"] == "text")
+        .filter_map(|c| c["text"].as_str())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n"))
+}
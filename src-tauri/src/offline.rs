@@ -0,0 +1,242 @@
+// offline.rs — modalità offline
+//
+// Si entra in offline in due modi:
+//   • a mano dal frontend (`set_offline_mode`), es. in aereo o dietro un captive portal
+//   • automaticamente, quando una richiesta Riot fallisce per rete e la sonda lo conferma
+// In offline riot_api non invia richieste (errore `Offline` immediato) e i comandi
+// servono profilo, match, maestrie e dati statici solo da store locale / disco,
+// aggiungendo `stale_since` (RFC3339: quando il dato era ancora fresco).
+// Gli aggiornamenti saltati finiscono in coda e partono al ritorno della rete.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+
+use crate::match_store::blocking;
+use crate::match_sync;
+use crate::riot_api::{champion_mastery_v4, league_v4, summoner_v4};
+use crate::static_data;
+
+/// Evento Tauri emesso a ogni cambio di stato (payload: `OfflineStatus`).
+pub const STATUS_EVENT: &str = "offline-status";
+
+const PROBE_URL: &str = "https://ddragon.leagueoflegends.com/api/versions.json";
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Offline attivato dall'utente: nessuna sonda, si esce solo a mano.
+static FORCED: AtomicBool = AtomicBool::new(false);
+/// Offline rilevato da un errore di rete: la sonda lo spegne da sola.
+static DETECTED: AtomicBool = AtomicBool::new(false);
+/// Sonda di rete in corso (al massimo una).
+static PROBING: AtomicBool = AtomicBool::new(false);
+/// Istante (RFC3339) in cui siamo andati offline.
+static SINCE: Mutex<Option<String>> = Mutex::new(None);
+static QUEUE: Mutex<Vec<RefreshJob>> = Mutex::new(Vec::new());
+
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+pub fn set_app_handle(handle: AppHandle) {
+    let _ = APP_HANDLE.set(handle);
+}
+
+/// Aggiornamento rimandato al ritorno della rete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshJob {
+    /// Sync incrementale dello storico match.
+    Matches { puuid: String, region: String },
+    /// Top maestrie → snapshot "masteries".
+    Masteries { puuid: String, region: String },
+    /// Livello, icona e rank → snapshot "summoner".
+    Profile { puuid: String, region: String },
+    /// Ricontrollo della patch Data Dragon.
+    StaticData,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineStatus {
+    pub offline: bool,
+    /// `true` se attivato dall'utente, `false` se rilevato.
+    pub forced: bool,
+    pub since: Option<String>,
+    /// Aggiornamenti in coda per il ritorno della rete.
+    pub queued: usize,
+}
+
+pub fn is_offline() -> bool {
+    FORCED.load(Ordering::Relaxed) || DETECTED.load(Ordering::Relaxed)
+}
+
+pub fn status() -> OfflineStatus {
+    OfflineStatus {
+        offline: is_offline(),
+        forced: FORCED.load(Ordering::Relaxed),
+        since: SINCE.lock().unwrap().clone(),
+        queued: QUEUE.lock().unwrap().len(),
+    }
+}
+
+fn emit_status() {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit(STATUS_EVENT, status());
+    }
+}
+
+/// Aggiorna lo stato globale e lo propaga a static_data (che non dipende da questo modulo).
+fn apply(forced: bool, detected: bool) {
+    let was_offline = is_offline();
+    FORCED.store(forced, Ordering::Relaxed);
+    DETECTED.store(detected, Ordering::Relaxed);
+    let offline = is_offline();
+    static_data::OFFLINE.store(offline, Ordering::Relaxed);
+    if offline != was_offline {
+        *SINCE.lock().unwrap() = offline.then(|| chrono::Utc::now().to_rfc3339());
+        eprintln!("[Offline] {}", if offline { "modalità offline" } else { "di nuovo online" });
+        // La patch Data Dragon non è stata verificata: si ricontrolla al ritorno
+        if offline { queue(RefreshJob::StaticData); }
+    }
+    emit_status();
+}
+
+// ── Rilevamento ──────────────────────────────────────────────────────────────
+
+fn http() -> Client {
+    Client::builder().timeout(Duration::from_secs(10)).build().unwrap()
+}
+
+async fn probe(client: &Client) -> bool {
+    client.get(PROBE_URL).send().await.is_ok_and(|r| r.status().is_success())
+}
+
+/// Una richiesta è fallita per rete dopo tutti i tentativi: se la sonda conferma
+/// passiamo in offline e la sonda continua finché la rete non torna.
+pub fn report_network_error() {
+    if FORCED.load(Ordering::Relaxed) || PROBING.swap(true, Ordering::Relaxed) { return; }
+    tokio::spawn(async {
+        let client = http();
+        while !probe(&client).await {
+            // L'utente può aver forzato l'offline nel frattempo: da lì si esce solo a mano
+            if FORCED.load(Ordering::Relaxed) { break; }
+            if !DETECTED.load(Ordering::Relaxed) { apply(false, true); }
+            tokio::time::sleep(PROBE_INTERVAL).await;
+        }
+        let recovered = DETECTED.load(Ordering::Relaxed) && !FORCED.load(Ordering::Relaxed);
+        PROBING.store(false, Ordering::Relaxed);
+        if recovered {
+            apply(false, false);
+            drain_queue().await;
+        }
+    });
+}
+
+// ── Coda di aggiornamento ────────────────────────────────────────────────────
+
+/// Mette in coda un aggiornamento (senza duplicati).
+pub fn queue(job: RefreshJob) {
+    {
+        let mut q = QUEUE.lock().unwrap();
+        if q.contains(&job) { return; }
+        q.push(job);
+    }
+    emit_status();
+}
+
+async fn run_job(job: &RefreshJob, client: &Client) -> Result<(), String> {
+    match job {
+        RefreshJob::Matches { puuid, region } => {
+            match_sync::sync_new(puuid, region, client).await?;
+            match_sync::spawn_backfill(puuid, region, client.clone(), None);
+        }
+        RefreshJob::Masteries { puuid, region } => {
+            let masteries = champion_mastery_v4::top_by_puuid(region, puuid, 20).send(client).await?;
+            let (owner, snap) = (puuid.clone(), json!(masteries));
+            blocking(move |store| store.put_snapshot("masteries", &owner, &snap)).await;
+        }
+        RefreshJob::Profile { puuid, region } => {
+            let owner = puuid.clone();
+            let Some((mut snap, _)) = blocking(move |store| store.get_snapshot("summoner", &owner)).await.flatten()
+                else { return Ok(()) };
+            let summoner = summoner_v4::by_puuid(region, puuid).send(client).await?;
+            let entries  = league_v4::entries_by_puuid(region, puuid).send(client).await?;
+            snap["profile"]["summonerLevel"] = json!(summoner.summoner_level);
+            snap["profile"]["profileIconId"] = json!(summoner.profile_icon_id);
+            snap["ranked_entries"] = json!(entries);
+            let owner = puuid.clone();
+            blocking(move |store| store.put_snapshot("summoner", &owner, &snap)).await;
+        }
+        RefreshJob::StaticData => {
+            static_data::static_data().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Esegue la coda; i job falliti restano per il prossimo ritorno online.
+async fn drain_queue() {
+    let jobs = std::mem::take(&mut *QUEUE.lock().unwrap());
+    if jobs.is_empty() { return; }
+    eprintln!("[Offline] {} aggiornamenti in coda", jobs.len());
+    let client = http();
+    let mut failed = Vec::new();
+    for job in jobs {
+        if let Err(e) = run_job(&job, &client).await {
+            eprintln!("[Offline] {:?} fallito: {}", job, e);
+            failed.push(job);
+        }
+    }
+    QUEUE.lock().unwrap().extend(failed);
+    emit_status();
+}
+
+// ── Risposte ─────────────────────────────────────────────────────────────────
+
+/// RFC3339 di un istante in ms; senza istante noto usa l'inizio dell'offline.
+pub fn stale_since(updated_ms: Option<i64>) -> String {
+    updated_ms
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|dt| dt.to_rfc3339())
+        .or_else(|| SINCE.lock().unwrap().clone())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339())
+}
+
+/// Aggiunge `stale_since` alla risposta (a ogni elemento se è una lista,
+/// per non cambiare la forma che il frontend si aspetta).
+pub fn mark_stale(mut value: Value, since: &str) -> Value {
+    match &mut value {
+        Value::Object(map) => { map.insert("stale_since".into(), json!(since)); }
+        Value::Array(items) => {
+            for it in items.iter_mut() {
+                if let Value::Object(map) = it { map.insert("stale_since".into(), json!(since)); }
+            }
+        }
+        _ => {}
+    }
+    value
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_offline_status() -> Result<OfflineStatus, String> {
+    Ok(status())
+}
+
+/// Attiva/disattiva l'offline esplicito. All'uscita verifica la rete e svuota la coda.
+#[tauri::command]
+pub async fn set_offline_mode(enabled: bool) -> Result<OfflineStatus, String> {
+    if enabled {
+        apply(true, false);
+        return Ok(status());
+    }
+    let online = probe(&http()).await;
+    apply(false, !online);
+    if online {
+        tokio::spawn(drain_queue());
+    } else {
+        report_network_error();
+    }
+    Ok(status())
+}
@@ -0,0 +1,995 @@
+// opgg.rs — build OP.GG dal server MCP (tool `lol_get_champion_analysis`)
+//
+// Il tool risponde con `structuredContent` (JSON) oppure con un testo compatto in
+// stile "chiamata di funzione", preceduto a volte dallo schema delle classi:
+//
+//   class Runes:
+//     primary_page_id: int
+//     ...
+//   LolGetChampionAnalysis(Data(SummonerSpells([4,14],["Flash","Ignite"]),
+//     Runes(8000,"Precision",[8008,9111,9103,8017],8300,"Inspiration",[8313,8321],[5005,5008,5011]),
+//     StarterItems([1055,2003],[...]), ...))
+//
+// Qui il testo viene tokenizzato e parsato in un albero (`Expr`), convertito in JSON
+// usando i nomi dei campi dello schema (o quelli noti di default, nell'ordine di
+// `desired_output_fields`) e infine ridotto alla build usata da champ_select.
+// Una sezione obbligatoria mancante è un `ParseError::MissingSection` col suo nome.
+// Le richieste passano da un'unica sessione MCP condivisa e le build finiscono in
+// una cache per (campione, lane, modalità, patch) salvata in opgg_builds.json.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Number, Value};
+
+use crate::mcp::{McpError, McpSession, ToolOutput};
+use crate::static_data::StaticData;
+
+pub const MCP_URL: &str = "https://mcp-api.op.gg/mcp";
+pub const ANALYSIS_TOOL: &str = "lol_get_champion_analysis";
+/// Nome della chiamata radice nel formato testuale.
+const ANALYSIS_ROOT: &str = "LolGetChampionAnalysis";
+
+/// Campi di `data` richiesti al tool, nell'ordine in cui arrivano nel testo.
+const ANALYSIS_FIELDS: [(&str, &str); 11] = [
+    ("summoner_spells", "data.summoner_spells.{ids,ids_names}"),
+    ("runes",           "data.runes.{primary_page_id,primary_page_name,primary_rune_ids,secondary_page_id,secondary_page_name,secondary_rune_ids,stat_mod_ids}"),
+    ("starter_items",   "data.starter_items[].{ids,ids_names}"),
+    ("core_items",      "data.core_items[].{ids,ids_names}"),
+    ("last_items",      "data.last_items[].{ids,ids_names}"),
+    ("fourth_items",    "data.fourth_items[].{ids,ids_names}"),
+    ("fifth_items",     "data.fifth_items[].{ids,ids_names}"),
+    ("sixth_items",     "data.sixth_items[].{ids,ids_names}"),
+    ("boots",           "data.boots[].{ids,ids_names}"),
+    ("skills",          "data.skills.{order}"),
+    ("mythic_items",    "data.mythic_items[].{ids,ids_names}"),
+];
+
+/// Campi per l'Arena: niente rune né lane, in compenso gli augment.
+const ARENA_FIELDS: [(&str, &str); 7] = [
+    ("summoner_spells", "data.summoner_spells.{ids,ids_names}"),
+    ("augments",        "data.augments[].{ids,ids_names}"),
+    ("starter_items",   "data.starter_items[].{ids,ids_names}"),
+    ("core_items",      "data.core_items[].{ids,ids_names}"),
+    ("last_items",      "data.last_items[].{ids,ids_names}"),
+    ("boots",           "data.boots[].{ids,ids_names}"),
+    ("skills",          "data.skills.{order}"),
+];
+
+/// Counter della lane: campioni contro cui il campione analizzato perde
+/// (`win_rate` è quello del campione analizzato contro di loro).
+const COUNTER_FIELDS: [(&str, &str); 1] = [
+    ("weak_counters", "data.weak_counters[].{champion_id,champion_name,play,win,win_rate}"),
+];
+
+/// Lane LCU ("TOP", "MIDDLE", "UTILITY", ...) → lane OP.GG (MID se sconosciuta).
+pub fn position(lcu_position: &str) -> &'static str {
+    match lcu_position.to_uppercase().as_str() {
+        "TOP"                     => "top",
+        "JUNGLE"                  => "jungle",
+        "MIDDLE"|"MID"            => "mid",
+        "BOTTOM"|"BOT"|"ADC"      => "adc",
+        "SUPPORT"|"UTILITY"       => "support",
+        _                         => "mid",
+    }
+}
+
+/// Modalità OP.GG (`game_mode` del tool): "ranked", "aram", "urf", "arena".
+fn analysis_fields(mode: &str) -> &'static [(&'static str, &'static str)] {
+    if mode == "arena" { &ARENA_FIELDS } else { &ANALYSIS_FIELDS }
+}
+
+/// Schema di default quando il testo non include le definizioni `class`.
+const DEFAULT_SCHEMA: [(&str, &[&str]); 4] = [
+    (ANALYSIS_ROOT, &["data"]),
+    ("Runes", &["primary_page_id", "primary_page_name", "primary_rune_ids",
+                "secondary_page_id", "secondary_page_name", "secondary_rune_ids", "stat_mod_ids"]),
+    ("Skills", &["order"]),
+    // Item e spell condividono la stessa forma (e spesso lo stesso nome di classe)
+    ("SummonerSpells", &["ids", "ids_names"]),
+];
+
+// ── Errori ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// Testo non valido alla posizione (in byte) indicata.
+    Syntax { offset: usize, message: String },
+    /// Manca la chiamata radice (es. `LolGetChampionAnalysis(`).
+    MissingRoot(String),
+    /// Manca una sezione obbligatoria della build.
+    MissingSection(&'static str),
+    /// Sezione presente ma con una forma inattesa.
+    InvalidSection { section: &'static str, reason: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Syntax { offset, message } => write!(f, "sintassi non valida al byte {}: {}", offset, message),
+            ParseError::MissingRoot(root) => write!(f, "{}( non trovato", root),
+            ParseError::MissingSection(section) => write!(f, "sezione '{}' mancante", section),
+            ParseError::InvalidSection { section, reason } => write!(f, "sezione '{}' non valida: {}", section, reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// ── Tokenizer ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Str(String),
+    /// Numero come scritto nel testo (intero o decimale).
+    Num(String),
+    LParen, RParen,
+    LBracket, RBracket,
+    LBrace, RBrace,
+    Comma, Colon, Equals,
+}
+
+/// Tokenizer "pigro": si ferma dove finisce l'espressione, così il testo
+/// libero che segue (note, percentuali, ...) non viene mai letto.
+pub struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    /// Inizio dell'ultimo token letto (per gli errori del parser).
+    token_start: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Lexer { src, pos: 0, token_start: 0 }
+    }
+
+    /// Offset (in byte) dell'ultimo token letto.
+    pub fn offset(&self) -> usize {
+        self.token_start
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::Syntax { offset: self.pos, message: message.into() }
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    pub fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        while self.peek_char().is_some_and(char::is_whitespace) { self.bump(); }
+        self.token_start = self.pos;
+        let Some(c) = self.peek_char() else { return Ok(None) };
+        let punct = match c {
+            '(' => Some(Token::LParen), ')' => Some(Token::RParen),
+            '[' => Some(Token::LBracket), ']' => Some(Token::RBracket),
+            '{' => Some(Token::LBrace), '}' => Some(Token::RBrace),
+            ',' => Some(Token::Comma), ':' => Some(Token::Colon), '=' => Some(Token::Equals),
+            _ => None,
+        };
+        if let Some(t) = punct {
+            self.bump();
+            return Ok(Some(t));
+        }
+        if c == '"' || c == '\'' { return self.string(c).map(Some); }
+        let next_is_digit = self.src[self.pos..].chars().nth(1).is_some_and(|d| d.is_ascii_digit());
+        if c.is_ascii_digit() || (c == '-' && next_is_digit) { return Ok(Some(self.number())); }
+        if c.is_alphabetic() || c == '_' {
+            let start = self.pos;
+            while self.peek_char().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.') { self.bump(); }
+            return Ok(Some(Token::Ident(self.src[start..self.pos].to_string())));
+        }
+        Err(self.error(format!("carattere inatteso {:?}", c)))
+    }
+
+    fn string(&mut self, quote: char) -> Result<Token, ParseError> {
+        let start = self.pos;
+        self.bump();
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None => { self.pos = start; return Err(self.error("stringa non terminata")); }
+                Some(c) if c == quote => return Ok(Token::Str(out)),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                        out.push(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                    }
+                    Some(other) => out.push(other),
+                    None => { self.pos = start; return Err(self.error("stringa non terminata")); }
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Token {
+        let start = self.pos;
+        self.bump();
+        while let Some(c) = self.peek_char() {
+            let exp_sign = (c == '-' || c == '+') && self.src[..self.pos].ends_with(['e', 'E']);
+            if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exp_sign { self.bump(); } else { break; }
+        }
+        Token::Num(self.src[start..self.pos].to_string())
+    }
+}
+
+// ── Parser ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// `Name(arg, key=arg, ...)`: argomenti posizionali (`None`) o per nome.
+    Call { name: String, args: Vec<(Option<String>, Expr)> },
+    List(Vec<Expr>),
+    Dict(Vec<(String, Expr)>),
+    Str(String),
+    Num(String),
+    Bool(bool),
+    Null,
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Token>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::Syntax { offset: self.lexer.offset(), message: message.into() }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>, ParseError> {
+        if self.peeked.is_none() { self.peeked = self.lexer.next_token()?; }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError> {
+        match self.peeked.take() {
+            Some(t) => Ok(t),
+            None => self.lexer.next_token()?.ok_or_else(|| self.error("fine del testo inattesa")),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        let t = self.next()?;
+        if t == expected { Ok(()) } else { Err(self.error(format!("atteso {:?}, trovato {:?}", expected, t))) }
+    }
+
+    /// Consuma `,` se presente; `true` se la sequenza è finita (`close` o fine).
+    fn separator(&mut self, close: &Token) -> Result<bool, ParseError> {
+        match self.peek()? {
+            Some(Token::Comma) => { self.next()?; Ok(self.peek()? == Some(close)) }
+            Some(t) if t == close => Ok(true),
+            _ => { let t = self.next()?; Err(self.error(format!("atteso ',' o {:?}, trovato {:?}", close, t))) }
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        match self.next()? {
+            Token::Str(s) => Ok(Expr::Str(s)),
+            Token::Num(n) => Ok(Expr::Num(n)),
+            Token::LBracket => {
+                let mut items = Vec::new();
+                if self.peek()? != Some(&Token::RBracket) {
+                    loop {
+                        items.push(self.expr()?);
+                        if self.separator(&Token::RBracket)? { break; }
+                    }
+                }
+                self.expect(Token::RBracket)?;
+                Ok(Expr::List(items))
+            }
+            Token::LBrace => {
+                let mut entries = Vec::new();
+                if self.peek()? != Some(&Token::RBrace) {
+                    loop {
+                        let key = match self.next()? {
+                            Token::Str(k) | Token::Ident(k) => k,
+                            t => return Err(self.error(format!("chiave non valida {:?}", t))),
+                        };
+                        self.expect(Token::Colon)?;
+                        entries.push((key, self.expr()?));
+                        if self.separator(&Token::RBrace)? { break; }
+                    }
+                }
+                self.expect(Token::RBrace)?;
+                Ok(Expr::Dict(entries))
+            }
+            Token::Ident(word) => self.after_ident(word),
+            t => Err(self.error(format!("token inatteso {:?}", t))),
+        }
+    }
+
+    /// Dopo un identificatore: chiamata `Name(...)` o valore letterale.
+    fn after_ident(&mut self, word: String) -> Result<Expr, ParseError> {
+        if self.peek()? != Some(&Token::LParen) {
+            return Ok(match word.as_str() {
+                "true" | "True" => Expr::Bool(true),
+                "false" | "False" => Expr::Bool(false),
+                "null" | "None" | "nil" => Expr::Null,
+                // Identificatore nudo (valori enum)
+                _ => Expr::Str(word),
+            });
+        }
+        self.next()?;
+        let mut args = Vec::new();
+        if self.peek()? != Some(&Token::RParen) {
+            loop {
+                let arg = match self.peek()? {
+                    Some(Token::Ident(_)) => {
+                        let Token::Ident(ident) = self.next()? else { unreachable!() };
+                        if self.peek()? == Some(&Token::Equals) {
+                            // `key=value`
+                            self.next()?;
+                            (Some(ident), self.expr()?)
+                        } else {
+                            (None, self.after_ident(ident)?)
+                        }
+                    }
+                    _ => (None, self.expr()?),
+                };
+                args.push(arg);
+                if self.separator(&Token::RParen)? { break; }
+            }
+        }
+        self.expect(Token::RParen)?;
+        Ok(Expr::Call { name: word, args })
+    }
+}
+
+/// Parsa la prima espressione di `src` (il resto del testo viene ignorato).
+pub fn parse_expr(src: &str) -> Result<Expr, ParseError> {
+    Parser { lexer: Lexer::new(src), peeked: None }.expr()
+}
+
+// ── Schema e conversione in JSON ─────────────────────────────────────────────
+
+/// Nome classe → nomi dei campi, nell'ordine degli argomenti posizionali.
+pub type Schema = HashMap<String, Vec<String>>;
+
+/// Legge le definizioni `class Name:` seguite da righe indentate `campo: Tipo`.
+pub fn parse_schema(text: &str) -> Schema {
+    let mut schema = Schema::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix("class ") {
+            let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+            current = (!name.is_empty()).then_some(name);
+            if let Some(name) = &current { schema.insert(name.clone(), Vec::new()); }
+            continue;
+        }
+        let indented = line.starts_with([' ', '\t']);
+        let field = trimmed.split_once(':').map(|(f, _)| f.trim())
+            .filter(|f| !f.is_empty() && f.chars().all(|c| c.is_alphanumeric() || c == '_'));
+        match (&current, indented, field) {
+            (Some(name), true, Some(field)) => schema.get_mut(name).unwrap().push(field.to_string()),
+            (_, true, None) if trimmed.is_empty() => {}
+            _ => current = None,
+        }
+    }
+    schema
+}
+
+fn default_schema(fields: &[(&str, &str)]) -> Schema {
+    let mut schema: Schema = DEFAULT_SCHEMA.iter()
+        .map(|(name, fields)| (name.to_string(), fields.iter().map(|f| f.to_string()).collect()))
+        .collect();
+    schema.insert("Data".to_string(), fields.iter().map(|(f, _)| f.to_string()).collect());
+    schema
+}
+
+fn number(raw: &str) -> Value {
+    raw.parse::<i64>().map(Value::from)
+        .ok()
+        .or_else(|| raw.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// Converte l'albero in JSON: le chiamate con schema noto diventano oggetti,
+/// le altre liste dei loro argomenti.
+pub fn to_json(expr: &Expr, schema: &Schema) -> Value {
+    match expr {
+        Expr::Call { name, args } => {
+            let fields = schema.get(name);
+            let named = fields.is_some() || args.iter().all(|(k, _)| k.is_some());
+            if !named {
+                return Value::Array(args.iter().map(|(_, a)| to_json(a, schema)).collect());
+            }
+            let mut obj = Map::new();
+            let mut positional = fields.map(|f| f.iter()).into_iter().flatten();
+            for (key, arg) in args {
+                let key = match key {
+                    Some(k) => Some(k.clone()),
+                    None => positional.next().cloned(),
+                };
+                if let Some(key) = key { obj.insert(key, to_json(arg, schema)); }
+            }
+            Value::Object(obj)
+        }
+        Expr::List(items) => Value::Array(items.iter().map(|e| to_json(e, schema)).collect()),
+        Expr::Dict(entries) => Value::Object(entries.iter().map(|(k, v)| (k.clone(), to_json(v, schema))).collect()),
+        Expr::Str(s) => Value::String(s.clone()),
+        Expr::Num(n) => number(n),
+        Expr::Bool(b) => Value::Bool(*b),
+        Expr::Null => Value::Null,
+    }
+}
+
+/// Testo del tool → JSON (`{"data": {...}}`). Accetta anche testo che è già JSON.
+/// `mode` decide i nomi dei campi posizionali di `Data`.
+pub fn parse_text(text: &str, mode: &str) -> Result<Value, ParseError> {
+    parse_text_fields(text, analysis_fields(mode))
+}
+
+/// Come `parse_text`, con i campi di `Data` richiesti esplicitamente.
+fn parse_text_fields(text: &str, fields: &[(&str, &str)]) -> Result<Value, ParseError> {
+    if let Ok(v) = serde_json::from_str::<Value>(text.trim()) {
+        if v.is_object() { return Ok(v); }
+    }
+    let start = text.find(&format!("{}(", ANALYSIS_ROOT)).ok_or_else(|| ParseError::MissingRoot(ANALYSIS_ROOT.to_string()))?;
+    let expr = parse_expr(&text[start..]).map_err(|e| match e {
+        ParseError::Syntax { offset, message } => ParseError::Syntax { offset: start + offset, message },
+        other => other,
+    })?;
+    let mut schema = default_schema(fields);
+    schema.extend(parse_schema(&text[..start]));
+    Ok(to_json(&expr, &schema))
+}
+
+// ── Build ────────────────────────────────────────────────────────────────────
+
+/// Un blocco `{ids, ids_names}`, una lista di blocchi (si prende il primo) o
+/// direttamente la lista di id.
+fn block_ids(v: &Value) -> Vec<u64> {
+    match v {
+        Value::Object(_) => v["ids"].as_array().map(|a| a.iter().filter_map(Value::as_u64).collect()).unwrap_or_default(),
+        Value::Array(items) => match items.first() {
+            Some(Value::Number(_)) => items.iter().filter_map(Value::as_u64).collect(),
+            Some(first) => block_ids(first),
+            None => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Tutti gli id di una lista di blocchi alternativi (4°, 5°, 6° item, ...).
+fn group_ids(v: &Value) -> Vec<u64> {
+    match v {
+        Value::Array(items) if items.iter().all(|i| !i.is_number()) => {
+            let mut ids = Vec::new();
+            for id in items.iter().flat_map(block_ids) {
+                if !ids.contains(&id) { ids.push(id); }
+            }
+            ids
+        }
+        other => block_ids(other),
+    }
+}
+
+fn ids_of(v: &Value) -> Vec<u64> {
+    v.as_array().map(|a| a.iter().filter_map(Value::as_u64).collect()).unwrap_or_default()
+}
+
+fn to_items(ids: &[u64]) -> Value {
+    Value::Array(ids.iter().map(|&id| json!({"id": id})).collect())
+}
+
+/// Coppie (id, nome) di una lista di blocchi `{ids, ids_names}` (o `[ids, nomi]`
+/// se il testo non aveva lo schema), senza duplicati.
+fn named_ids(v: &Value) -> Vec<(u64, String)> {
+    let blocks: Vec<&Value> = match v {
+        Value::Array(items) if items.first().is_some_and(|i| i.is_object() || i.get(0).is_some_and(Value::is_array)) => items.iter().collect(),
+        other => vec![other],
+    };
+    let mut out: Vec<(u64, String)> = Vec::new();
+    for block in blocks {
+        let (ids, names) = match block {
+            Value::Object(_) => (&block["ids"], &block["ids_names"]),
+            _ => (&block[0], &block[1]),
+        };
+        for (i, id) in ids_of(ids).into_iter().enumerate() {
+            if out.iter().any(|(seen, _)| *seen == id) { continue; }
+            let name = names.get(i).and_then(Value::as_str).map(String::from).unwrap_or_else(|| id.to_string());
+            out.push((id, name));
+        }
+    }
+    out
+}
+
+/// Sequenza livello per livello (al massimo 18 punti, "Q" | "W" | "E" | "R").
+fn skill_sequence(skills: &Value) -> Vec<String> {
+    let order = skills.get("order").unwrap_or(skills);
+    order.as_array().into_iter().flatten()
+        .filter_map(|s| match s {
+            Value::String(k) => Some(k.trim().to_uppercase()),
+            // 1=Q 2=W 3=E 4=R
+            Value::Number(n) => ["Q", "W", "E", "R"].get((n.as_u64()? as usize).checked_sub(1)?).map(|k| k.to_string()),
+            _ => None,
+        })
+        .filter(|k| matches!(k.as_str(), "Q" | "W" | "E" | "R"))
+        .take(18)
+        .collect()
+}
+
+/// "Q → E → W": ordine di massimizzazione dalle prime tre abilità distinte.
+fn skill_order(sequence: &[String]) -> String {
+    let mut seen: Vec<&str> = Vec::new();
+    for key in sequence {
+        if key != "R" && !seen.contains(&key.as_str()) {
+            seen.push(key);
+            if seen.len() == 3 { break; }
+        }
+    }
+    if seen.len() == 3 { seen.join(" → ") } else { String::new() }
+}
+
+/// Sezione `runes` normalizzata (`secondary_*` → `sub_*`).
+fn rune_section(runes: &Value) -> Result<Value, ParseError> {
+    let page_id = |keys: &[&str]| keys.iter().find_map(|k| runes[*k].as_u64());
+    let primary_page_id = page_id(&["primary_page_id"])
+        .ok_or_else(|| ParseError::InvalidSection { section: "runes", reason: "primary_page_id mancante".into() })?;
+    let sub_page_id = page_id(&["secondary_page_id", "sub_page_id"])
+        .ok_or_else(|| ParseError::InvalidSection { section: "runes", reason: "secondary_page_id mancante".into() })?;
+    let primary_rune_ids = ids_of(&runes["primary_rune_ids"]);
+    if primary_rune_ids.is_empty() {
+        return Err(ParseError::InvalidSection { section: "runes", reason: "primary_rune_ids vuoto".into() });
+    }
+    let sub_rune_ids = ids_of(runes.get("secondary_rune_ids").unwrap_or(&runes["sub_rune_ids"]));
+    let stat_mod_ids = ids_of(&runes["stat_mod_ids"]);
+    Ok(json!({
+        "primary_page_id": primary_page_id, "sub_page_id": sub_page_id,
+        "primary_rune_ids": primary_rune_ids, "sub_rune_ids": sub_rune_ids,
+        "stat_mod_ids": stat_mod_ids
+    }))
+}
+
+/// JSON dell'analisi (strutturato o convertito dal testo) → build per l'import.
+/// In Arena rune e spell non sono obbligatorie (`runes: null`).
+pub fn build_from_analysis(analysis: &Value, ddragon: Option<&StaticData>, mode: &str) -> Result<Value, ParseError> {
+    let data = analysis.get("data").unwrap_or(analysis);
+    let valid_item = |id: &u64| ddragon.is_none_or(|d| d.items.get(*id as u32).is_some());
+    let arena = mode == "arena";
+
+    // ── Rune ─────────────────────────────────────────────────────────────────
+    let runes = match data.get("runes").filter(|r| r.is_object()) {
+        Some(runes) => Some(rune_section(runes)?),
+        None if arena => None,
+        None => return Err(ParseError::MissingSection("runes")),
+    };
+
+    // ── Summoner spells ──────────────────────────────────────────────────────
+    let spell_ids = data.get("summoner_spells").map(block_ids).unwrap_or_default();
+    if spell_ids.len() < 2 && !arena { return Err(ParseError::MissingSection("summoner_spells")); }
+    // Chiave DDragon ("SummonerFlash"): il nome "Flash" vale anche per la variante Arena.
+    // Gli id sconosciuti (o senza DDragon) restano numerici: li segnala summoner_spells.rs.
+    let spell_key = |id: u64| ddragon.and_then(|d| d.spells.get(id as u32)).map(|sp| sp.key.clone())
+        .unwrap_or_else(|| id.to_string());
+    let summoner_spells: Vec<String> = spell_ids.iter().take(2).map(|&id| spell_key(id)).collect();
+
+    // ── Augment (solo Arena) ─────────────────────────────────────────────────
+    let augments: Vec<Value> = data.get("augments").map(named_ids).unwrap_or_default().into_iter()
+        .map(|(id, name)| json!({"id": id, "name": name}))
+        .collect();
+
+    // ── Item ─────────────────────────────────────────────────────────────────
+    let section = |name: &str| -> Vec<u64> {
+        data.get(name).map(block_ids).unwrap_or_default().into_iter().filter(valid_item).collect()
+    };
+    let starter = section("starter_items");
+    let core = section("core_items");
+    if core.is_empty() { return Err(ParseError::MissingSection("core_items")); }
+    let boots = section("boots");
+
+    let mut seen: HashSet<u64> = starter.iter().chain(&core).chain(&boots).copied().collect();
+    let mut slots = Vec::new();
+    for (label, name) in [("3rd Item Options", "last_items"), ("4th Item", "fourth_items"), ("5th Item", "fifth_items"), ("6th Item", "sixth_items")] {
+        let ids: Vec<u64> = data.get(name).map(group_ids).unwrap_or_default().into_iter()
+            .filter(valid_item)
+            .filter(|id| seen.insert(*id))
+            .collect();
+        if !ids.is_empty() { slots.push(json!({"label": label, "items": to_items(&ids)})); }
+    }
+
+    let skill_sequence = data.get("skills").map(skill_sequence).unwrap_or_default();
+
+    Ok(json!({
+        "data": {
+            "runes":           runes,
+            "summoner_spells": summoner_spells,
+            "augments":        augments,
+            "starter_items":   to_items(&starter),
+            "core_items":      to_items(&core),
+            "situ_slots":      Value::Array(slots),
+            "boots":           to_items(&boots),
+            "skill_order":     skill_order(&skill_sequence),
+            "skill_sequence":  skill_sequence
+        }
+    }))
+}
+
+/// Output del tool (strutturato o testo) → build.
+pub fn parse_analysis(output: &ToolOutput, ddragon: Option<&StaticData>, mode: &str) -> Result<Value, ParseError> {
+    let analysis = match output {
+        ToolOutput::Structured(v) => v.clone(),
+        ToolOutput::Text(t) => parse_text(t, mode)?,
+    };
+    build_from_analysis(&analysis, ddragon, mode)
+}
+
+// ── Counter ──────────────────────────────────────────────────────────────────
+
+/// Campione che batte in lane quello analizzato.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Counter {
+    pub champion_id: u32,
+    pub champion_name: String,
+    pub games: u32,
+    /// Win rate (0-1) del campione analizzato contro questo.
+    pub win_rate: f64,
+}
+
+/// Voce di `weak_counters`: oggetto con i nomi dei campi o lista posizionale
+/// `[champion_id, champion_name, play, win, win_rate]`.
+fn counter_entry(v: &Value) -> Option<Counter> {
+    let field = |name: &str, idx: usize| v.get(name).or_else(|| v.get(idx)).filter(|f| !f.is_null());
+    let games = field("play", 2).and_then(Value::as_u64).unwrap_or(0) as u32;
+    let wins = field("win", 3).and_then(Value::as_u64);
+    // Win rate a volte in percentuale (58.1), a volte in frazione (0.581)
+    let win_rate = field("win_rate", 4).and_then(Value::as_f64)
+        .map(|w| if w > 1.0 { w / 100.0 } else { w })
+        .or_else(|| wins.filter(|_| games > 0).map(|w| w as f64 / games as f64))?;
+    Some(Counter {
+        champion_id: field("champion_id", 0)?.as_u64()? as u32,
+        champion_name: field("champion_name", 1).and_then(Value::as_str).unwrap_or("").to_string(),
+        games,
+        win_rate,
+    })
+}
+
+/// Output del tool (strutturato o testo) → counter.
+pub fn parse_counters(output: &ToolOutput) -> Result<Vec<Counter>, ParseError> {
+    let analysis = match output {
+        ToolOutput::Structured(v) => v.clone(),
+        ToolOutput::Text(t) => parse_text_fields(t, &COUNTER_FIELDS)?,
+    };
+    let data = analysis.get("data").unwrap_or(&analysis);
+    let list = data.get("weak_counters").and_then(Value::as_array)
+        .ok_or(ParseError::MissingSection("weak_counters"))?;
+    Ok(list.iter().filter_map(counter_entry).collect())
+}
+
+// ── Cache ────────────────────────────────────────────────────────────────────
+
+/// Una build resta valida per 6 ore (le statistiche OP.GG cambiano lentamente).
+const CACHE_TTL_SECS: i64 = 6 * 60 * 60;
+/// Le voci scadute restano su disco per 3 giorni come ripiego se OP.GG non risponde.
+const CACHE_KEEP_SECS: i64 = 3 * 24 * 60 * 60;
+
+/// Cartella dati dell'app; impostata in `setup()`.
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Cache caricata da disco al primo utilizzo.
+static CACHE: Mutex<Option<HashMap<String, CachedBuild>>> = Mutex::new(None);
+/// Sessione MCP condivisa da tutte le richieste (anche le 5 lane in parallelo).
+static SESSION: McpSession = McpSession::new(MCP_URL);
+
+pub fn init(app_data_dir: &Path) {
+    let _ = CACHE_DIR.set(app_data_dir.to_path_buf());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedBuild {
+    /// Unix secondi.
+    fetched_at: i64,
+    build: Value,
+}
+
+fn cache_file() -> Option<PathBuf> {
+    CACHE_DIR.get().map(|d| d.join("opgg_builds.json"))
+}
+
+/// Cambia quando cambia il formato della build: le voci vecchie non vengono più lette.
+const CACHE_FORMAT: u32 = 2;
+
+fn cache_key(champion: &str, position: &str, mode: &str, patch: &str) -> String {
+    format!("v{}|{}|{}|{}|{}", CACHE_FORMAT, champion.to_lowercase(), position, mode, patch)
+}
+
+/// Esegue `f` sulla cache (caricandola da disco se serve).
+fn with_cache<T>(f: impl FnOnce(&mut HashMap<String, CachedBuild>) -> T) -> T {
+    let mut guard = CACHE.lock().unwrap();
+    let cache = guard.get_or_insert_with(|| {
+        cache_file()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    });
+    f(cache)
+}
+
+/// Build in cache: `fresh` la scarta se più vecchia del TTL.
+fn cached(key: &str, fresh: bool) -> Option<Value> {
+    let now = chrono::Utc::now().timestamp();
+    with_cache(|c| c.get(key)
+        .filter(|e| !fresh || now - e.fetched_at < CACHE_TTL_SECS)
+        .map(|e| e.build.clone()))
+}
+
+fn store(key: String, build: &Value) {
+    let now = chrono::Utc::now().timestamp();
+    let content = with_cache(|c| {
+        c.retain(|_, e| now - e.fetched_at < CACHE_KEEP_SECS);
+        c.insert(key, CachedBuild { fetched_at: now, build: build.clone() });
+        serde_json::to_string(c).ok()
+    });
+    let (Some(path), Some(content)) = (cache_file(), content) else { return };
+    // Scrittura atomica: file temporaneo + rename
+    let tmp = path.with_extension("json.tmp");
+    let result = fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+        .and_then(|_| fs::write(&tmp, content))
+        .and_then(|_| fs::rename(&tmp, &path));
+    if let Err(e) = result { eprintln!("[OPGG] salvataggio cache fallito: {}", e); }
+}
+
+// ── Fetch ────────────────────────────────────────────────────────────────────
+
+/// Build per campione, lane OP.GG ("top", "jungle", "mid", "adc", "support") e
+/// modalità ("ranked", "aram", "urf", "arena"). ARAM, URF e Arena non hanno lane:
+/// `position` è `None`. Dalla cache se fresca per questa patch; se OP.GG non
+/// risponde si ripiega sull'ultima build salvata, anche se scaduta.
+pub async fn champion_build(champion: &str, position: Option<&str>, mode: &str, ddragon: Option<&StaticData>) -> Result<Value, McpError> {
+    let patch = ddragon.map(|d| d.version.as_str()).unwrap_or("unknown");
+    let key = cache_key(champion, position.unwrap_or("-"), mode, patch);
+    if let Some(build) = cached(&key, true) {
+        eprintln!("[OPGG] cache hit {}", key);
+        return Ok(build);
+    }
+
+    let desired: Vec<&str> = analysis_fields(mode).iter().map(|(_, spec)| *spec).collect();
+    let mut args = json!({
+        "champion": champion.to_uppercase(),
+        "game_mode": mode,
+        "lang": "en_US",
+        "desired_output_fields": desired,
+    });
+    if let Some(position) = position { args["position"] = json!(position); }
+    let output = SESSION.call_tool(ANALYSIS_TOOL, args).await;
+    let build = match output {
+        Ok(output) => {
+            if let ToolOutput::Text(t) = &output {
+                eprintln!("[OPGG] {} {} {}: {} byte di testo", champion, mode, position.unwrap_or("-"), t.len());
+            }
+            parse_analysis(&output, ddragon, mode)?
+        }
+        Err(e) => {
+            if let Some(build) = cached(&key, false) {
+                eprintln!("[OPGG] {} — uso la build scaduta in cache {}", e, key);
+                return Ok(build);
+            }
+            return Err(e);
+        }
+    };
+    store(key, &build);
+    Ok(build)
+}
+
+/// Counter di lane per campione e lane OP.GG, dal ranked. Stessa cache delle build.
+/// Li usano solo i suggerimenti di matchup (matchups.rs, nel binario).
+#[allow(dead_code)]
+pub async fn champion_counters(champion: &str, position: &str, ddragon: Option<&StaticData>) -> Result<Vec<Counter>, McpError> {
+    let patch = ddragon.map(|d| d.version.as_str()).unwrap_or("unknown");
+    let key = cache_key(champion, position, "counters", patch);
+    let from_value = |v: Value| serde_json::from_value::<Vec<Counter>>(v).ok();
+    if let Some(counters) = cached(&key, true).and_then(from_value) {
+        eprintln!("[OPGG] cache hit {}", key);
+        return Ok(counters);
+    }
+    let desired: Vec<&str> = COUNTER_FIELDS.iter().map(|(_, spec)| *spec).collect();
+    let args = json!({
+        "champion": champion.to_uppercase(),
+        "game_mode": "ranked",
+        "position": position,
+        "lang": "en_US",
+        "desired_output_fields": desired,
+    });
+    let counters = match SESSION.call_tool(ANALYSIS_TOOL, args).await {
+        Ok(output) => parse_counters(&output)?,
+        Err(e) => {
+            if let Some(counters) = cached(&key, false).and_then(from_value) {
+                eprintln!("[OPGG] {} — uso i counter scaduti in cache {}", e, key);
+                return Ok(counters);
+            }
+            return Err(e);
+        }
+    };
+    store(key, &json!(counters));
+    Ok(counters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_FIXTURE: &str = include_str!("../tests/fixtures/opgg_analysis_schema.txt");
+    const POSITIONAL_FIXTURE: &str = include_str!("../tests/fixtures/opgg_analysis_positional.txt");
+    const STRUCTURED_FIXTURE: &str = include_str!("../tests/fixtures/opgg_analysis_structured.json");
+    const NO_RUNES_FIXTURE: &str = include_str!("../tests/fixtures/opgg_analysis_no_runes.txt");
+    const ARENA_FIXTURE: &str = include_str!("../tests/fixtures/opgg_analysis_arena.txt");
+
+    fn tokens(src: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(src);
+        let mut out = Vec::new();
+        while let Some(t) = lexer.next_token().unwrap() { out.push(t); }
+        out
+    }
+
+    #[test]
+    fn tokenizes_calls_lists_and_literals() {
+        assert_eq!(tokens(r#"Runes(8000,"Pre\"cision",[-1, 2.5e-3])"#), vec![
+            Token::Ident("Runes".into()), Token::LParen,
+            Token::Num("8000".into()), Token::Comma,
+            Token::Str("Pre\"cision".into()), Token::Comma,
+            Token::LBracket, Token::Num("-1".into()), Token::Comma, Token::Num("2.5e-3".into()), Token::RBracket,
+            Token::RParen,
+        ]);
+    }
+
+    #[test]
+    fn lexer_stops_at_expression_end() {
+        let expr = parse_expr("Skills([\"Q\",\"E\"]) — 52.3% win rate, ecc.").unwrap();
+        assert_eq!(expr, Expr::Call {
+            name: "Skills".into(),
+            args: vec![(None, Expr::List(vec![Expr::Str("Q".into()), Expr::Str("E".into())]))],
+        });
+    }
+
+    #[test]
+    fn parses_keyword_arguments_and_constants() {
+        let expr = parse_expr("Boots(ids=[3047], note=None, core=True, tier=S)").unwrap();
+        let json = to_json(&expr, &Schema::new());
+        assert_eq!(json, json!({"ids": [3047], "note": null, "core": true, "tier": "S"}));
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_offset() {
+        assert_eq!(parse_expr("Runes(8000,,1)"), Err(ParseError::Syntax {
+            offset: 11, message: "token inatteso Comma".into(),
+        }));
+        assert!(matches!(parse_expr("Runes(\"abc"), Err(ParseError::Syntax { offset: 6, .. })));
+        assert!(matches!(parse_expr("Runes(1, 2"), Err(ParseError::Syntax { .. })));
+    }
+
+    #[test]
+    fn syntax_error_offset_is_relative_to_full_text() {
+        let text = "intro\nLolGetChampionAnalysis(Data(@))";
+        assert!(matches!(parse_text(text, "ranked"), Err(ParseError::Syntax { offset: 34, .. })));
+    }
+
+    #[test]
+    fn reads_class_schema() {
+        let schema = parse_schema(SCHEMA_FIXTURE);
+        assert_eq!(schema["Runes"][0], "primary_page_id");
+        assert_eq!(schema["Runes"].len(), 7);
+        assert_eq!(schema["CoreItems"], vec!["ids", "ids_names"]);
+        assert_eq!(schema["Skills"], vec!["order"]);
+    }
+
+    #[test]
+    fn schema_fixture_builds() {
+        let build = parse_analysis(&ToolOutput::Text(SCHEMA_FIXTURE.into()), None, "ranked").unwrap();
+        let data = &build["data"];
+        assert_eq!(data["runes"]["primary_page_id"], 8000);
+        assert_eq!(data["runes"]["sub_page_id"], 8300);
+        assert_eq!(data["runes"]["primary_rune_ids"], json!([8010, 9111, 9104, 8299]));
+        assert_eq!(data["runes"]["sub_rune_ids"], json!([8304, 8347]));
+        assert_eq!(data["runes"]["stat_mod_ids"], json!([5005, 5008, 5011]));
+        assert_eq!(data["starter_items"], json!([{"id": 1055}, {"id": 2003}]));
+        assert_eq!(data["core_items"], json!([{"id": 6672}, {"id": 3031}, {"id": 3094}]));
+        assert_eq!(data["boots"], json!([{"id": 3006}]));
+        assert_eq!(data["skill_order"], "Q → W → E");
+        assert_eq!(data["skill_sequence"], json!(["Q","W","E","Q","Q","R","Q","W","Q","W","R","W","W","E","E","R","E","E"]));
+        // 4° item: le alternative già viste (3031) non si ripetono
+        assert_eq!(data["situ_slots"][1], json!({"label": "4th Item", "items": [{"id": 3036}, {"id": 3072}]}));
+    }
+
+    #[test]
+    fn positional_fixture_matches_schema_fixture() {
+        let with_schema = parse_analysis(&ToolOutput::Text(SCHEMA_FIXTURE.into()), None, "ranked").unwrap();
+        let positional = parse_analysis(&ToolOutput::Text(POSITIONAL_FIXTURE.into()), None, "ranked").unwrap();
+        assert_eq!(with_schema, positional);
+    }
+
+    #[test]
+    fn structured_content_builds() {
+        let v: Value = serde_json::from_str(STRUCTURED_FIXTURE).unwrap();
+        let build = parse_analysis(&ToolOutput::Structured(v), None, "ranked").unwrap();
+        assert_eq!(build["data"]["runes"]["primary_rune_ids"], json!([8112, 8139, 8138, 8135]));
+        assert_eq!(build["data"]["core_items"], json!([{"id": 6655}, {"id": 3020}, {"id": 4645}]));
+        assert_eq!(build["data"]["skill_order"], "Q → E → W");
+        // Il testo che contiene JSON viene trattato come strutturato
+        let from_text = parse_analysis(&ToolOutput::Text(STRUCTURED_FIXTURE.into()), None, "ranked").unwrap();
+        assert_eq!(build, from_text);
+    }
+
+    #[test]
+    fn missing_sections_are_named() {
+        let err = parse_analysis(&ToolOutput::Text(NO_RUNES_FIXTURE.into()), None, "ranked").unwrap_err();
+        assert_eq!(err, ParseError::MissingSection("runes"));
+        assert_eq!(err.to_string(), "sezione 'runes' mancante");
+
+        let no_spells = json!({"data": {"runes": {"primary_page_id": 8000, "secondary_page_id": 8100, "primary_rune_ids": [8005]}}});
+        assert_eq!(build_from_analysis(&no_spells, None, "ranked"), Err(ParseError::MissingSection("summoner_spells")));
+
+        let no_core = json!({"data": {
+            "runes": {"primary_page_id": 8000, "secondary_page_id": 8100, "primary_rune_ids": [8005]},
+            "summoner_spells": {"ids": [4, 14]},
+        }});
+        assert_eq!(build_from_analysis(&no_core, None, "ranked"), Err(ParseError::MissingSection("core_items")));
+
+        assert_eq!(parse_text("Nessun dato disponibile", "ranked"),
+            Err(ParseError::MissingRoot(ANALYSIS_ROOT.into())));
+    }
+
+    #[test]
+    fn arena_build_has_augments_and_no_runes() {
+        let build = parse_analysis(&ToolOutput::Text(ARENA_FIXTURE.into()), None, "arena").unwrap();
+        let data = &build["data"];
+        assert_eq!(data["runes"], Value::Null);
+        assert_eq!(data["augments"], json!([
+            {"id": 1107, "name": "Jeweled Gauntlet"},
+            {"id": 1095, "name": "Mystic Punch"},
+        ]));
+        assert_eq!(data["core_items"], json!([{"id": 3089}, {"id": 4645}]));
+        assert_eq!(data["boots"], json!([{"id": 3020}]));
+        assert_eq!(data["skill_order"], "Q → E → W");
+        assert_eq!(data["skill_sequence"], json!(["Q", "E", "W", "Q"]));
+        // Lo stesso testo letto come ranked non ha rune: errore esplicito
+        assert!(parse_analysis(&ToolOutput::Text(ARENA_FIXTURE.into()), None, "ranked").is_err());
+    }
+
+    #[test]
+    fn skill_sequence_accepts_numeric_keys() {
+        let seq = skill_sequence(&json!({"order": [1, 3, 2, 1, 1, 4, 0, "x"]}));
+        assert_eq!(seq, ["Q", "E", "W", "Q", "Q", "R"]);
+        assert_eq!(skill_order(&seq), "Q → E → W");
+        assert_eq!(skill_order(&seq[..2]), "");
+    }
+
+    #[test]
+    fn counters_from_text_and_structured() {
+        let text = r#"LolGetChampionAnalysis(Data([Counter(268,"Azir",1520,640,42.1),Counter(61,"Orianna",900,null,0.45)]))"#;
+        let counters = parse_counters(&ToolOutput::Text(text.into())).unwrap();
+        assert_eq!(counters.len(), 2);
+        assert_eq!((counters[0].champion_id, counters[0].games), (268, 1520));
+        assert!((counters[0].win_rate - 0.421).abs() < 1e-9);
+        assert!((counters[1].win_rate - 0.45).abs() < 1e-9);
+
+        let structured = json!({"data": {"weak_counters": [
+            {"champion_id": 268, "champion_name": "Azir", "play": 1000, "win": 420},
+        ]}});
+        let counters = parse_counters(&ToolOutput::Structured(structured)).unwrap();
+        assert!((counters[0].win_rate - 0.42).abs() < 1e-9);
+
+        assert_eq!(parse_counters(&ToolOutput::Structured(json!({"data": {}}))).unwrap_err(),
+            ParseError::MissingSection("weak_counters"));
+    }
+
+    #[test]
+    fn invalid_runes_section_is_reported() {
+        let v = json!({"data": {"runes": {"primary_rune_ids": [8005]}}});
+        assert!(matches!(build_from_analysis(&v, None, "ranked"),
+            Err(ParseError::InvalidSection { section: "runes", .. })));
+    }
+}
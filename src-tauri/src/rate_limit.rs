@@ -0,0 +1,290 @@
+// rate_limit.rs — rate limiter condiviso per le Riot API
+//
+// Riot applica limiti per chiave su ogni host (euw1, europe, ...):
+//   • app limit    → X-App-Rate-Limit: "20:1,100:120" (20 req/s, 100 req/2min)
+//   • method limit → X-Method-Rate-Limit: "2000:60" (per endpoint)
+// Ogni richiesta chiama `acquire(host, method)` prima di partire: se una finestra è
+// piena la richiesta resta in coda (FIFO per host e metodo, grazie al Mutex tokio)
+// invece di prendere un 429. Un metodo al suo limite non blocca gli altri metodi
+// dello stesso host: il controllo degli slot sta dietro un Mutex sincrono, mai tenuto
+// durante l'attesa. I limiti vengono aggiornati dagli header di ogni risposta.
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Mutex, OnceCell};
+
+/// Limiti della dev key, usati finché Riot non ci manda i suoi header.
+const DEFAULT_APP_LIMIT: &str = "20:1,100:120";
+
+/// Evento Tauri con la profondità della coda (payload: `RateLimitStatus`).
+pub const QUEUE_EVENT: &str = "riot-rate-limit";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStatus {
+    pub host: String,
+    /// Richieste in attesa di uno slot su questo host.
+    pub queued: usize,
+    /// Richieste in attesa su tutti gli host.
+    pub total_queued: usize,
+}
+
+/// Finestra scorrevole: al massimo `limit` richieste negli ultimi `period`.
+#[derive(Debug)]
+struct Window {
+    limit: usize,
+    period: Duration,
+    hits: VecDeque<Instant>,
+}
+
+impl Window {
+    fn prune(&mut self, now: Instant) {
+        while let Some(&t) = self.hits.front() {
+            if now.duration_since(t) >= self.period { self.hits.pop_front(); } else { break; }
+        }
+    }
+
+    /// Tempo da attendere prima che si liberi uno slot (zero = slot libero).
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.prune(now);
+        if self.hits.len() < self.limit { return Duration::ZERO; }
+        let oldest = self.hits[self.hits.len() - self.limit];
+        (oldest + self.period).saturating_duration_since(now)
+    }
+}
+
+/// "20:1,100:120" → [(20, 1s), (100, 120s)]
+fn parse_limits(header: &str) -> Vec<(usize, Duration)> {
+    header.split(',').filter_map(|pair| {
+        let (count, secs) = pair.trim().split_once(':')?;
+        let count = count.trim().parse::<usize>().ok()?;
+        let secs  = secs.trim().parse::<u64>().ok()?;
+        if count == 0 || secs == 0 { return None; }
+        Some((count, Duration::from_secs(secs)))
+    }).collect()
+}
+
+/// Aggiorna le finestre mantenendo lo storico delle richieste già fatte.
+fn apply_limits(windows: &mut Vec<Window>, limits: &[(usize, Duration)]) {
+    if limits.is_empty() { return; }
+    let same = windows.len() == limits.len()
+        && windows.iter().zip(limits).all(|(w, (l, p))| w.limit == *l && w.period == *p);
+    if same { return; }
+    let history: VecDeque<Instant> = windows.iter()
+        .max_by_key(|w| w.period)
+        .map(|w| w.hits.clone())
+        .unwrap_or_default();
+    *windows = limits.iter().map(|&(limit, period)| Window {
+        limit, period, hits: history.clone(),
+    }).collect();
+}
+
+#[derive(Debug)]
+struct HostState {
+    app: Vec<Window>,
+    methods: HashMap<&'static str, Vec<Window>>,
+    /// Impostato da un 429 con Retry-After: nessuna richiesta prima di questo istante.
+    blocked_until: Option<Instant>,
+}
+
+impl HostState {
+    fn new() -> Self {
+        let mut app = Vec::new();
+        apply_limits(&mut app, &parse_limits(DEFAULT_APP_LIMIT));
+        HostState { app, methods: HashMap::new(), blocked_until: None }
+    }
+
+    /// Attesa necessaria per `method` adesso; se zero registra subito la richiesta.
+    fn try_take(&mut self, method: &'static str) -> Duration {
+        let now = Instant::now();
+        let mut wait = self.blocked_until
+            .map(|t| t.saturating_duration_since(now))
+            .unwrap_or(Duration::ZERO);
+        for w in self.app.iter_mut() { wait = wait.max(w.wait(now)); }
+        if let Some(ws) = self.methods.get_mut(method) {
+            for w in ws.iter_mut() { wait = wait.max(w.wait(now)); }
+        }
+        if !wait.is_zero() { return wait; }
+
+        self.blocked_until = None;
+        for w in self.app.iter_mut() { w.hits.push_back(now); }
+        if let Some(ws) = self.methods.get_mut(method) {
+            for w in ws.iter_mut() { w.hits.push_back(now); }
+        }
+        Duration::ZERO
+    }
+}
+
+struct Host {
+    /// Una coda per metodo: serializza le richieste in attesa (il Mutex tokio è FIFO).
+    queues: std::sync::Mutex<HashMap<&'static str, Arc<Mutex<()>>>>,
+    state: std::sync::Mutex<HostState>,
+}
+
+impl Host {
+    fn queue(&self, method: &'static str) -> Arc<Mutex<()>> {
+        self.queues.lock().unwrap().entry(method).or_default().clone()
+    }
+}
+
+pub struct RateLimiter {
+    hosts: std::sync::Mutex<HashMap<&'static str, Arc<Host>>>,
+    queued: std::sync::Mutex<HashMap<&'static str, usize>>,
+    total_queued: AtomicUsize,
+}
+
+static RATE_LIMITER: OnceCell<RateLimiter> = OnceCell::const_new();
+pub async fn rate_limiter() -> &'static RateLimiter {
+    RATE_LIMITER.get_or_init(|| async { RateLimiter::new() }).await
+}
+
+/// Handle usato per emettere `QUEUE_EVENT`; impostato in `setup()`.
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+pub fn set_app_handle(handle: AppHandle) {
+    let _ = APP_HANDLE.set(handle);
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        RateLimiter {
+            hosts: std::sync::Mutex::new(HashMap::new()),
+            queued: std::sync::Mutex::new(HashMap::new()),
+            total_queued: AtomicUsize::new(0),
+        }
+    }
+
+    fn host(&self, host: &'static str) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts.entry(host).or_insert_with(|| Arc::new(Host {
+            queues: std::sync::Mutex::new(HashMap::new()),
+            state: std::sync::Mutex::new(HostState::new()),
+        })).clone()
+    }
+
+    fn set_queued(&self, host: &'static str, delta: isize) {
+        let queued = {
+            let mut q = self.queued.lock().unwrap();
+            let entry = q.entry(host).or_insert(0);
+            *entry = entry.saturating_add_signed(delta);
+            *entry
+        };
+        let total = if delta >= 0 {
+            self.total_queued.fetch_add(delta as usize, Ordering::SeqCst) + delta as usize
+        } else {
+            self.total_queued.fetch_sub(delta.unsigned_abs(), Ordering::SeqCst) - delta.unsigned_abs()
+        };
+        if let Some(handle) = APP_HANDLE.get() {
+            let _ = handle.emit(QUEUE_EVENT, RateLimitStatus {
+                host: host.to_string(), queued, total_queued: total,
+            });
+        }
+    }
+
+    /// Attende uno slot libero su `host` per `method` e lo registra.
+    /// Le richieste concorrenti sullo stesso host e metodo vengono servite in ordine di arrivo.
+    pub async fn acquire(&self, host: &'static str, method: &'static str) {
+        let h = self.host(host);
+        self.set_queued(host, 1);
+        // Il guard decrementa la coda anche se il chiamante viene cancellato.
+        let _queued = QueuedGuard { limiter: self, host };
+        let queue = h.queue(method);
+        let _turn = queue.lock().await;
+        loop {
+            let wait = h.state.lock().unwrap().try_take(method);
+            if wait.is_zero() { break; }
+            eprintln!("[RateLimit] {} {} in coda per {:?}", host, method, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Legge `X-App-Rate-Limit` / `X-Method-Rate-Limit` da una risposta.
+    pub fn observe(&self, host: &'static str, method: &'static str, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(parse_limits);
+        let app_limits    = header("X-App-Rate-Limit");
+        let method_limits = header("X-Method-Rate-Limit");
+        if app_limits.is_none() && method_limits.is_none() { return; }
+
+        let h = self.host(host);
+        let mut st = h.state.lock().unwrap();
+        if let Some(limits) = app_limits {
+            apply_limits(&mut st.app, &limits);
+        }
+        if let Some(limits) = method_limits {
+            apply_limits(st.methods.entry(method).or_default(), &limits);
+        }
+    }
+
+    /// 429: blocca l'host per `retry_after` (o 1s se l'header manca).
+    pub fn penalize(&self, host: &'static str, retry_after: Option<Duration>) {
+        let until = Instant::now() + retry_after.unwrap_or(Duration::from_secs(1));
+        let h = self.host(host);
+        let mut st = h.state.lock().unwrap();
+        st.blocked_until = Some(st.blocked_until.map_or(until, |t| t.max(until)));
+    }
+}
+
+struct QueuedGuard<'a> {
+    limiter: &'a RateLimiter,
+    host: &'static str,
+}
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) { self.limiter.set_queued(self.host, -1); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(limit: usize, secs: u64, hits: &[Instant]) -> Window {
+        Window { limit, period: Duration::from_secs(secs), hits: hits.iter().copied().collect() }
+    }
+
+    #[test]
+    fn parses_rate_limit_headers() {
+        assert_eq!(parse_limits("20:1,100:120"), vec![(20, Duration::from_secs(1)), (100, Duration::from_secs(120))]);
+        assert_eq!(parse_limits(" 2000 : 60 "), vec![(2000, Duration::from_secs(60))]);
+        // Coppie malformate o nulle scartate, le altre restano
+        assert_eq!(parse_limits("abc,0:10,5:0,10:10"), vec![(10, Duration::from_secs(10))]);
+        assert!(parse_limits("").is_empty());
+    }
+
+    #[test]
+    fn window_frees_slots_when_hits_expire() {
+        let now = Instant::now();
+        let mut w = window(2, 10, &[now - Duration::from_secs(4), now - Duration::from_secs(1)]);
+        // Piena: si libera quando scade la richiesta più vecchia (tra 6s)
+        assert_eq!(w.wait(now), Duration::from_secs(6));
+        // Dopo 6s la prima esce dalla finestra
+        assert_eq!(w.wait(now + Duration::from_secs(6)), Duration::ZERO);
+        assert_eq!(w.hits.len(), 1);
+
+        let mut free = window(3, 10, &[now]);
+        assert_eq!(free.wait(now), Duration::ZERO);
+    }
+
+    #[test]
+    fn new_limits_keep_request_history() {
+        let now = Instant::now();
+        let hits = [now - Duration::from_secs(30), now - Duration::from_secs(2)];
+        let mut windows = vec![window(20, 1, &hits[1..]), window(100, 120, &hits)];
+
+        // Stessi limiti: nulla cambia
+        apply_limits(&mut windows, &[(20, Duration::from_secs(1)), (100, Duration::from_secs(120))]);
+        assert_eq!(windows[0].hits.len(), 1);
+
+        // Limiti nuovi: tutte le finestre ripartono dallo storico della più lunga
+        apply_limits(&mut windows, &[(1, Duration::from_secs(60))]);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].hits, VecDeque::from(hits.to_vec()));
+        assert_eq!(windows[0].wait(now), Duration::from_secs(58));
+
+        // Header vuoto o non valido: finestre invariate
+        apply_limits(&mut windows, &[]);
+        assert_eq!(windows[0].limit, 1);
+    }
+}
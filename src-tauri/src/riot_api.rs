@@ -0,0 +1,485 @@
+// riot_api.rs — client tipizzato per le Riot API
+//
+// Ogni endpoint è un builder `RiotRequest<T>` che conosce host, path e tipo di
+// risposta: `send()` centralizza header, retry e mappatura degli errori, così i
+// comandi Tauri non devono più costruire URL a mano né leggere `Value` grezzi.
+use std::fmt;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::offline;
+use crate::rate_limit::rate_limiter;
+
+const MAX_ATTEMPTS: u32 = 3;
+
+pub fn riot_api_key() -> &'static str {
+    env!("RIOT_API_KEY")
+}
+
+// ── Errori ────────────────────────────────────────────────────────────────────
+
+/// Errore di una chiamata Riot API.
+/// `NotFound` è un esito "normale" (summoner inesistente, nessuna partita in corso),
+/// gli altri indicano un problema di rete, di chiave o di formato.
+#[derive(Debug, Clone)]
+pub enum RiotApiError {
+    /// HTTP 404 — la risorsa non esiste (summoner, match, partita live).
+    NotFound,
+    /// HTTP 429 dopo tutti i tentativi. `retry_after` viene dall'header `Retry-After`.
+    RateLimited { retry_after: Option<Duration> },
+    /// HTTP 401/403 — chiave API scaduta o non autorizzata per l'endpoint.
+    Forbidden,
+    /// Errore di rete o timeout: nessuna risposta da Riot.
+    Transport(String),
+    /// Status HTTP inatteso (es. 503 temporaneo): Riot risponde, quindi non siamo offline.
+    Status(u16),
+    /// Risposta 200 con un corpo che non corrisponde al tipo atteso.
+    Decode(String),
+    /// Modalità offline attiva (o rete assente): nessuna richiesta inviata.
+    Offline,
+}
+
+impl fmt::Display for RiotApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiotApiError::NotFound => write!(f, "Risorsa non trovata su Riot API"),
+            RiotApiError::RateLimited { retry_after: Some(d) } =>
+                write!(f, "Rate limit Riot API superato, riprova tra {}s", d.as_secs().max(1)),
+            RiotApiError::RateLimited { retry_after: None } =>
+                write!(f, "Rate limit Riot API superato, riprova tra poco"),
+            RiotApiError::Forbidden => write!(f, "Chiave Riot API non valida o scaduta"),
+            RiotApiError::Transport(e) => write!(f, "Errore di rete Riot API: {}", e),
+            RiotApiError::Status(s) => write!(f, "Riot API ha risposto HTTP {}", s),
+            RiotApiError::Decode(e) => write!(f, "Risposta Riot API non valida: {}", e),
+            RiotApiError::Offline => write!(f, "Modalità offline: Riot API non raggiungibile"),
+        }
+    }
+}
+
+impl std::error::Error for RiotApiError {}
+
+impl RiotApiError {
+    /// Riot non raggiungibile (offline o rete): ha senso servire i dati locali.
+    /// Uno status HTTP inatteso no: il server ha risposto.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, RiotApiError::Offline | RiotApiError::Transport(_))
+    }
+}
+
+impl From<RiotApiError> for String {
+    fn from(e: RiotApiError) -> Self { e.to_string() }
+}
+
+// ── Host ──────────────────────────────────────────────────────────────────────
+
+/// Percent-encodes un path segment RFC-3986.
+pub fn encode_path(s: &str) -> String {
+    s.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+        | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+        b' '  => "%20".to_string(),
+        b     => format!("%{:02X}", b),
+    }).collect::<Vec<_>>().join("")
+}
+
+/// Mappa la stringa regione (lowercase, inviata dal frontend) all'host della piattaforma.
+/// Esempi: "euw" → "euw1", "na" → "na1", "kr" → "kr", "jp" → "jp1"
+pub fn platform_host(region: &str) -> &'static str {
+    match region.to_lowercase().as_str() {
+        "euw"  | "euw1"  => "euw1",
+        "eune" | "eun1"  => "eun1",
+        "na"   | "na1"   => "na1",
+        "br"   | "br1"   => "br1",
+        "lan"  | "la1"   => "la1",
+        "las"  | "la2"   => "la2",
+        "oce"  | "oc1"   => "oc1",
+        "tr"   | "tr1"   => "tr1",
+        "ru"             => "ru",
+        "kr"             => "kr",
+        "jp"   | "jp1"   => "jp1",
+        "sg"   | "sg2"   => "sg2",
+        "tw"   | "tw2"   => "tw2",
+        "vn"   | "vn2"   => "vn2",
+        _                => "euw1", // fallback sicuro
+    }
+}
+
+/// Mappa la stringa regione all'host di routing (cluster regionale).
+/// EUW/EUNE/TR/RU → europe | NA/BR/LAN/LAS → americas | KR/JP → asia | OCE/SG/TW/VN → sea
+pub fn routing_host(region: &str) -> &'static str {
+    match region.to_lowercase().as_str() {
+        "euw" | "euw1" | "eune" | "eun1" | "tr" | "tr1" | "ru" => "europe",
+        "na"  | "na1"  | "br"   | "br1"  | "lan" | "la1" | "las" | "la2" => "americas",
+        "kr"  | "jp"   | "jp1"  => "asia",
+        "oce" | "oc1"  | "sg"   | "sg2"  | "tw"  | "tw2" | "vn" | "vn2" => "sea",
+        _ => "europe",
+    }
+}
+
+// ── Request builder ───────────────────────────────────────────────────────────
+
+/// Richiesta GET verso un endpoint Riot che restituisce un `T`.
+/// Si costruisce con le funzioni dei moduli `account_v1`, `summoner_v4`, ecc.
+#[derive(Debug, Clone)]
+pub struct RiotRequest<T> {
+    host: &'static str,
+    /// Chiave del method rate limit (una per endpoint, non per URL).
+    method: &'static str,
+    path: String,
+    query: Vec<(&'static str, String)>,
+    timeout: Option<Duration>,
+    _response: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> RiotRequest<T> {
+    fn new(host: &'static str, method: &'static str, path: String) -> Self {
+        RiotRequest { host, method, path, query: Vec::new(), timeout: None, _response: PhantomData }
+    }
+
+    /// Aggiunge un parametro di query (`?key=value`).
+    pub fn query(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.query.push((key, value.to_string()));
+        self
+    }
+
+    /// Timeout per singolo tentativo (default: quello del `Client`).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn url(&self) -> String {
+        format!("https://{}.api.riotgames.com{}", self.host, self.path)
+    }
+
+    /// Esegue la richiesta passando dal rate limiter condiviso, con retry su 429
+    /// (rispettando `Retry-After`) ed errori di rete.
+    /// 404/401/403 non vengono ritentati: inutile insistere.
+    pub async fn send(&self, client: &Client) -> Result<T, RiotApiError> {
+        if offline::is_offline() { return Err(RiotApiError::Offline); }
+        let url = self.url();
+        let limiter = rate_limiter().await;
+        let mut last_err = RiotApiError::Transport("nessun tentativo eseguito".into());
+
+        for attempt in 0..MAX_ATTEMPTS {
+            limiter.acquire(self.host, self.method).await;
+            let mut req = client.get(&url).header("X-Riot-Token", riot_api_key());
+            if !self.query.is_empty() { req = req.query(&self.query); }
+            if let Some(t) = self.timeout { req = req.timeout(t); }
+
+            let res = match req.send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("[RiotApi] {} attempt {}: {}", self.path, attempt + 1, e);
+                    last_err = RiotApiError::Transport(e.to_string());
+                    if attempt + 1 < MAX_ATTEMPTS {
+                        tokio::time::sleep(Duration::from_millis(800)).await;
+                    }
+                    continue;
+                }
+            };
+
+            limiter.observe(self.host, self.method, res.headers());
+            let status = res.status().as_u16();
+            match status {
+                200..=299 => {
+                    // Connessione caduta a metà corpo (o corpo troncato): si ritenta come
+                    // un errore di invio, così conta anche per il rilevamento offline
+                    last_err = match res.text().await {
+                        Ok(body) => match serde_json::from_str::<T>(&body) {
+                            Ok(value) => return Ok(value),
+                            Err(e) => {
+                                eprintln!("[RiotApi] {} decode error: {} body={}",
+                                    self.path, e, &body[..body.len().min(300)]);
+                                RiotApiError::Decode(e.to_string())
+                            }
+                        },
+                        Err(e) => {
+                            eprintln!("[RiotApi] {} attempt {}: lettura corpo: {}", self.path, attempt + 1, e);
+                            RiotApiError::Transport(e.to_string())
+                        }
+                    };
+                    if attempt + 1 < MAX_ATTEMPTS {
+                        tokio::time::sleep(Duration::from_millis(800)).await;
+                    }
+                }
+                429 => {
+                    let retry_after = res.headers().get("Retry-After")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|s| s.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    // Il limiter mette in pausa tutto l'host: il prossimo acquire()
+                    // aspetta Retry-After, e così anche le altre richieste in coda.
+                    eprintln!("[RiotApi] 429 su {} (Retry-After {:?})", self.path, retry_after);
+                    limiter.penalize(self.host, retry_after);
+                    last_err = RiotApiError::RateLimited { retry_after };
+                }
+                404       => return Err(RiotApiError::NotFound),
+                401 | 403 => return Err(RiotApiError::Forbidden),
+                _         => return Err(RiotApiError::Status(status)),
+            }
+        }
+        // Nessuna risposta dopo tutti i tentativi: probabilmente siamo senza rete
+        if matches!(last_err, RiotApiError::Transport(_)) { offline::report_network_error(); }
+        Err(last_err)
+    }
+}
+
+// ── Endpoint ──────────────────────────────────────────────────────────────────
+
+pub mod account_v1 {
+    use super::*;
+
+    pub fn by_riot_id(region: &str, game_name: &str, tag_line: &str) -> RiotRequest<AccountDto> {
+        RiotRequest::new(routing_host(region), "account-v1.by-riot-id", format!(
+            "/riot/account/v1/accounts/by-riot-id/{}/{}", encode_path(game_name), encode_path(tag_line)
+        ))
+    }
+
+    pub fn by_puuid(region: &str, puuid: &str) -> RiotRequest<AccountDto> {
+        RiotRequest::new(routing_host(region), "account-v1.by-puuid", format!("/riot/account/v1/accounts/by-puuid/{}", puuid))
+    }
+}
+
+pub mod summoner_v4 {
+    use super::*;
+
+    pub fn by_puuid(region: &str, puuid: &str) -> RiotRequest<SummonerDto> {
+        RiotRequest::new(platform_host(region), "summoner-v4.by-puuid", format!("/lol/summoner/v4/summoners/by-puuid/{}", puuid))
+    }
+}
+
+pub mod league_v4 {
+    use super::*;
+
+    pub fn entries_by_puuid(region: &str, puuid: &str) -> RiotRequest<Vec<LeagueEntryDto>> {
+        RiotRequest::new(platform_host(region), "league-v4.entries-by-puuid", format!("/lol/league/v4/entries/by-puuid/{}", puuid))
+    }
+}
+
+pub mod match_v5 {
+    use super::*;
+
+    /// ID partite per puuid. Parametri opzionali via `.query()`:
+    /// `start`, `count`, `startTime`/`endTime` (epoch in secondi), `queue`.
+    pub fn ids_by_puuid(region: &str, puuid: &str) -> RiotRequest<Vec<String>> {
+        RiotRequest::new(routing_host(region), "match-v5.ids-by-puuid", format!("/lol/match/v5/matches/by-puuid/{}/ids", puuid))
+    }
+
+    pub fn by_id(region: &str, match_id: &str) -> RiotRequest<MatchDto> {
+        RiotRequest::new(routing_host(region), "match-v5.by-id", format!("/lol/match/v5/matches/{}", match_id))
+    }
+
+    pub fn timeline(region: &str, match_id: &str) -> RiotRequest<MatchTimelineDto> {
+        RiotRequest::new(routing_host(region), "match-v5.timeline", format!("/lol/match/v5/matches/{}/timeline", match_id))
+    }
+}
+
+pub mod champion_mastery_v4 {
+    use super::*;
+
+    pub fn top_by_puuid(region: &str, puuid: &str, count: u32) -> RiotRequest<Vec<ChampionMasteryDto>> {
+        RiotRequest::new(platform_host(region), "champion-mastery-v4.top", format!(
+            "/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/top", puuid
+        )).query("count", count)
+    }
+
+    /// Maestria su un solo campione (404 se mai giocato).
+    pub fn by_champion(region: &str, puuid: &str, champion_id: u32) -> RiotRequest<ChampionMasteryDto> {
+        RiotRequest::new(platform_host(region), "champion-mastery-v4.by-champion", format!(
+            "/lol/champion-mastery/v4/champion-masteries/by-puuid/{}/by-champion/{}", puuid, champion_id
+        ))
+    }
+}
+
+pub mod spectator_v5 {
+    use super::*;
+
+    pub fn active_game_by_puuid(region: &str, puuid: &str) -> RiotRequest<CurrentGameInfo> {
+        RiotRequest::new(platform_host(region), "spectator-v5.active-game", format!(
+            "/lol/spectator/v5/active-games/by-summoner/{}", puuid
+        ))
+    }
+}
+
+// ── DTO ───────────────────────────────────────────────────────────────────────
+// I campi non tipizzati finiscono in `extra` (flatten): serializzando di nuovo il
+// DTO il frontend riceve lo stesso JSON che arrivava da Riot.
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AccountDto {
+    pub puuid: String,
+    pub game_name: Option<String>,
+    pub tag_line: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SummonerDto {
+    pub puuid: String,
+    pub profile_icon_id: u64,
+    pub summoner_level: u64,
+    pub revision_date: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LeagueEntryDto {
+    pub queue_type: String,
+    pub tier: Option<String>,
+    pub rank: Option<String>,
+    pub league_points: i64,
+    pub wins: u32,
+    pub losses: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChampionMasteryDto {
+    pub puuid: String,
+    pub champion_id: i64,
+    pub champion_level: u32,
+    pub champion_points: u64,
+    pub last_play_time: i64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchDto {
+    pub metadata: MatchMetadataDto,
+    pub info: MatchInfoDto,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchMetadataDto {
+    pub match_id: String,
+    pub participants: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchInfoDto {
+    pub game_creation: u64,
+    pub game_duration: u64,
+    pub game_mode: String,
+    pub game_version: String,
+    pub queue_id: u64,
+    pub participants: Vec<ParticipantDto>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ParticipantDto {
+    pub puuid: String,
+    pub champion_id: u64,
+    pub champion_name: String,
+    pub team_id: u64,
+    pub team_position: String,
+    pub win: bool,
+    pub riot_id_game_name: String,
+    pub riot_id_tagline: String,
+    pub summoner1_id: u64,
+    pub summoner2_id: u64,
+    pub item0: u64,
+    pub item1: u64,
+    pub item2: u64,
+    pub item3: u64,
+    pub item4: u64,
+    pub item5: u64,
+    pub item6: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchTimelineDto {
+    pub metadata: MatchMetadataDto,
+    pub info: TimelineInfoDto,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimelineInfoDto {
+    pub frame_interval: u64,
+    pub frames: Vec<TimelineFrameDto>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimelineFrameDto {
+    pub timestamp: u64,
+    pub events: Vec<TimelineEventDto>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimelineEventDto {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participant_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CurrentGameInfo {
+    pub game_id: i64,
+    pub game_mode: String,
+    pub game_length: u64,
+    pub game_start_time: i64,
+    pub game_queue_config_id: u64,
+    pub banned_champions: Vec<BannedChampion>,
+    pub participants: Vec<CurrentGameParticipant>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BannedChampion {
+    pub champion_id: i64,
+    pub team_id: u64,
+    pub pick_turn: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CurrentGameParticipant {
+    /// `null` per i bot.
+    pub puuid: Option<String>,
+    pub champion_id: u64,
+    pub team_id: u64,
+    pub profile_icon_id: u64,
+    pub riot_id: String,
+    pub summoner_name: String,
+    pub spell1_id: u64,
+    pub spell2_id: u64,
+    /// `perks`, `gameCustomizationObjects`, ... restano com'erano per il frontend.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
@@ -0,0 +1,294 @@
+// rune_pages.rs — gestione delle pagine rune del giocatore
+//
+// RLP scrive solo su pagine sue: quelle create da noi (id salvati in rune_pages.json)
+// o con nome che inizia per "RLP ". Le pagine fatte a mano non vengono mai toccate
+// implicitamente: a limite raggiunto senza pagine RLP da riciclare si restituisce
+// l'errore `RUNE_PAGE_LIMIT` e il frontend chiede se sostituire la pagina attiva
+// (dopo un backup automatico).
+// Backup/restore: tutte le pagine modificabili in rune_pages_backup.json.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::lcu::{LcuClient, LcuConnection};
+
+/// Prefisso del nome delle pagine create da RLP.
+pub const PAGE_PREFIX: &str = "RLP ";
+/// Codice d'errore: limite pagine raggiunto e nessuna pagina RLP da riciclare.
+pub const PAGE_LIMIT_ERROR: &str = "RUNE_PAGE_LIMIT";
+
+const PAGES_PATH: &str = "/lol-perks/v1/pages";
+const INVENTORY_PATH: &str = "/lol-perks/v1/inventory";
+
+/// Cartella dati dell'app; impostata in `setup()`.
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Id delle pagine create da RLP (persistiti per riconoscerle anche se rinominate).
+static OWNED: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+pub fn init(app_data_dir: &Path) {
+    let _ = DATA_DIR.set(app_data_dir.to_path_buf());
+    let ids: Vec<u64> = fs::read_to_string(app_data_dir.join("rune_pages.json")).ok()
+        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        .and_then(|v| serde_json::from_value(v["page_ids"].clone()).ok())
+        .unwrap_or_default();
+    *OWNED.lock().unwrap() = ids;
+}
+
+fn data_file(name: &str) -> Result<PathBuf, String> {
+    DATA_DIR.get().map(|d| d.join(name)).ok_or_else(|| "Cartella dati non disponibile".to_string())
+}
+
+fn save_owned(ids: &[u64]) {
+    let Ok(path) = data_file("rune_pages.json") else { return };
+    if let Some(dir) = path.parent() { let _ = fs::create_dir_all(dir); }
+    if let Err(e) = fs::write(&path, json!({ "page_ids": ids }).to_string()) {
+        eprintln!("[RunePages] salvataggio id fallito: {}", e);
+    }
+}
+
+fn remember(id: u64) {
+    let mut owned = OWNED.lock().unwrap();
+    if !owned.contains(&id) { owned.push(id); }
+    save_owned(&owned);
+}
+
+fn forget(id: u64) {
+    let mut owned = OWNED.lock().unwrap();
+    owned.retain(|&o| o != id);
+    save_owned(&owned);
+}
+
+// ── Pagine LCU ───────────────────────────────────────────────────────────────
+
+fn page_id(page: &Value) -> Option<u64> {
+    page["id"].as_u64()
+}
+
+/// Pagine create dall'utente (esclude preset e pagine automatiche non cancellabili).
+fn is_editable(page: &Value) -> bool {
+    page["isDeletable"].as_bool().unwrap_or(false)
+}
+
+/// Pagina che RLP può riciclare liberamente.
+fn is_rlp_page(page: &Value, owned: &[u64]) -> bool {
+    is_editable(page) && (
+        page_id(page).is_some_and(|id| owned.contains(&id))
+        || page["name"].as_str().is_some_and(|n| n.starts_with(PAGE_PREFIX))
+    )
+}
+
+async fn list_pages(lcu: &LcuClient) -> Result<Vec<Value>, String> {
+    lcu.get(PAGES_PATH).await
+        .and_then(|v| v.as_array().cloned())
+        .ok_or_else(|| "Pagine rune non disponibili".to_string())
+}
+
+/// (pagine modificabili, massimo consentito); il massimo è `None` se l'inventario non risponde.
+async fn page_usage(lcu: &LcuClient, pages: &[Value]) -> (usize, Option<usize>) {
+    let used = pages.iter().filter(|p| is_editable(p)).count();
+    let limit = lcu.get(INVENTORY_PATH).await
+        .and_then(|inv| inv["ownedPageCount"].as_u64())
+        .map(|n| n as usize);
+    (used, limit)
+}
+
+async fn delete_page(lcu: &LcuClient, id: u64) -> Result<(), String> {
+    let status = lcu.delete(&format!("{}/{}", PAGES_PATH, id)).await?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Il client ha rifiutato la cancellazione della pagina {} (HTTP {})", id, status));
+    }
+    forget(id);
+    Ok(())
+}
+
+async fn create_page(lcu: &LcuClient, body: &Value) -> Result<u64, String> {
+    let resp = lcu.request(Method::POST, PAGES_PATH).json(body).send().await
+        .map_err(|e| format!("HTTP error rune page: {}", e))?;
+    let status = resp.status().as_u16();
+    if !(200..300).contains(&status) {
+        return Err(format!("Il client ha rifiutato la pagina rune (HTTP {})", status));
+    }
+    let page: Value = resp.json().await.unwrap_or(json!({}));
+    Ok(page_id(&page).unwrap_or(0))
+}
+
+/// Aggiorna sul posto una pagina esistente e la rende attiva: se il client rifiuta,
+/// la pagina resta com'era.
+async fn update_page(lcu: &LcuClient, id: u64, body: &Value) -> Result<(), String> {
+    let mut page = body.clone();
+    page["id"] = json!(id);
+    let status = lcu.put_status(&format!("{}/{}", PAGES_PATH, id), &page).await?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Il client ha rifiutato l'aggiornamento della pagina {} (HTTP {})", id, status));
+    }
+    let status = lcu.put_status("/lol-perks/v1/currentpage", &json!(id)).await?;
+    if !(200..300).contains(&status) {
+        eprintln!("[RunePages] pagina {} aggiornata ma non resa attiva (HTTP {})", id, status);
+    }
+    Ok(())
+}
+
+/// Crea (o ricicla) la pagina RLP `name` e la rende attiva.
+///
+/// Ordine di scelta dello slot:
+///   1. pagina RLP con lo stesso nome → aggiornata sul posto
+///   2. slot libero → nuova pagina
+///   3. pool pieno → la pagina RLP modificata meno di recente
+///   4. con `replace_active` la pagina attiva dell'utente, dopo un backup
+///   5. altrimenti errore `RUNE_PAGE_LIMIT`
+pub async fn save_page(lcu: &LcuClient, name: &str, primary_style: u32, sub_style: u32, perks: &[u32], replace_active: bool) -> Result<u64, String> {
+    // Senza l'elenco non si sa se il limite è raggiunto: meglio fermarsi qui
+    let pages = list_pages(lcu).await?;
+    let owned = OWNED.lock().unwrap().clone();
+    let mine: Vec<&Value> = pages.iter().filter(|p| is_rlp_page(p, &owned)).collect();
+    let (used, limit) = page_usage(lcu, &pages).await;
+    let full = limit.is_some_and(|l| used >= l);
+
+    let mut target = mine.iter().find(|p| p["name"].as_str() == Some(name)).copied();
+    if target.is_none() && full {
+        target = mine.iter().min_by_key(|p| p["lastModified"].as_u64().unwrap_or(0)).copied();
+    }
+    if target.is_none() && full {
+        let active = pages.iter().find(|p| is_editable(p) && p["current"].as_bool() == Some(true));
+        match active {
+            Some(page) if replace_active => {
+                backup_pages(lcu).await?;
+                eprintln!("[RunePages] sostituisco la pagina utente '{}' (backup salvato)", page["name"].as_str().unwrap_or(""));
+                target = Some(page);
+            }
+            _ => return Err(format!(
+                "{}: limite pagine rune raggiunto ({}/{}) e nessuna pagina RLP da riutilizzare. \
+                 Elimina una pagina nel client o conferma la sostituzione della pagina attiva.",
+                PAGE_LIMIT_ERROR, used, limit.unwrap_or(used),
+            )),
+        }
+    }
+    let body = json!({
+        "name": name, "primaryStyleId": primary_style, "subStyleId": sub_style,
+        "selectedPerkIds": perks, "current": true,
+    });
+    // Pagine riciclate aggiornate sul posto: un rifiuto del client non ne cancella nessuna
+    let id = match target.and_then(page_id) {
+        Some(id) => { update_page(lcu, id, &body).await?; id }
+        None => create_page(lcu, &body).await?,
+    };
+    if id != 0 { remember(id); }
+    eprintln!("[RunePages] pagina '{}' salvata (id={})", name, id);
+    Ok(id)
+}
+
+// ── Backup ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedPage {
+    pub name: String,
+    pub primary_style_id: u32,
+    pub sub_style_id: u32,
+    pub selected_perk_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuneBackup {
+    /// RFC3339
+    pub created_at: String,
+    pub pages: Vec<SavedPage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub created_at: String,
+    pub pages: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub restored: usize,
+    /// Già presenti nel client (stesso nome e stesse rune).
+    pub skipped: usize,
+    /// Non ripristinate per mancanza di slot.
+    pub no_space: usize,
+}
+
+fn saved_page(page: &Value) -> Option<SavedPage> {
+    Some(SavedPage {
+        name: page["name"].as_str()?.to_string(),
+        primary_style_id: page["primaryStyleId"].as_u64()? as u32,
+        sub_style_id: page["subStyleId"].as_u64()? as u32,
+        selected_perk_ids: serde_json::from_value(page["selectedPerkIds"].clone()).ok()?,
+    })
+}
+
+/// Salva tutte le pagine modificabili del giocatore (comprese quelle RLP).
+async fn backup_pages(lcu: &LcuClient) -> Result<BackupInfo, String> {
+    let pages = list_pages(lcu).await?;
+    let backup = RuneBackup {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        pages: pages.iter().filter(|p| is_editable(p)).filter_map(saved_page).collect(),
+    };
+    let path = data_file("rune_pages_backup.json")?;
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+    let content = serde_json::to_string_pretty(&backup).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Scrittura backup fallita: {}", e))?;
+    eprintln!("[RunePages] backup di {} pagine in {:?}", backup.pages.len(), path);
+    Ok(BackupInfo { path: path.to_string_lossy().into_owned(), created_at: backup.created_at, pages: backup.pages.len() })
+}
+
+/// Ricrea le pagine del backup che mancano nel client. Per fare spazio si
+/// eliminano solo pagine RLP; le pagine dell'utente restano intatte.
+async fn restore_pages(lcu: &LcuClient) -> Result<RestoreResult, String> {
+    let path = data_file("rune_pages_backup.json")?;
+    let content = fs::read_to_string(&path).map_err(|_| "Nessun backup delle pagine rune".to_string())?;
+    let backup: RuneBackup = serde_json::from_str(&content).map_err(|e| format!("Backup non valido: {}", e))?;
+
+    let pages = list_pages(lcu).await?;
+    let present: Vec<SavedPage> = pages.iter().filter(|p| is_editable(p)).filter_map(saved_page).collect();
+    let owned = OWNED.lock().unwrap().clone();
+    let backed_up = |p: &Value| backup.pages.iter().any(|b| p["name"].as_str() == Some(b.name.as_str()));
+    // Pagine RLP sacrificabili (quelle presenti anche nel backup restano)
+    let mut recyclable: Vec<u64> = pages.iter()
+        .filter(|p| is_rlp_page(p, &owned) && !backed_up(p))
+        .filter_map(page_id).collect();
+    let (used, limit) = page_usage(lcu, &pages).await;
+    let mut free = limit.map(|l| l.saturating_sub(used)).unwrap_or(usize::MAX);
+
+    let mut result = RestoreResult { restored: 0, skipped: 0, no_space: 0 };
+    for page in &backup.pages {
+        let exists = present.iter().any(|p| p.name == page.name && p.selected_perk_ids == page.selected_perk_ids);
+        if exists { result.skipped += 1; continue; }
+        if free == 0 {
+            match recyclable.pop() {
+                Some(id) => { delete_page(lcu, id).await?; free += 1; }
+                None => { result.no_space += 1; continue; }
+            }
+        }
+        create_page(lcu, &json!({
+            "name": page.name, "primaryStyleId": page.primary_style_id,
+            "subStyleId": page.sub_style_id, "selectedPerkIds": page.selected_perk_ids,
+        })).await?;
+        free -= 1;
+        result.restored += 1;
+    }
+    eprintln!("[RunePages] restore: {:?}", result);
+    Ok(result)
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn backup_rune_pages(lcu: State<'_, LcuConnection>) -> Result<BackupInfo, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
+    backup_pages(&lcu).await
+}
+
+#[tauri::command]
+pub async fn restore_rune_pages(lcu: State<'_, LcuConnection>) -> Result<RestoreResult, String> {
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
+    restore_pages(&lcu).await
+}
@@ -0,0 +1,247 @@
+// rune_validator.rs — validazione di una RunePageData prima di inviarla all'LCU
+//
+// L'LCU rifiuta una pagina non valida con un HTTP 400 senza spiegazioni. Qui la
+// pagina si controlla sull'albero runesReforged della patch corrente:
+//   • path primario e secondario esistenti e diversi
+//   • primario: una runa per riga (keystone + 3), tutte del path primario
+//   • secondario: 2 rune del path secondario, da due righe minori diverse
+//   • shard: una per riga, tra quelle valide per quella riga
+// Le rune mancanti o fuori posto vengono sostituite (non con 0, che l'LCU scarta)
+// da una lista statica di preferenze: DDragon non ha tassi di scelta, quindi è una
+// lista scritta a mano che può invecchiare con le patch. Per le righe dove nessuna
+// preferenza esiste più si prende la prima runa della riga. Ogni correzione è riportata.
+use crate::champ_select::RunePageData;
+use crate::static_data::{RunePath, StaticData};
+
+/// Shard per riga (offesa, flex, difesa) — non sono in runesReforged.
+/// Dalla patch 14.1: 5008 Adaptive, 5005 Attack Speed, 5007 Ability Haste,
+/// 5010 Move Speed, 5001 Health Scaling, 5011 Health, 5013 Tenacity/Slow Resist.
+const STAT_SHARDS: [[u32; 3]; 3] = [
+    [5008, 5005, 5007],
+    [5008, 5010, 5001],
+    [5011, 5013, 5001],
+];
+/// Shard di ripiego per riga.
+const PREFERRED_SHARDS: [u32; 3] = [5008, 5008, 5011];
+
+/// Rune di ripiego in ordine di preferenza (scelte popolari quando la lista è stata
+/// scritta, non aggiornate dalla patch). Per ogni riga si prende la prima che esiste
+/// in quella riga della patch corrente, altrimenti la prima della riga.
+const PREFERRED_RUNES: &[u32] = &[
+    // Precision: Conqueror, Triumph, Legend: Alacrity, Coup de Grace
+    8010, 9111, 9104, 8014,
+    // Domination: Electrocute, Taste of Blood, Grisly Mementos / Eyeball Collection, Treasure Hunter
+    8112, 8139, 8140, 8138, 8135,
+    // Sorcery: Summon Aery, Manaflow Band, Transcendence, Scorch
+    8214, 8226, 8210, 8237,
+    // Resolve: Grasp of the Undying, Demolish, Second Wind, Overgrowth
+    8437, 8446, 8444, 8451,
+    // Inspiration: Glacial Augment, Magical Footwear, Biscuit Delivery, Cosmic Insight
+    8351, 8304, 8345, 8347,
+];
+
+fn filler_rune(path: &RunePath, slot: usize) -> Option<u32> {
+    let row = path.slots.get(slot)?;
+    PREFERRED_RUNES.iter().copied().find(|id| row.contains(id)).or_else(|| row.first().copied())
+}
+
+fn rune_name(data: &StaticData, id: u32) -> String {
+    data.runes.get(id).map(|r| r.name.clone()).unwrap_or_else(|| id.to_string())
+}
+
+/// Corregge `page` in una pagina accettata dall'LCU. Restituisce le correzioni
+/// applicate (vuoto se era già valida); errore solo se i path non sono utilizzabili.
+pub fn validate(page: &mut RunePageData, data: &StaticData) -> Result<Vec<String>, String> {
+    let mut fixes = Vec::new();
+
+    let primary = data.rune_paths.get(page.primary_path_id)
+        .ok_or_else(|| format!("Path primario {} inesistente nella patch {}", page.primary_path_id, data.version))?;
+    let secondary = match data.rune_paths.get(page.secondary_path_id) {
+        Some(p) if p.id != primary.id => p,
+        _ => {
+            // Path secondario assente o uguale al primario: si ricava dalle rune, altrimenti il primo diverso
+            let from_runes = page.secondary_rune_ids.iter()
+                .filter_map(|&id| data.runes.get(id))
+                .find(|r| r.path != primary.id && r.slot > 0)
+                .and_then(|r| data.rune_paths.get(r.path));
+            let fallback = from_runes
+                .or_else(|| PREFERRED_RUNES.iter().filter_map(|&id| data.runes.get(id))
+                    .find(|r| r.path != primary.id).and_then(|r| data.rune_paths.get(r.path)))
+                .ok_or("Nessun path secondario disponibile")?;
+            fixes.push(format!("path secondario {} non valido → {}", page.secondary_path_id, fallback.name));
+            fallback
+        }
+    };
+
+    // ── Primario: una runa per riga ──────────────────────────────────────────
+    let mut rows: Vec<Option<u32>> = vec![None; primary.slots.len()];
+    for &id in &page.primary_rune_ids {
+        match data.runes.get(id) {
+            Some(r) if r.path == primary.id && rows[r.slot].is_none() => rows[r.slot] = Some(id),
+            Some(r) if r.path == primary.id => fixes.push(format!("{} duplicata sulla riga {}", r.name, r.slot)),
+            _ if id != 0 => fixes.push(format!("{} non appartiene a {}", rune_name(data, id), primary.name)),
+            _ => {}
+        }
+    }
+    let mut primary_ids = Vec::with_capacity(rows.len());
+    for (slot, picked) in rows.into_iter().enumerate() {
+        let id = match picked {
+            Some(id) => id,
+            None => {
+                let id = filler_rune(primary, slot).ok_or("Albero rune incompleto")?;
+                fixes.push(format!("riga {} di {} vuota → {}", slot, primary.name, rune_name(data, id)));
+                id
+            }
+        };
+        primary_ids.push(id);
+    }
+
+    // ── Secondario: 2 rune da righe minori diverse ───────────────────────────
+    let mut secondary_ids: Vec<(usize, u32)> = Vec::new();
+    for &id in &page.secondary_rune_ids {
+        match data.runes.get(id) {
+            Some(r) if r.path == secondary.id && r.slot > 0 => {
+                if secondary_ids.len() == 2 || secondary_ids.iter().any(|&(s, _)| s == r.slot) {
+                    fixes.push(format!("{} scartata: serve una runa per riga nel secondario", r.name));
+                } else {
+                    secondary_ids.push((r.slot, id));
+                }
+            }
+            _ if id != 0 => fixes.push(format!("{} non valida come secondaria di {}", rune_name(data, id), secondary.name)),
+            _ => {}
+        }
+    }
+    for slot in 1..secondary.slots.len() {
+        if secondary_ids.len() >= 2 { break; }
+        if secondary_ids.iter().any(|&(s, _)| s == slot) { continue; }
+        if let Some(id) = filler_rune(secondary, slot) {
+            fixes.push(format!("secondaria mancante → {}", rune_name(data, id)));
+            secondary_ids.push((slot, id));
+        }
+    }
+    // L'LCU vuole le secondarie nell'ordine delle righe
+    secondary_ids.sort_by_key(|&(slot, _)| slot);
+
+    // ── Shard ────────────────────────────────────────────────────────────────
+    let mut stat_ids = Vec::with_capacity(3);
+    for (row, valid) in STAT_SHARDS.iter().enumerate() {
+        match page.stat_mod_ids.get(row) {
+            Some(id) if valid.contains(id) => stat_ids.push(*id),
+            other => {
+                if let Some(id) = other.filter(|&&id| id != 0) {
+                    fixes.push(format!("shard {} non valida per la riga {}", id, row));
+                }
+                stat_ids.push(PREFERRED_SHARDS[row]);
+            }
+        }
+    }
+
+    page.primary_path_id = primary.id;
+    page.primary_path_name = primary.key.clone();
+    page.primary_rune_ids = primary_ids;
+    page.secondary_path_id = secondary.id;
+    page.secondary_path_name = secondary.key.clone();
+    page.secondary_rune_ids = secondary_ids.into_iter().map(|(_, id)| id).collect();
+    page.stat_mod_ids = stat_ids;
+    Ok(fixes)
+}
+
+impl RunePageData {
+    /// `selectedPerkIds` nell'ordine dell'LCU: 4 primarie, 2 secondarie, 3 shard.
+    pub fn perk_ids(&self) -> Vec<u32> {
+        let mut perks = Vec::with_capacity(9);
+        perks.extend_from_slice(&self.primary_rune_ids);
+        perks.extend_from_slice(&self.secondary_rune_ids);
+        perks.extend_from_slice(&self.stat_mod_ids);
+        perks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(id: u32, name: &str, slots: &[&[u32]]) -> RunePath {
+        RunePath { id, key: name.into(), name: name.into(), slots: slots.iter().map(|r| r.to_vec()).collect() }
+    }
+
+    fn data() -> StaticData {
+        StaticData::for_tests(Vec::new(), Vec::new(), vec![
+            path(8000, "Precision", &[&[8005, 8008, 8021, 8010], &[9101, 9111, 8009], &[9104, 9105, 9103], &[8014, 8017, 8299]]),
+            path(8100, "Domination", &[&[8112, 8128, 9923], &[8126, 8139, 8143], &[8136, 8120, 8138], &[8135, 8105, 8106]]),
+        ], Vec::new())
+    }
+
+    fn page(primary: u32, primary_ids: &[u32], secondary: u32, secondary_ids: &[u32], shards: &[u32]) -> RunePageData {
+        RunePageData {
+            champion_name: "Test".into(),
+            primary_path_id: primary,
+            primary_path_name: String::new(),
+            primary_rune_ids: primary_ids.to_vec(),
+            secondary_path_id: secondary,
+            secondary_path_name: String::new(),
+            secondary_rune_ids: secondary_ids.to_vec(),
+            stat_mod_ids: shards.to_vec(),
+        }
+    }
+
+    #[test]
+    fn valid_page_is_untouched() {
+        let mut p = page(8000, &[8010, 9111, 9104, 8014], 8100, &[8139, 8135], &[5008, 5010, 5011]);
+        assert!(validate(&mut p, &data()).unwrap().is_empty());
+        assert_eq!(p.perk_ids(), vec![8010, 9111, 9104, 8014, 8139, 8135, 5008, 5010, 5011]);
+        assert_eq!((p.primary_path_name.as_str(), p.secondary_path_name.as_str()), ("Precision", "Domination"));
+    }
+
+    #[test]
+    fn primary_runes_from_wrong_path_or_row_are_replaced() {
+        // 8112 è di Domination; 8005 è un secondo keystone
+        let mut p = page(8000, &[8112, 8008, 8005, 9111, 9104, 8014], 8100, &[8139, 8135], &[5008, 5008, 5011]);
+        let fixes = validate(&mut p, &data()).unwrap();
+        assert_eq!(p.primary_rune_ids, vec![8008, 9111, 9104, 8014]);
+        assert_eq!(fixes, vec!["Rune 8112 non appartiene a Precision", "Rune 8005 duplicata sulla riga 0"]);
+    }
+
+    #[test]
+    fn secondaries_need_two_different_rows() {
+        // 8126 e 8139 sono entrambe sulla riga 1; il keystone 8112 non è ammesso come secondaria
+        let mut p = page(8000, &[8010, 9111, 9104, 8014], 8100, &[8112, 8126, 8139], &[5008, 5008, 5011]);
+        let fixes = validate(&mut p, &data()).unwrap();
+        assert_eq!(p.secondary_rune_ids, vec![8126, 8138]);
+        assert_eq!(fixes, vec![
+            "Rune 8112 non valida come secondaria di Domination",
+            "Rune 8139 scartata: serve una runa per riga nel secondario",
+            "secondaria mancante → Rune 8138",
+        ]);
+
+        // Ordine delle righe, non quello della build
+        let mut p = page(8000, &[8010, 9111, 9104, 8014], 8100, &[8135, 8126], &[5008, 5008, 5011]);
+        assert!(validate(&mut p, &data()).unwrap().is_empty());
+        assert_eq!(p.secondary_rune_ids, vec![8126, 8135]);
+    }
+
+    #[test]
+    fn shards_are_checked_per_row() {
+        // 5011 (Health) esiste solo sulla riga di difesa
+        let mut p = page(8000, &[8010, 9111, 9104, 8014], 8100, &[8139, 8135], &[5011, 5011, 5011]);
+        let fixes = validate(&mut p, &data()).unwrap();
+        assert_eq!(p.stat_mod_ids, vec![5008, 5008, 5011]);
+        assert_eq!(fixes, vec!["shard 5011 non valida per la riga 0", "shard 5011 non valida per la riga 1"]);
+    }
+
+    #[test]
+    fn missing_slots_are_filled() {
+        let mut p = page(8000, &[], 8100, &[], &[]);
+        let fixes = validate(&mut p, &data()).unwrap();
+        // Preferenze dove esistono nella riga, altrimenti la prima della riga
+        assert_eq!(p.perk_ids(), vec![8010, 9111, 9104, 8014, 8139, 8138, 5008, 5008, 5011]);
+        assert_eq!(fixes.len(), 6);
+
+        let mut p = page(8000, &[8010, 9111, 9104, 8014], 8000, &[8126, 8135], &[5008, 5008, 5011]);
+        let fixes = validate(&mut p, &data()).unwrap();
+        assert_eq!(p.secondary_path_id, 8100);
+        assert_eq!(fixes, vec!["path secondario 8000 non valido → Domination"]);
+
+        assert!(validate(&mut page(8300, &[], 8100, &[], &[]), &data()).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+// scouting.rs — scouting dei compagni in champ select
+//
+// Dai `myTeam` della sessione LCU (puuid, o summonerId risolto via LCU) si ricava per
+// ogni compagno: rank (fetch_ranked_entry), win rate e pool campioni delle ultime
+// partite, ruoli più giocati e maestria sul campione in hover/lock.
+// I compagni si scoutano in parallelo: tutte le chiamate passano da `RiotRequest::send`,
+// quindi dal rate limiter condiviso, che mette in coda le richieste oltre i limiti
+// della chiave invece di prendere 429. Per restare leggeri si guardano solo le ultime
+// SCOUT_GAMES partite, e i dettagli già nello store non si richiedono.
+// Flag:
+//   • first time → nessuna partita recente sul campione e maestria sotto soglia;
+//   • autofill   → la lane assegnata non è tra i ruoli più giocati.
+use std::collections::HashMap;
+
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::lcu::{LcuClient, LcuConnection};
+use crate::riot_api::{champion_mastery_v4, RiotApiError};
+use crate::static_data::{static_data, StaticData};
+
+/// Partite recenti analizzate per compagno.
+const SCOUT_GAMES: u32 = 10;
+/// Campioni mostrati nel pool.
+const POOL_SIZE: usize = 3;
+/// Ruoli considerati "suoi" per il flag autofill.
+const MAIN_ROLES: usize = 2;
+/// Partite con ruolo noto sotto cui l'autofill non si valuta.
+const MIN_ROLE_GAMES: u32 = 5;
+/// Sotto questi punti maestria (e senza partite recenti) il pick è un first time.
+const FIRST_TIME_POINTS: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolChampion {
+    pub champion_id: u32,
+    /// Id DDragon, per le immagini.
+    pub champion: String,
+    pub champion_name: String,
+    pub games: u32,
+    pub wins: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TeammateScout {
+    pub cell_id: i64,
+    /// Lane assegnata (vuota fuori dalle code con ruoli).
+    pub position: String,
+    /// Riot ID se il client lo espone (vuoto con i nomi nascosti in champ select).
+    pub name: String,
+    /// Nessun puuid disponibile (bot o giocatore non risolvibile).
+    pub hidden: bool,
+    pub tier: String,
+    pub rank: String,
+    pub lp: i64,
+    pub games: u32,
+    pub wins: u32,
+    /// Win rate delle ultime partite (0-1).
+    pub win_rate: Option<f64>,
+    pub pool: Vec<PoolChampion>,
+    /// Ruoli più giocati, dal più frequente.
+    pub main_roles: Vec<String>,
+    /// Campione in hover o lock (0 se nessuno).
+    pub champion_id: u32,
+    pub champion_name: String,
+    pub mastery_level: Option<u32>,
+    pub mastery_points: Option<u64>,
+    /// Partite recenti sul campione in hover.
+    pub champion_games: u32,
+    pub first_time: bool,
+    pub autofill: bool,
+}
+
+// ── Recupero dati ─────────────────────────────────────────────────────────────
+
+/// puuid del compagno: dalla sessione o, se manca, dal summonerId via LCU.
+async fn resolve_puuid(lcu: &LcuClient, player: &Value) -> Option<String> {
+    if let Some(p) = player["puuid"].as_str().filter(|p| !p.is_empty()) {
+        return Some(p.to_string());
+    }
+    let id = player["summonerId"].as_u64().filter(|&id| id != 0)?;
+    let summoner = lcu.get(&format!("/lol-summoner/v1/summoners/{}", id)).await?;
+    summoner["puuid"].as_str().filter(|p| !p.is_empty()).map(String::from)
+}
+
+/// Ultime partite del giocatore (dettagli dallo store quando ci sono).
+async fn recent_matches(puuid: &str, region: &str, client: &Client) -> Vec<Value> {
+    let ids = match crate::fetch_match_ids_since(puuid, 0, SCOUT_GAMES, None, None, region, client).await {
+        Ok(ids) => ids,
+        Err(e) => { eprintln!("[Scouting] match id non disponibili: {}", e); return Vec::new(); }
+    };
+    let details = ids.iter().map(|id| crate::fetch_match_detail(id, region, client));
+    futures_util::future::join_all(details).await.into_iter().flatten()
+        .filter(|m| m["info"]["queueId"].as_u64().unwrap_or(0) != 0)
+        .collect()
+}
+
+/// (livello, punti) sul campione; `None` se mai giocato o non recuperabile.
+async fn champion_mastery(puuid: &str, champion_id: u32, region: &str, client: &Client) -> Option<(u32, u64)> {
+    if champion_id == 0 { return None; }
+    match champion_mastery_v4::by_champion(region, puuid, champion_id).send(client).await {
+        Ok(m) => Some((m.champion_level, m.champion_points)),
+        Err(RiotApiError::NotFound) => Some((0, 0)),
+        Err(e) => { eprintln!("[Scouting] maestria non disponibile: {}", e); None }
+    }
+}
+
+// ── Analisi ──────────────────────────────────────────────────────────────────
+
+/// Statistiche delle partite recenti: win rate, pool, ruoli, partite sul campione.
+fn summarize(scout: &mut TeammateScout, puuid: &str, matches: &[Value], data: &StaticData) {
+    let mut champs: HashMap<u32, (u32, u32)> = HashMap::new();
+    let mut roles: HashMap<String, u32> = HashMap::new();
+    for m in matches {
+        let Some(me) = m.pointer("/info/participants").and_then(Value::as_array)
+            .and_then(|ps| ps.iter().find(|p| p["puuid"] == puuid)) else { continue };
+        let win = me["win"].as_bool() == Some(true);
+        scout.games += 1;
+        if win { scout.wins += 1; }
+        if let Some(id) = me["championId"].as_u64() {
+            let entry = champs.entry(id as u32).or_default();
+            entry.0 += 1;
+            if win { entry.1 += 1; }
+        }
+        if let Some(role) = me["teamPosition"].as_str().filter(|r| !r.is_empty()) {
+            *roles.entry(role.to_uppercase()).or_default() += 1;
+        }
+    }
+    scout.win_rate = (scout.games > 0).then(|| scout.wins as f64 / scout.games as f64);
+    scout.champion_games = champs.get(&scout.champion_id).map(|c| c.0).unwrap_or(0);
+
+    let mut pool: Vec<(u32, (u32, u32))> = champs.into_iter().collect();
+    pool.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(b.1.1.cmp(&a.1.1)));
+    scout.pool = pool.into_iter().take(POOL_SIZE).filter_map(|(id, (games, wins))| {
+        let c = data.champions.get(id)?;
+        Some(PoolChampion { champion_id: id, champion: c.id.clone(), champion_name: c.name.clone(), games, wins })
+    }).collect();
+
+    let role_games: u32 = roles.values().sum();
+    let mut roles: Vec<(String, u32)> = roles.into_iter().collect();
+    roles.sort_by_key(|r| std::cmp::Reverse(r.1));
+    scout.main_roles = roles.into_iter().take(MAIN_ROLES).map(|(r, _)| r).collect();
+    scout.autofill = !scout.position.is_empty()
+        && role_games >= MIN_ROLE_GAMES
+        && !scout.main_roles.contains(&scout.position);
+}
+
+async fn scout(lcu: &LcuClient, player: &Value, region: &str, client: &Client, data: &StaticData) -> TeammateScout {
+    let locked = player["championId"].as_u64().unwrap_or(0) as u32;
+    let hover = player["championPickIntent"].as_u64().unwrap_or(0) as u32;
+    let champion_id = if locked != 0 { locked } else { hover };
+    let name = match (player["gameName"].as_str(), player["tagLine"].as_str()) {
+        (Some(n), Some(t)) if !n.is_empty() => format!("{}#{}", n, t),
+        _ => String::new(),
+    };
+    let mut scout = TeammateScout {
+        cell_id: player["cellId"].as_i64().unwrap_or(-1),
+        position: player["assignedPosition"].as_str().unwrap_or("").to_uppercase(),
+        name,
+        champion_id,
+        champion_name: data.champions.get(champion_id).map(|c| c.name.clone()).unwrap_or_default(),
+        ..Default::default()
+    };
+    let Some(puuid) = resolve_puuid(lcu, player).await else {
+        scout.hidden = true;
+        return scout;
+    };
+
+    let (rank, matches, mastery) = tokio::join!(
+        crate::fetch_ranked_entry(puuid.clone(), region.to_string(), client.clone()),
+        recent_matches(&puuid, region, client),
+        champion_mastery(&puuid, champion_id, region, client),
+    );
+    (scout.tier, scout.rank, scout.lp) = rank;
+    summarize(&mut scout, &puuid, &matches, data);
+    if let Some((level, points)) = mastery {
+        scout.mastery_level = Some(level);
+        scout.mastery_points = Some(points);
+        scout.first_time = scout.champion_games == 0 && points < FIRST_TIME_POINTS;
+    }
+    scout
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_teammate_scouting(lcu: State<'_, LcuConnection>, region: String) -> Result<Vec<TeammateScout>, String> {
+    if crate::offline::is_offline() {
+        return Err(RiotApiError::Offline.to_string());
+    }
+    let lcu = lcu.client().ok_or("CLIENT_CLOSED")?;
+    let session: Value = lcu.get("/lol-champ-select/v1/session").await
+        .filter(|s| s.get("errorCode").is_none())
+        .ok_or("Nessuna champion select attiva")?;
+    let data = static_data().await?;
+    let client = Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(std::time::Duration::from_secs(15))
+        .build().map_err(|e| e.to_string())?;
+
+    let my_cell = session["localPlayerCellId"].as_i64().unwrap_or(-1);
+    let teammates: Vec<&Value> = session["myTeam"].as_array().into_iter().flatten()
+        .filter(|p| p["cellId"].as_i64() != Some(my_cell))
+        .collect();
+    let jobs = teammates.iter().map(|p| scout(&lcu, p, &region, &client, &data));
+    let scouts = futures_util::future::join_all(jobs).await;
+    eprintln!("[Scouting] {} compagni ({} first time, {} autofill)", scouts.len(),
+        scouts.iter().filter(|s| s.first_time).count(), scouts.iter().filter(|s| s.autofill).count());
+    Ok(scouts)
+}
@@ -0,0 +1,227 @@
+// seasons.rs — calendario stagioni/split e filtri temporali sui match
+//
+// Il calendario è in data/seasons.json (incluso nel binario). Un file `seasons.json`
+// nella app data dir, se presente e valido, lo sostituisce: così una nuova stagione
+// non richiede un rilascio. Le date sono "YYYY-MM-DD" (00:00 UTC); la fine di ogni
+// stagione/split è l'inizio della successiva.
+//
+// I match vengono sempre salvati tutti: il filtro (stagione, split, patch, date)
+// si applica solo in lettura, con `MatchRange`.
+use std::path::Path;
+use std::sync::OnceLock;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const BUNDLED: &str = include_str!("../data/seasons.json");
+
+/// Nome del file di override nella app data dir.
+pub const OVERRIDE_FILE: &str = "seasons.json";
+
+// ── Formato file ─────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct CalendarFile {
+    current: String,
+    seasons: Vec<PeriodFile>,
+}
+
+#[derive(Deserialize)]
+struct PeriodFile {
+    id: String,
+    name: String,
+    start: String,
+    #[serde(default)]
+    splits: Vec<PeriodFile>,
+}
+
+fn parse_date_ms(s: &str) -> Result<u64, String> {
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("data non valida '{}': {}", s, e))?;
+    let ms = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+    Ok(ms.max(0) as u64)
+}
+
+// ── Calendario ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Split {
+    pub id: String,
+    pub name: String,
+    pub start_ms: u64,
+    /// `None` = split in corso.
+    pub end_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Season {
+    pub id: String,
+    pub name: String,
+    pub start_ms: u64,
+    /// `None` = stagione in corso.
+    pub end_ms: Option<u64>,
+    pub splits: Vec<Split>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Calendar {
+    pub current: String,
+    pub seasons: Vec<Season>,
+}
+
+impl Calendar {
+    fn parse(raw: &str) -> Result<Self, String> {
+        let file: CalendarFile = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+
+        let mut seasons = Vec::with_capacity(file.seasons.len());
+        for s in &file.seasons {
+            let mut splits = Vec::with_capacity(s.splits.len());
+            for sp in &s.splits {
+                splits.push(Split { id: sp.id.clone(), name: sp.name.clone(), start_ms: parse_date_ms(&sp.start)?, end_ms: None });
+            }
+            splits.sort_by_key(|sp| sp.start_ms);
+            seasons.push(Season { id: s.id.clone(), name: s.name.clone(), start_ms: parse_date_ms(&s.start)?, end_ms: None, splits });
+        }
+        seasons.sort_by_key(|s| s.start_ms);
+
+        // Fine = inizio del periodo successivo
+        let starts: Vec<u64> = seasons.iter().map(|s| s.start_ms).collect();
+        for (i, season) in seasons.iter_mut().enumerate() {
+            season.end_ms = starts.get(i + 1).copied();
+            let split_starts: Vec<u64> = season.splits.iter().map(|sp| sp.start_ms).collect();
+            let season_end = season.end_ms;
+            for (j, split) in season.splits.iter_mut().enumerate() {
+                split.end_ms = split_starts.get(j + 1).copied().or(season_end);
+            }
+        }
+
+        if !seasons.iter().any(|s| s.id == file.current) {
+            return Err(format!("stagione corrente '{}' non presente nel calendario", file.current));
+        }
+        Ok(Calendar { current: file.current, seasons })
+    }
+
+    pub fn current_season(&self) -> &Season {
+        self.seasons.iter().find(|s| s.id == self.current)
+            .expect("stagione corrente validata in parse()")
+    }
+
+    /// Traduce il filtro del frontend in un intervallo concreto.
+    /// Senza stagione né date = stagione corrente; `"all"` = nessun limite.
+    pub fn resolve(&self, filter: &SeasonFilter) -> Result<MatchRange, String> {
+        let (mut since_ms, mut until_ms) = match filter.season.as_deref() {
+            Some("all") => (None, None),
+            Some(id) => {
+                if let Some(s) = self.seasons.iter().find(|s| s.id == id) {
+                    (Some(s.start_ms), s.end_ms)
+                } else if let Some(sp) = self.seasons.iter().flat_map(|s| &s.splits).find(|sp| sp.id == id) {
+                    (Some(sp.start_ms), sp.end_ms)
+                } else {
+                    return Err(format!("Stagione sconosciuta: {}", id));
+                }
+            }
+            None if filter.from_ms.is_some() || filter.to_ms.is_some() => (None, None),
+            None => {
+                let s = self.current_season();
+                (Some(s.start_ms), s.end_ms)
+            }
+        };
+        // Le date esplicite restringono (o sostituiscono) i limiti della stagione
+        if let Some(from) = filter.from_ms { since_ms = Some(since_ms.map_or(from, |s| s.max(from))); }
+        if let Some(to)   = filter.to_ms   { until_ms = Some(until_ms.map_or(to, |u| u.min(to))); }
+
+        let patch = filter.patch.as_deref().map(str::trim).filter(|p| !p.is_empty()).map(String::from);
+        Ok(MatchRange { since_ms, until_ms, patch })
+    }
+}
+
+static CALENDAR: OnceLock<Calendar> = OnceLock::new();
+
+fn bundled() -> Calendar {
+    Calendar::parse(BUNDLED).expect("data/seasons.json non valido")
+}
+
+/// Carica il calendario: override nella app data dir se valido, altrimenti quello incluso.
+pub fn init(app_data_dir: &Path) {
+    let path = app_data_dir.join(OVERRIDE_FILE);
+    let calendar = match std::fs::read_to_string(&path) {
+        Ok(raw) => match Calendar::parse(&raw) {
+            Ok(c) => {
+                eprintln!("[Seasons] override caricato: {:?}", path);
+                c
+            }
+            Err(e) => {
+                eprintln!("[Seasons] ❌ override non valido ({:?}): {} — uso il calendario incluso", path, e);
+                bundled()
+            }
+        },
+        Err(_) => bundled(),
+    };
+    let _ = CALENDAR.set(calendar);
+}
+
+pub fn calendar() -> &'static Calendar {
+    CALENDAR.get_or_init(bundled)
+}
+
+// ── Filtri ───────────────────────────────────────────────────────────────────
+
+/// Filtro opzionale accettato dai comandi (`{ season, patch, fromMs, toMs }`).
+/// `season` può essere l'id di una stagione ("2025"), di uno split ("2025-S2") o "all".
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeasonFilter {
+    pub season: Option<String>,
+    /// Prefisso di `gameVersion`, es. "16.4".
+    pub patch: Option<String>,
+    pub from_ms: Option<u64>,
+    pub to_ms: Option<u64>,
+}
+
+impl SeasonFilter {
+    /// Nessun filtro esplicito = stagione corrente (l'unico caso che usa le cache profilo).
+    pub fn is_default(&self) -> bool {
+        self.season.is_none() && self.patch.is_none() && self.from_ms.is_none() && self.to_ms.is_none()
+    }
+}
+
+/// Intervallo risolto: `gameCreation` in [since_ms, until_ms) e patch opzionale.
+#[derive(Debug, Clone, Default)]
+pub struct MatchRange {
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+    pub patch: Option<String>,
+}
+
+impl MatchRange {
+    /// L'intervallo arriva fino ad oggi (quindi ha senso sincronizzare i match nuovi).
+    pub fn is_open(&self) -> bool {
+        self.until_ms.is_none()
+    }
+
+    pub fn contains(&self, m: &Value) -> bool {
+        let gc = m["info"]["gameCreation"].as_u64()
+            .or_else(|| m["gameCreation"].as_u64())
+            .unwrap_or(0);
+        if self.since_ms.is_some_and(|s| gc < s) { return false; }
+        if self.until_ms.is_some_and(|u| gc >= u) { return false; }
+        match &self.patch {
+            Some(p) => m["info"]["gameVersion"].as_str()
+                .or_else(|| m["gameVersion"].as_str())
+                .is_some_and(|v| v == p || v.starts_with(&format!("{}.", p))),
+            None => true,
+        }
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Calendario per i selettori stagione/split del frontend.
+#[tauri::command]
+pub async fn get_season_calendar() -> Result<Calendar, String> {
+    Ok(calendar().clone())
+}
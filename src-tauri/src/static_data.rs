@@ -0,0 +1,420 @@
+// static_data.rs — dati statici Data Dragon (campioni, item, rune, summoner spell)
+//
+// La versione corrente viene letta da versions.json (ricontrollata ogni 6 ore);
+// i file di ogni patch si scaricano una volta sola e restano su disco in
+// <app data>/ddragon/<versione>/. Senza rete si usa l'ultima patch già scaricata.
+// Tutti i lookup accettano sia l'id numerico sia il nome o la chiave DDragon
+// ("Flash" / "SummonerFlash", "Kai'Sa" / "Kaisa"), normalizzati. Se un nome è condiviso
+// (Flash e la Flash di Arena, varianti di item per modalità) vince la voce standard,
+// poi l'id più basso: il risultato non dipende dall'ordine di caricamento.
+// In modalità offline versions.json non viene nemmeno richiesto.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tokio::sync::{OnceCell, RwLock};
+
+const DDRAGON_URL: &str = "https://ddragon.leagueoflegends.com";
+const LOCALE: &str = "en_US";
+const VERSION_CHECK_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Patch non verificata online: versions.json si ritenta al massimo ogni RETRY_BACKOFF.
+const RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Cartella per i file per-patch; impostata in `setup()`.
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+pub fn init(app_data_dir: &Path) {
+    let _ = CACHE_DIR.set(app_data_dir.join("ddragon"));
+}
+
+/// Modalità offline (impostata da offline.rs): si usa solo quanto già su disco.
+pub static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+// ── Tipi ─────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct Champion {
+    /// Chiave numerica (championId delle API Riot / LCU).
+    pub key: u32,
+    /// Id DDragon, usato anche per le immagini ("MonkeyKing").
+    pub id: String,
+    pub name: String,
+    /// Classi DDragon ("Fighter", "Tank", "Mage", "Assassin", "Marksman", "Support").
+    pub tags: Vec<String>,
+    /// Valutazioni DDragon 0-10 (`info`): danno fisico, magico, resistenza.
+    pub attack: u8,
+    pub magic: u8,
+    pub defense: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub id: u32,
+    pub name: String,
+    // Costo e tipo servono solo alle build locali (local_builds.rs, nel binario)
+    /// Costo totale.
+    #[allow(dead_code)]
+    pub gold: u32,
+    /// Item finale: non entra nella ricetta di nessun altro.
+    #[allow(dead_code)]
+    pub completed: bool,
+    #[allow(dead_code)]
+    pub boots: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rune {
+    pub id: u32,
+    pub key: String,
+    pub name: String,
+    /// Id del path (8000 Precision, 8100 Domination, ...).
+    pub path: u32,
+    /// Riga nel path: 0 = keystone, 1..=3 = righe minori.
+    pub slot: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunePath {
+    pub id: u32,
+    pub key: String,
+    pub name: String,
+    /// Id delle rune per riga, nell'ordine di runesReforged.
+    pub slots: Vec<Vec<u32>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SummonerSpell {
+    /// Id numerico (spell1Id / spell2Id).
+    pub id: u32,
+    /// Chiave DDragon, usata per le immagini ("SummonerFlash").
+    pub key: String,
+    pub name: String,
+    /// gameMode in cui è selezionabile ("CLASSIC", "ARAM", ...).
+    pub modes: Vec<String>,
+}
+
+/// Elemento indicizzabile per id numerico e per nome/chiave.
+pub trait Entry {
+    fn entry_id(&self) -> u32;
+    fn entry_names(&self) -> [&str; 2];
+    /// Voce della modalità classica: vince sui nomi condivisi con varianti di altre modalità.
+    fn standard(&self) -> bool { true }
+}
+
+impl Entry for Champion      { fn entry_id(&self) -> u32 { self.key } fn entry_names(&self) -> [&str; 2] { [&self.id, &self.name] } }
+impl Entry for Item          { fn entry_id(&self) -> u32 { self.id }  fn entry_names(&self) -> [&str; 2] { [&self.name, &self.name] } }
+impl Entry for Rune          { fn entry_id(&self) -> u32 { self.id }  fn entry_names(&self) -> [&str; 2] { [&self.key, &self.name] } }
+impl Entry for RunePath      { fn entry_id(&self) -> u32 { self.id }  fn entry_names(&self) -> [&str; 2] { [&self.key, &self.name] } }
+impl Entry for SummonerSpell {
+    fn entry_id(&self) -> u32 { self.id }
+    fn entry_names(&self) -> [&str; 2] { [&self.key, &self.name] }
+    fn standard(&self) -> bool { self.modes.iter().any(|m| m == "CLASSIC") }
+}
+
+/// "Kai'Sa" → "kaisa", "Dr. Mundo" → "drmundo"
+pub fn normalize(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+#[derive(Debug)]
+pub struct Index<T> {
+    items: Vec<T>,
+    by_id: HashMap<u32, usize>,
+    by_name: HashMap<String, usize>,
+}
+
+impl<T: Entry> Index<T> {
+    fn new(mut items: Vec<T>) -> Self {
+        items.sort_by_key(|it| (!it.standard(), it.entry_id()));
+        let mut by_id = HashMap::new();
+        let mut by_name = HashMap::new();
+        for (i, it) in items.iter().enumerate() {
+            by_id.insert(it.entry_id(), i);
+            for n in it.entry_names() {
+                by_name.entry(normalize(n)).or_insert(i);
+            }
+        }
+        Index { items, by_id, by_name }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.by_id.get(&id).map(|&i| &self.items[i])
+    }
+
+    /// Per nome o chiave DDragon (case/spazi/punteggiatura ignorati).
+    pub fn find(&self, name: &str) -> Option<&T> {
+        self.by_name.get(&normalize(name)).map(|&i| &self.items[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+}
+
+#[derive(Debug)]
+pub struct StaticData {
+    pub version: String,
+    pub champions: Index<Champion>,
+    pub items: Index<Item>,
+    pub runes: Index<Rune>,
+    pub rune_paths: Index<RunePath>,
+    pub spells: Index<SummonerSpell>,
+}
+
+#[cfg(test)]
+impl StaticData {
+    /// Dati minimi per i test dei moduli che li usano; le rune si ricavano dalle righe dei path.
+    pub fn for_tests(champions: Vec<Champion>, items: Vec<Item>, rune_paths: Vec<RunePath>, spells: Vec<SummonerSpell>) -> Self {
+        let runes = rune_paths.iter()
+            .flat_map(|p| p.slots.iter().enumerate().flat_map(move |(slot, ids)| ids.iter().map(move |&id| Rune {
+                id, key: format!("Rune{}", id), name: format!("Rune {}", id), path: p.id, slot,
+            })))
+            .collect();
+        StaticData {
+            version: "test".to_string(),
+            champions: Index::new(champions),
+            items: Index::new(items),
+            runes: Index::new(runes),
+            rune_paths: Index::new(rune_paths),
+            spells: Index::new(spells),
+        }
+    }
+}
+
+// ── Parsing file DDragon ─────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct DataFile<T> { data: HashMap<String, T> }
+
+#[derive(Deserialize)]
+struct ChampionRaw { id: String, key: String, name: String, #[serde(default)] tags: Vec<String>, #[serde(default)] info: ChampionInfoRaw }
+
+#[derive(Deserialize, Default)]
+struct ChampionInfoRaw { #[serde(default)] attack: u8, #[serde(default)] defense: u8, #[serde(default)] magic: u8 }
+
+#[derive(Deserialize, Default)]
+struct GoldRaw { total: u32 }
+
+#[derive(Deserialize)]
+struct ItemRaw {
+    name: String,
+    #[serde(default)] into: Vec<String>,
+    #[serde(default)] tags: Vec<String>,
+    #[serde(default)] gold: GoldRaw,
+}
+
+#[derive(Deserialize)]
+struct SpellRaw { id: String, key: String, name: String, #[serde(default)] modes: Vec<String> }
+
+#[derive(Deserialize)]
+struct RuneRaw { id: u32, key: String, name: String }
+
+#[derive(Deserialize)]
+struct RuneSlotRaw { runes: Vec<RuneRaw> }
+
+#[derive(Deserialize)]
+struct RunePathRaw { id: u32, key: String, name: String, slots: Vec<RuneSlotRaw> }
+
+fn parse_static(version: &str, files: &HashMap<&str, Value>) -> Result<StaticData, String> {
+    let field = |name: &str| files.get(name).cloned().ok_or(format!("{} mancante", name));
+
+    let champs: DataFile<ChampionRaw> = serde_json::from_value(field("champion")?).map_err(|e| format!("champion.json: {}", e))?;
+    let champions = champs.data.into_values()
+        .filter_map(|c| Some(Champion {
+            key: c.key.parse().ok()?, id: c.id, name: c.name, tags: c.tags,
+            attack: c.info.attack, magic: c.info.magic, defense: c.info.defense,
+        }))
+        .collect();
+
+    let items: DataFile<ItemRaw> = serde_json::from_value(field("item")?).map_err(|e| format!("item.json: {}", e))?;
+    let items = items.data.into_iter()
+        .filter_map(|(id, it)| Some(Item {
+            id: id.parse().ok()?,
+            boots: it.tags.iter().any(|t| t == "Boots"),
+            completed: it.into.is_empty(),
+            gold: it.gold.total,
+            name: it.name,
+        }))
+        .collect();
+
+    let spells: DataFile<SpellRaw> = serde_json::from_value(field("summoner")?).map_err(|e| format!("summoner.json: {}", e))?;
+    let spells = spells.data.into_values()
+        .filter_map(|s| Some(SummonerSpell { id: s.key.parse().ok()?, key: s.id, name: s.name, modes: s.modes }))
+        .collect();
+
+    let paths: Vec<RunePathRaw> = serde_json::from_value(field("runesReforged")?).map_err(|e| format!("runesReforged.json: {}", e))?;
+    let mut runes = Vec::new();
+    let mut rune_paths = Vec::new();
+    for p in paths {
+        let mut slots = Vec::new();
+        for (slot, s) in p.slots.into_iter().enumerate() {
+            slots.push(s.runes.iter().map(|r| r.id).collect());
+            runes.extend(s.runes.into_iter().map(|r| Rune { id: r.id, key: r.key, name: r.name, path: p.id, slot }));
+        }
+        rune_paths.push(RunePath { id: p.id, key: p.key, name: p.name, slots });
+    }
+
+    Ok(StaticData {
+        version: version.to_string(),
+        champions: Index::new(champions),
+        items: Index::new(items),
+        runes: Index::new(runes),
+        rune_paths: Index::new(rune_paths),
+        spells: Index::new(spells),
+    })
+}
+
+// ── Download / cache su disco ────────────────────────────────────────────────
+
+const FILES: [&str; 4] = ["champion", "item", "summoner", "runesReforged"];
+
+fn http() -> Client {
+    Client::builder().timeout(Duration::from_secs(20)).build().unwrap()
+}
+
+async fn fetch_latest_version(client: &Client) -> Result<String, String> {
+    let versions: Vec<String> = client.get(format!("{}/api/versions.json", DDRAGON_URL))
+        .send().await.map_err(|e| e.to_string())?
+        .json().await.map_err(|e| e.to_string())?;
+    versions.into_iter().next().ok_or("versions.json vuoto".into())
+}
+
+/// Ultima patch già presente su disco (per l'avvio offline).
+fn newest_cached_version() -> Option<String> {
+    let dir = CACHE_DIR.get()?;
+    let mut versions: Vec<(Vec<u32>, String)> = std::fs::read_dir(dir).ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|v| FILES.iter().all(|f| dir.join(v).join(format!("{}.json", f)).exists()))
+        .map(|v| (v.split('.').filter_map(|n| n.parse().ok()).collect(), v))
+        .collect();
+    versions.sort();
+    versions.pop().map(|(_, v)| v)
+}
+
+async fn load_file(client: &Client, version: &str, name: &str) -> Result<Value, String> {
+    let path = CACHE_DIR.get().map(|d| d.join(version).join(format!("{}.json", name)));
+    if let Some(p) = &path {
+        if let Some(v) = std::fs::read_to_string(p).ok().and_then(|s| serde_json::from_str(&s).ok()) {
+            return Ok(v);
+        }
+    }
+    let url = format!("{}/cdn/{}/data/{}/{}.json", DDRAGON_URL, version, LOCALE, name);
+    let body = client.get(&url).send().await.map_err(|e| e.to_string())?
+        .error_for_status().map_err(|e| e.to_string())?
+        .text().await.map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&body).map_err(|e| format!("{}: {}", name, e))?;
+    if let Some(p) = &path {
+        if let Some(dir) = p.parent() { let _ = std::fs::create_dir_all(dir); }
+        let _ = std::fs::write(p, &body);
+    }
+    Ok(value)
+}
+
+async fn load_version(client: &Client, version: &str) -> Result<StaticData, String> {
+    let mut files = HashMap::new();
+    for name in FILES {
+        files.insert(name, load_file(client, version, name).await?);
+    }
+    let data = parse_static(version, &files)?;
+    eprintln!("[StaticData] patch {} caricata ({} campioni, {} item, {} rune, {} spell)",
+        version, data.champions.items.len(), data.items.items.len(), data.runes.items.len(), data.spells.items.len());
+    Ok(data)
+}
+
+struct Loaded {
+    checked_at: Instant,
+    /// `false` se la patch non è stata verificata online (offline / rete assente):
+    /// si ricontrolla versions.json dopo RETRY_BACKOFF invece che dopo 6 ore.
+    fresh: bool,
+    data: Arc<StaticData>,
+}
+
+impl Loaded {
+    fn valid(&self) -> bool {
+        if OFFLINE.load(Ordering::Relaxed) { return true; }
+        let ttl = if self.fresh { VERSION_CHECK_TTL } else { RETRY_BACKOFF };
+        self.checked_at.elapsed() < ttl
+    }
+}
+
+static STATIC_DATA: OnceCell<RwLock<Option<Loaded>>> = OnceCell::const_new();
+async fn static_data_cell() -> &'static RwLock<Option<Loaded>> {
+    STATIC_DATA.get_or_init(|| async { RwLock::new(None) }).await
+}
+
+/// Dati statici della patch corrente (scaricati una volta per patch).
+pub async fn static_data() -> Result<Arc<StaticData>, String> {
+    let cell = static_data_cell().await;
+    if let Some(l) = cell.read().await.as_ref() {
+        if l.valid() { return Ok(l.data.clone()); }
+    }
+
+    let mut guard = cell.write().await;
+    if let Some(l) = guard.as_ref() {
+        if l.valid() { return Ok(l.data.clone()); }
+    }
+
+    let client = http();
+    let latest = if OFFLINE.load(Ordering::Relaxed) {
+        Err("modalità offline".to_string())
+    } else {
+        fetch_latest_version(&client).await
+    };
+    let (version, fresh) = match latest {
+        Ok(v) => (v, true),
+        Err(e) => {
+            eprintln!("[StaticData] versions.json non raggiungibile: {}", e);
+            // Offline: teniamo la patch già in memoria, altrimenti l'ultima su disco
+            if let Some(l) = guard.as_mut() {
+                l.checked_at = Instant::now();
+                return Ok(l.data.clone());
+            }
+            let v = newest_cached_version().ok_or(format!("Data Dragon non disponibile: {}", e))?;
+            (v, false)
+        }
+    };
+
+    if let Some(l) = guard.as_mut() {
+        if l.data.version == version {
+            l.checked_at = Instant::now();
+            l.fresh = fresh;
+            return Ok(l.data.clone());
+        }
+    }
+
+    match load_version(&client, &version).await {
+        Ok(data) => {
+            let data = Arc::new(data);
+            *guard = Some(Loaded { checked_at: Instant::now(), fresh, data: data.clone() });
+            Ok(data)
+        }
+        // Patch nuova non scaricabile: meglio la precedente che niente
+        Err(e) => match guard.as_mut() {
+            Some(l) => {
+                eprintln!("[StaticData] patch {} non caricata ({}), resto su {}", version, e, l.data.version);
+                l.checked_at = Instant::now();
+                Ok(l.data.clone())
+            }
+            None => Err(e),
+        },
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Mappa campioni per il frontend (funziona anche offline, dai file su disco):
+/// `{ version, champions: { "157": { id: "Yasuo", name: "Yasuo" }, ... } }`
+#[tauri::command]
+pub async fn get_champion_map() -> Result<Value, String> {
+    let data = static_data().await?;
+    let champions: Map<String, Value> = data.champions.items.iter()
+        .map(|c| (c.key.to_string(), json!({ "id": c.id, "name": c.name })))
+        .collect();
+    Ok(json!({ "version": data.version, "champions": champions }))
+}
@@ -0,0 +1,273 @@
+// summoner_spells.rs — import delle summoner spell in champ select
+//
+// Le spell della build (chiavi DDragon o id numerici: i nomi non sono univoci, "Flash"
+// esiste anche nella variante Arena) si validano contro la coda corrente:
+// ogni spell deve essere selezionabile nel gameMode (campo `modes` di summoner.json,
+// es. Mark/Snowball solo in ARAM) e Punizione solo per chi ha la jungla assegnata
+// (senza lane assegnata, blind pick e custom, nessun vincolo). Le spell
+// sconosciute o non ammesse si sostituiscono con quelle di default della modalità;
+// ogni sostituzione finisce nel risultato dell'import.
+// Preferenza "Flash su D/F" (spell_prefs.json): la coppia si riordina di conseguenza;
+// "auto" mantiene Flash sul tasto dove l'utente lo ha già in champ select.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::lcu::LcuClient;
+use crate::static_data::{static_data, StaticData, SummonerSpell};
+
+const FLASH: u32 = 4;
+const SMITE: u32 = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashKey {
+    D,
+    F,
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellPrefs {
+    #[serde(default)]
+    pub flash_key: FlashKey,
+}
+
+static PREFS_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+pub fn init(app_data_dir: &Path) {
+    let _ = PREFS_FILE.set(app_data_dir.join("spell_prefs.json"));
+}
+
+pub fn prefs() -> SpellPrefs {
+    PREFS_FILE.get()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+// ── Validazione ──────────────────────────────────────────────────────────────
+
+/// Spell della build per id numerico o chiave DDragon ("SummonerFlash").
+fn resolve<'a>(spell: &str, data: &'a StaticData) -> Option<&'a SummonerSpell> {
+    match spell.parse::<u32>() {
+        Ok(id) => data.spells.get(id),
+        Err(_) => data.spells.iter().find(|s| s.key.eq_ignore_ascii_case(spell)),
+    }
+}
+
+/// gameMode DDragon della modalità normalizzata.
+fn game_mode(mode: &str) -> &'static str {
+    match mode {
+        "aram"  => "ARAM",
+        "urf"   => "URF",
+        "arena" => "CHERRY",
+        _       => "CLASSIC",
+    }
+}
+
+/// Coppia di ripiego per modalità e lane.
+fn defaults(mode: &str, position: &str) -> [&'static str; 2] {
+    match (mode, position.to_uppercase().as_str()) {
+        ("aram", _)       => ["SummonerFlash", "SummonerSnowball"],
+        (_, "JUNGLE")     => ["SummonerFlash", "SummonerSmite"],
+        (_, "UTILITY")    => ["SummonerFlash", "SummonerExhaust"],
+        (_, "BOTTOM")     => ["SummonerFlash", "SummonerHeal"],
+        _                 => ["SummonerFlash", "SummonerDot"],
+    }
+}
+
+/// Coppia da impostare, già validata e ordinata.
+#[derive(Debug, Clone)]
+pub struct SpellPlan {
+    /// [spell1Id (D), spell2Id (F)]
+    pub ids: [u32; 2],
+    pub names: Vec<String>,
+    /// Spell scartate o aggiunte rispetto alla build.
+    pub fixes: Vec<String>,
+}
+
+pub fn plan(build_spells: &[String], mode: &str, position: &str, current: Option<[u32; 2]>,
+            prefs: &SpellPrefs, data: &StaticData) -> Result<SpellPlan, String> {
+    let gm = game_mode(mode);
+    // Senza lane assegnata (blind, custom) Punizione resta ammessa
+    let smite_ok = position.is_empty() || position.eq_ignore_ascii_case("JUNGLE");
+    // Cache DDragon senza `modes` (o modalità che non vi compare): nessun filtro per modalità
+    let mode_known = data.spells.iter().any(|s| s.modes.iter().any(|m| m == gm));
+    let allowed = |sp: &SummonerSpell| {
+        (!mode_known || sp.modes.iter().any(|m| m == gm)) && (sp.id != SMITE || smite_ok)
+    };
+
+    let mut chosen: Vec<&SummonerSpell> = Vec::new();
+    let mut fixes = Vec::new();
+    for name in build_spells {
+        if chosen.len() == 2 { break; }
+        match resolve(name, data) {
+            None => fixes.push(format!("spell {} sconosciuta", name)),
+            Some(sp) if !allowed(sp) => fixes.push(format!("{} non ammessa in questa coda", sp.name)),
+            Some(sp) if chosen.iter().any(|c| c.id == sp.id) => {}
+            Some(sp) => chosen.push(sp),
+        }
+    }
+    let mut fallback: Vec<&SummonerSpell> = defaults(mode, position).iter().filter_map(|k| resolve(k, data)).collect();
+    let mut others: Vec<&SummonerSpell> = data.spells.iter().collect();
+    others.sort_by_key(|s| s.id);
+    fallback.extend(others);
+    for sp in fallback {
+        if chosen.len() == 2 { break; }
+        if allowed(sp) && !chosen.iter().any(|c| c.id == sp.id) {
+            fixes.push(format!("aggiunta {}", sp.name));
+            chosen.push(sp);
+        }
+    }
+    if chosen.len() < 2 {
+        return Err(format!("Nessuna coppia di spell valida per {}", gm));
+    }
+
+    // Flash sul tasto preferito (o dove l'utente lo tiene già)
+    let flash_slot = match prefs.flash_key {
+        FlashKey::D => Some(0),
+        FlashKey::F => Some(1),
+        FlashKey::Auto => current.and_then(|c| c.iter().position(|&id| id == FLASH)),
+    };
+    if let (Some(slot), Some(pos)) = (flash_slot, chosen.iter().position(|s| s.id == FLASH)) {
+        if slot != pos { chosen.swap(0, 1); }
+    }
+
+    Ok(SpellPlan {
+        ids: [chosen[0].id, chosen[1].id],
+        names: chosen.iter().map(|s| s.name.clone()).collect(),
+        fixes,
+    })
+}
+
+// ── Import ───────────────────────────────────────────────────────────────────
+
+/// Spell attuali del giocatore locale in champ select.
+async fn current_spells(lcu: &LcuClient) -> Option<[u32; 2]> {
+    let session = lcu.get("/lol-champ-select/v1/session").await?;
+    let my_cell = session["localPlayerCellId"].as_i64()?;
+    let me = session["myTeam"].as_array()?.iter().find(|p| p["cellId"].as_i64() == Some(my_cell))?;
+    Some([me["spell1Id"].as_u64()? as u32, me["spell2Id"].as_u64()? as u32])
+}
+
+/// Esito dell'import: `Ok(None)` se la build non ha spell (import saltato).
+pub async fn import(lcu: &LcuClient, build: &Value, mode: &str, position: &str) -> Result<Option<SpellPlan>, String> {
+    let spells: Vec<String> = build.pointer("/data/summoner_spells").and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if spells.is_empty() { return Ok(None); }
+
+    let data = static_data().await?;
+    let current = current_spells(lcu).await;
+    let plan = plan(&spells, mode, position, current, &prefs(), &data)?;
+    if !plan.fixes.is_empty() { eprintln!("[Spells] correzioni: {:?}", plan.fixes); }
+
+    if current == Some(plan.ids) {
+        eprintln!("[Spells] già impostate: {:?}", plan.names);
+        return Ok(Some(plan));
+    }
+    let status = lcu.patch_status("/lol-champ-select/v1/session/my-selection",
+        &json!({"spell1Id": plan.ids[0], "spell2Id": plan.ids[1]})).await?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Il client ha rifiutato il cambio spell (HTTP {})", status));
+    }
+    eprintln!("[Spells] impostate {:?}", plan.names);
+    Ok(Some(plan))
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_spell_prefs() -> Result<SpellPrefs, String> {
+    Ok(prefs())
+}
+
+#[tauri::command]
+pub async fn set_spell_prefs(prefs: SpellPrefs) -> Result<(), String> {
+    let path = PREFS_FILE.get().ok_or("Cartella dati non disponibile")?;
+    let content = serde_json::to_string_pretty(&prefs).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Salvataggio preferenze spell fallito: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell(id: u32, key: &str, name: &str, modes: &[&str]) -> SummonerSpell {
+        SummonerSpell { id, key: key.into(), name: name.into(), modes: modes.iter().map(|m| m.to_string()).collect() }
+    }
+
+    fn data() -> StaticData {
+        StaticData::for_tests(Vec::new(), Vec::new(), Vec::new(), vec![
+            spell(FLASH, "SummonerFlash", "Flash", &["CLASSIC", "ARAM"]),
+            spell(SMITE, "SummonerSmite", "Smite", &["CLASSIC"]),
+            spell(14, "SummonerDot", "Ignite", &["CLASSIC", "ARAM"]),
+            spell(7, "SummonerHeal", "Heal", &["CLASSIC", "ARAM"]),
+            spell(3, "SummonerExhaust", "Exhaust", &["CLASSIC", "ARAM"]),
+            spell(32, "SummonerSnowball", "Mark", &["ARAM"]),
+        ])
+    }
+
+    fn build(spells: &[&str]) -> Vec<String> {
+        spells.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn plan_for(spells: &[&str], mode: &str, position: &str) -> SpellPlan {
+        plan(&build(spells), mode, position, None, &SpellPrefs::default(), &data()).unwrap()
+    }
+
+    #[test]
+    fn mark_only_in_aram() {
+        let aram = plan_for(&["SummonerFlash", "SummonerSnowball"], "aram", "");
+        assert_eq!(aram.ids, [FLASH, 32]);
+        assert!(aram.fixes.is_empty());
+
+        let ranked = plan_for(&["SummonerFlash", "SummonerSnowball"], "ranked", "MIDDLE");
+        assert_eq!(ranked.ids, [FLASH, 14]);
+        assert_eq!(ranked.fixes, vec!["Mark non ammessa in questa coda", "aggiunta Ignite"]);
+    }
+
+    #[test]
+    fn smite_needs_jungle_only_when_a_lane_is_assigned() {
+        assert_eq!(plan_for(&["SummonerFlash", "SummonerSmite"], "ranked", "JUNGLE").ids, [FLASH, SMITE]);
+        assert_eq!(plan_for(&["SummonerFlash", "SummonerSmite"], "ranked", "jungle").ids, [FLASH, SMITE]);
+        // Blind pick e custom: assignedPosition vuota
+        let blind = plan_for(&["SummonerFlash", "SummonerSmite"], "normal", "");
+        assert_eq!(blind.ids, [FLASH, SMITE]);
+        assert!(blind.fixes.is_empty());
+
+        let top = plan_for(&["SummonerFlash", "SummonerSmite"], "ranked", "TOP");
+        assert_eq!(top.ids, [FLASH, 14]);
+        assert_eq!(top.fixes, vec!["Smite non ammessa in questa coda", "aggiunta Ignite"]);
+    }
+
+    #[test]
+    fn unknown_spells_are_replaced_by_mode_defaults() {
+        let p = plan_for(&["4", "9999"], "ranked", "BOTTOM");
+        assert_eq!(p.ids, [FLASH, 7]);
+        assert_eq!(p.fixes, vec!["spell 9999 sconosciuta", "aggiunta Heal"]);
+
+        // Numerici e chiavi DDragon sono equivalenti
+        assert_eq!(plan_for(&["14", "SummonerFlash"], "ranked", "MIDDLE").ids, [14, FLASH]);
+        assert!(plan(&build(&["1", "2"]), "ranked", "MIDDLE", None, &SpellPrefs::default(),
+            &StaticData::for_tests(Vec::new(), Vec::new(), Vec::new(), Vec::new())).is_err());
+    }
+
+    #[test]
+    fn flash_follows_key_preference() {
+        let spells = build(&["SummonerDot", "SummonerFlash"]);
+        let on = |flash_key, current| plan(&spells, "ranked", "MIDDLE", current, &SpellPrefs { flash_key }, &data()).unwrap().ids;
+        assert_eq!(on(FlashKey::D, None), [FLASH, 14]);
+        assert_eq!(on(FlashKey::F, None), [14, FLASH]);
+        // Auto: dove l'utente tiene già Flash, altrimenti l'ordine della build
+        assert_eq!(on(FlashKey::Auto, Some([FLASH, 7])), [FLASH, 14]);
+        assert_eq!(on(FlashKey::Auto, Some([7, FLASH])), [14, FLASH]);
+        assert_eq!(on(FlashKey::Auto, None), [14, FLASH]);
+    }
+}
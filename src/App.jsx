@@ -1,5 +1,6 @@
 import { useEffect, useState, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { check } from "@tauri-apps/plugin-updater";
 import { relaunch } from "@tauri-apps/plugin-process";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "./components/ui/tabs";
@@ -8,7 +9,7 @@ import { MatchHistoryTab } from "./components/match-history-tab";
 import { ChampionMetaTab } from "./components/champion-meta-tab";
 import { MetaTab } from "./components/meta-tab";
 import { MasteriesTab } from "./components/masteries-tab";
-import { User, History, TrendingUp, Search, BarChart2, Swords, Tv, Star, X } from "lucide-react";
+import { User, History, TrendingUp, Search, BarChart2, Swords, Tv, Star, X, Wifi, WifiOff } from "lucide-react";
 import { Input } from "./components/ui/input";
 import { Button } from "./components/ui/button";
 import "./App.css";
@@ -163,26 +164,41 @@ export default function App() {
     const [liveGamePuuid, setLiveGamePuuid] = useState(null);
     const liveGamePuuidRef = useRef(null);
     const [isInLiveGame, setIsInLiveGame] = useState(false);
-    const champSelectPollRef = useRef(null);
     const wasInChampSelect = useRef(false);
+    const [offlineStatus, setOfflineStatus] = useState({ offline: false, forced: false, since: null, queued: 0 });
+
+    // Modalità offline: stato iniziale + aggiornamenti dal backend (evento "offline-status")
+    useEffect(() => {
+        invoke("get_offline_status").then(setOfflineStatus).catch(() => { });
+        const unlisten = listen("offline-status", e => setOfflineStatus(e.payload));
+        return () => { unlisten.then(f => f()); };
+    }, []);
+
+    async function toggleOffline() {
+        try {
+            setOfflineStatus(await invoke("set_offline_mode", { enabled: !offlineStatus.forced }));
+        } catch (e) { console.error("[offline]", e); }
+    }
 
     useEffect(() => {
         if (activeTab === "champ-select" && (searchData || !profileData)) setActiveTab("profile");
     }, [searchData, profileData, activeTab]);
 
     useEffect(() => {
+        function onSession(session) {
+            const inProgress = session?.in_progress === true;
+            if (inProgress && !wasInChampSelect.current) setActiveTab("champ-select");
+            wasInChampSelect.current = inProgress;
+            setIsInChampSelect(inProgress);
+        }
         async function checkChampSelect() {
-            try {
-                const session = await invoke("get_champ_select_session");
-                const inProgress = session?.in_progress === true;
-                if (inProgress && !wasInChampSelect.current) setActiveTab("champ-select");
-                wasInChampSelect.current = inProgress;
-                setIsInChampSelect(inProgress);
-            } catch { wasInChampSelect.current = false; setIsInChampSelect(false); }
+            try { onSession(await invoke("get_champ_select_session")); }
+            catch { wasInChampSelect.current = false; setIsInChampSelect(false); }
         }
-        champSelectPollRef.current = setInterval(checkChampSelect, 5000);
+        // Stato iniziale una volta, poi solo eventi dal websocket LCU (niente polling)
         checkChampSelect();
-        return () => clearInterval(champSelectPollRef.current);
+        const unlisten = listen("lcu-champ-select", e => onSession(e.payload));
+        return () => { unlisten.then(f => f()); };
     }, []);
 
     useEffect(() => {
@@ -215,6 +231,12 @@ export default function App() {
         return Array.from(map.values());
     }
 
+    // Cursore per la paginazione: gameCreation del match più vecchio già caricato
+    function oldestGameCreation(arr) {
+        const times = (arr ?? []).map(m => m?.info?.gameCreation ?? m?.gameCreation).filter(Boolean);
+        return times.length > 0 ? Math.min(...times) : null;
+    }
+
     // ✅ FIX: helper per impostare recentStats con fallback su errore
     function fetchRecentStats(puuid, r, setter) {
         invoke("get_recent_stats", { puuid, region: r })
@@ -291,7 +313,16 @@ export default function App() {
         fetchData();
         const interval = setInterval(fetchData, 60000);
         const regionInterval = setInterval(detectRegionFromLCU, 30000);
-        return () => { clearInterval(interval); clearInterval(regionInterval); };
+        // Client League aperto/riavviato: ricarica subito senza aspettare il prossimo giro
+        const unlisten = listen("lcu-connection", e => {
+            if (e.payload?.state === "Connected") { detectRegionFromLCU(); fetchData(); }
+        });
+        // Fine partita: le statistiche sono pronte, ricarica subito i match
+        const unlistenEog = listen("lcu-end-of-game", () => fetchData());
+        return () => {
+            clearInterval(interval); clearInterval(regionInterval);
+            unlisten.then(f => f()); unlistenEog.then(f => f());
+        };
     }, []);
 
     useEffect(() => {
@@ -336,7 +367,7 @@ export default function App() {
         const snapshot = dedupeMatches(allMatches);
         const existingIds = new Set(snapshot.map(m => m?.metadata?.matchId ?? m?.matchId).filter(Boolean));
         try {
-            const more = await invoke("get_more_matches", { puuid: profileData.puuid, start: snapshot.length, region: regionRef.current });
+            const more = await invoke("get_more_matches", { puuid: profileData.puuid, start: snapshot.length, before: oldestGameCreation(snapshot), region: regionRef.current });
             if (!more || more.length === 0) return 0;
             const trueNew = more.filter(m => { const id = m?.metadata?.matchId ?? m?.matchId; return id && !existingIds.has(id); });
             if (trueNew.length > 0) { setAllMatches(prev => dedupeMatches([...prev, ...trueNew])); return trueNew.length; }
@@ -351,7 +382,7 @@ export default function App() {
         const currentMatches = dedupeMatches(searchData.matches ?? []);
         const existingIds = new Set(currentMatches.map(m => m?.metadata?.matchId ?? m?.matchId).filter(Boolean));
         try {
-            const more = await invoke("get_more_matches", { puuid: searchData.puuid, start: currentMatches.length, region: searchData._region ?? regionRef.current });
+            const more = await invoke("get_more_matches", { puuid: searchData.puuid, start: currentMatches.length, before: oldestGameCreation(currentMatches), region: searchData._region ?? regionRef.current });
             if (!more || more.length === 0) return 0;
             const trueNew = more.filter(m => { const id = m?.metadata?.matchId ?? m?.matchId; return id && !existingIds.has(id); });
             if (trueNew.length > 0) {
@@ -482,6 +513,13 @@ export default function App() {
                             </div>
                         </div>
                         <div className="flex items-center gap-3">
+                            <button
+                                onClick={toggleOffline}
+                                className={`p-2 rounded-lg border transition-colors ${offlineStatus.offline ? "bg-amber-950/60 border-amber-700/60 text-amber-300" : "bg-[#0d1f38] border-[#1a3558] text-[#5a8ab0] hover:text-white"}`}
+                                title={offlineStatus.forced ? "Modalità offline attiva — clicca per tornare online" : offlineStatus.offline ? "Rete non disponibile — dati locali" : "Passa alla modalità offline"}
+                            >
+                                {offlineStatus.offline ? <WifiOff className="w-4 h-4" /> : <Wifi className="w-4 h-4" />}
+                            </button>
                             <div className="relative flex items-center">
                                 <select
                                     value={region}
@@ -577,6 +615,18 @@ export default function App() {
                 </div>
             </header>
 
+            {(searchData ?? profileData)?.stale_since && (
+                <div className="max-w-7xl mx-auto px-4 mt-4">
+                    <div className="p-3 bg-amber-950/40 border border-amber-700/50 text-amber-200 rounded-lg text-sm flex items-center gap-2">
+                        <WifiOff className="w-4 h-4 shrink-0" />
+                        <span>
+                            Dati locali aggiornati al {new Date((searchData ?? profileData).stale_since).toLocaleString()}
+                            {offlineStatus.queued > 0 && ` — ${offlineStatus.queued} aggiornamenti in attesa della rete`}
+                        </span>
+                    </div>
+                </div>
+            )}
+
             {searchError && (
                 <div className="max-w-7xl mx-auto px-4 mt-4">
                     <div className="p-3 bg-red-950/60 border border-red-700/60 text-red-300 rounded-lg text-sm flex items-center justify-between gap-3">
@@ -649,7 +699,7 @@ export default function App() {
                     </TabsContent>
 
                     <TabsContent value="tier-list"><MetaTab onMetaDataReady={setLiveMetaData} /></TabsContent>
-                    <TabsContent value="champ-select" keepMounted><ChampSelectTab /></TabsContent>
+                    <TabsContent value="champ-select" keepMounted><ChampSelectTab region={region} /></TabsContent>
                     <TabsContent value="live-game"><LiveGameTab puuidOverride={liveGamePuuid} myPuuid={myPuuid} region={region} onStatusChange={setIsInLiveGame} /></TabsContent>
                 </Tabs>
             </main>
@@ -1,5 +1,6 @@
-import { useEffect, useState, useRef } from "react";
+import { Fragment, useEffect, useState, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Card } from "./ui/card";
 import { Badge } from "./ui/badge";
 import { Shield, Zap, Package, CheckCircle, XCircle, Loader2, Database, TrendingUp } from "lucide-react";
@@ -241,14 +242,298 @@ function StepRow({ icon: Icon, label, done, active, error }) {
     );
 }
 
+// ── Draft ────────────────────────────────────────────────────────────────────
+const POSITION_SHORT = { TOP: "Top", JUNGLE: "Jgl", MIDDLE: "Mid", BOTTOM: "Bot", UTILITY: "Sup" };
+
+function DamageBar({ damage }) {
+    return (
+        <div>
+            <div className="flex h-2 rounded overflow-hidden bg-[#142545]">
+                <div className="bg-orange-500" style={{ width: `${damage.physical}%` }} />
+                <div className="bg-[#4fc3f7]" style={{ width: `${damage.magic}%` }} />
+                <div className="bg-white" style={{ width: `${damage.true}%` }} />
+            </div>
+            <p className="text-[10px] text-[#5a8ab0] mt-1">
+                AD {damage.physical}% · AP {damage.magic}% · Puro {damage.true}%
+            </p>
+        </div>
+    );
+}
+
+function DraftTeam({ title, team }) {
+    const ind = team.indicators;
+    return (
+        <div className="space-y-2">
+            <h5 className="text-xs font-semibold text-[#b8d4e8]">{title}</h5>
+            <div className="flex gap-1.5">
+                {team.picks.map(p => (
+                    <div key={p.cell_id} className="text-center w-10" title={p.champion_name}>
+                        {p.champion ? (
+                            <img
+                                src={`https://ddragon.leagueoflegends.com/cdn/${PATCH}/img/champion/${p.champion}.png`}
+                                alt={p.champion_name}
+                                className={`w-10 h-10 rounded object-cover border ${p.is_me ? "border-[#1e6fff]" : "border-[#1a3558]"} ${p.intent ? "opacity-50" : ""}`}
+                                onError={e => { e.target.style.display = "none"; }}
+                            />
+                        ) : (
+                            <div className="w-10 h-10 rounded bg-[#142545]" />
+                        )}
+                        <p className="text-[10px] text-[#3a6080]">{POSITION_SHORT[p.position] ?? ""}</p>
+                    </div>
+                ))}
+            </div>
+            {team.bans.length > 0 && (
+                <p className="text-[10px] text-[#5a8ab0]">
+                    Ban: {team.bans.map(b => b.champion_name + (b.completed ? "" : " (hover)")).join(", ")}
+                </p>
+            )}
+            <DamageBar damage={team.damage} />
+            <p className="text-[10px] text-[#5a8ab0]">
+                Engage {ind.engage} · Peel {ind.peel} · Frontline {ind.frontline} · CC {ind.cc}/10
+            </p>
+            {team.missing_roles.length > 0 && (
+                <p className="text-[10px] text-[#5a8ab0]">
+                    Da scegliere: {team.missing_roles.map(r => POSITION_SHORT[r] ?? r).join(", ")}
+                </p>
+            )}
+            {team.warnings.map(w => (
+                <p key={w} className="text-xs text-yellow-400">⚠ {w}</p>
+            ))}
+        </div>
+    );
+}
+
+function MatchupPanel({ region, enemyKey }) {
+    const [matchups, setMatchups] = useState(null);
+    const [error, setError] = useState(null);
+
+    useEffect(() => {
+        // Ricalcolato solo quando cambiano gli avversari visibili
+        if (!enemyKey) { setMatchups(null); return; }
+        let cancelled = false;
+        invoke("get_matchup_suggestions", { region })
+            .then(r => { if (!cancelled) { setMatchups(r); setError(null); } })
+            .catch(e => { if (!cancelled) setError(String(e)); });
+        return () => { cancelled = true; };
+    }, [enemyKey, region]);
+
+    if (error) return <p className="text-xs text-red-400 mt-3">Counter non disponibili: {error}</p>;
+    if (!matchups?.suggestions?.length) return null;
+    const pct = (v) => `${(v * 100).toFixed(1)}%`;
+    return (
+        <div className="mt-4 pt-3 border-t border-[#1a3558]">
+            <h5 className="text-xs font-semibold text-[#b8d4e8] mb-2">
+                Counter pick ({matchups.position.toLowerCase()}) contro {matchups.enemies.join(", ")}
+            </h5>
+            <div className="space-y-1">
+                {matchups.suggestions.map(s => (
+                    <div key={s.champion_id} className="flex items-center gap-2 text-xs"
+                        title={s.versus.map(v => `${v.champion_name}: ${pct(v.win_rate)} (${v.games})`).join("\n")}>
+                        <img
+                            src={`https://ddragon.leagueoflegends.com/cdn/${PATCH}/img/champion/${s.champion}.png`}
+                            alt={s.champion_name}
+                            className="w-6 h-6 rounded object-cover"
+                            onError={e => { e.target.style.display = "none"; }}
+                        />
+                        <span className="text-[#b8d4e8] w-24 truncate">{s.champion_name}</span>
+                        <span className={s.lane_win_rate >= 0.5 ? "text-green-400" : "text-[#5a8ab0]"}>
+                            {pct(s.lane_win_rate)}
+                        </span>
+                        <span className="text-[#3a6080]">{s.games} partite</span>
+                        {s.mastery_level != null && (
+                            <span className="text-[#7dd8ff]">M{s.mastery_level}</span>
+                        )}
+                        {s.personal_games > 0 && (
+                            <span className="text-[#5a8ab0] ml-auto">
+                                tu: {pct(s.personal_win_rate)} in {s.personal_games}
+                            </span>
+                        )}
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+}
+
+function ScoutingPanel({ region, teamKey }) {
+    const [scouts, setScouts] = useState(null);
+    const [error, setError] = useState(null);
+
+    useEffect(() => {
+        // Ricalcolato quando i compagni cambiano campione (hover o lock)
+        let cancelled = false;
+        invoke("get_teammate_scouting", { region })
+            .then(r => { if (!cancelled) { setScouts(r); setError(null); } })
+            .catch(e => { if (!cancelled) setError(String(e)); });
+        return () => { cancelled = true; };
+    }, [teamKey, region]);
+
+    if (error) return <p className="text-xs text-red-400 mt-3">Scouting non disponibile: {error}</p>;
+    if (!scouts?.length) return null;
+    const pct = (v) => `${Math.round(v * 100)}%`;
+    return (
+        <div className="mt-4 pt-3 border-t border-[#1a3558]">
+            <h5 className="text-xs font-semibold text-[#b8d4e8] mb-2">Compagni</h5>
+            <div className="space-y-2">
+                {scouts.map(s => (
+                    <div key={s.cell_id} className="text-xs">
+                        <div className="flex items-center gap-2">
+                            <span className="text-[#3a6080] w-16">{s.position.toLowerCase() || "—"}</span>
+                            <span className="text-[#b8d4e8] w-24 truncate">{s.champion_name || s.name || "—"}</span>
+                            {s.hidden ? (
+                                <span className="text-[#3a6080]">nascosto</span>
+                            ) : (
+                                <>
+                                    <span className="text-[#7dd8ff]">
+                                        {s.tier ? `${s.tier} ${s.rank} ${s.lp} LP` : "Unranked"}
+                                    </span>
+                                    {s.win_rate != null && (
+                                        <span className={s.win_rate >= 0.5 ? "text-green-400" : "text-[#5a8ab0]"}>
+                                            {pct(s.win_rate)} in {s.games}
+                                        </span>
+                                    )}
+                                    {s.mastery_level != null && s.champion_id > 0 && (
+                                        <span className="text-[#5a8ab0]">M{s.mastery_level}</span>
+                                    )}
+                                    {s.first_time && <Badge className="bg-orange-900/50 text-orange-300 border-0">First time</Badge>}
+                                    {s.autofill && <Badge className="bg-red-900/50 text-red-300 border-0">Autofill</Badge>}
+                                </>
+                            )}
+                        </div>
+                        {s.pool.length > 0 && (
+                            <div className="flex items-center gap-1 mt-1 ml-[4.5rem]"
+                                title={s.main_roles.length ? `Ruoli: ${s.main_roles.join(", ").toLowerCase()}` : undefined}>
+                                {s.pool.map(c => (
+                                    <img key={c.champion_id}
+                                        src={`https://ddragon.leagueoflegends.com/cdn/${PATCH}/img/champion/${c.champion}.png`}
+                                        alt={c.champion_name}
+                                        title={`${c.champion_name}: ${c.wins}V ${c.games - c.wins}S`}
+                                        className="w-5 h-5 rounded object-cover"
+                                        onError={e => { e.target.style.display = "none"; }}
+                                    />
+                                ))}
+                            </div>
+                        )}
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+}
+
+const AUTOMATION_ROLES = ["TOP", "JUNGLE", "MIDDLE", "BOTTOM", "UTILITY", "DEFAULT"];
+
+function AutomationPanel() {
+    const [settings, setSettings] = useState(null);
+    const [log, setLog] = useState([]);
+    const [error, setError] = useState(null);
+
+    useEffect(() => {
+        invoke("get_automation_settings").then(setSettings).catch(e => setError(String(e)));
+        invoke("get_automation_log").then(setLog).catch(() => {});
+        const unlisten = listen("lcu-auto-action", e => setLog(l => [...l.slice(-49), e.payload]));
+        return () => { unlisten.then(f => f()); };
+    }, []);
+
+    async function save(next) {
+        try {
+            await invoke("set_automation_settings", { settings: next });
+            setSettings(next);
+            setError(null);
+        } catch (e) {
+            setError(`Automazioni non salvate: ${e}`);
+        }
+    }
+
+    // Liste per ruolo come testo separato da virgole ("Ahri, Syndra")
+    function changeList(key, role, text) {
+        const list = text.split(",").map(c => c.trim()).filter(Boolean);
+        save({ ...settings, [key]: { ...settings[key], [role]: list } });
+    }
+
+    if (!settings) return error ? <p className="text-xs text-red-400">{error}</p> : null;
+    const toggle = (key, label) => (
+        <label className="flex items-center gap-1 text-xs text-[#b8d4e8]">
+            <input type="checkbox" checked={settings[key]}
+                onChange={e => save({ ...settings, [key]: e.target.checked })} />
+            {label}
+        </label>
+    );
+    return (
+        <Card className="p-4 bg-[#070f1e] border-[#1a3558]">
+            <h3 className="text-xs font-semibold text-[#5a8ab0] uppercase tracking-wider mb-3">Automazioni</h3>
+            <div className="flex gap-4 mb-3">
+                {toggle("autoAccept", "Accetta ready check")}
+                {toggle("autoBan", "Preseleziona ban")}
+                {toggle("autoHover", "Hover pick preferito")}
+            </div>
+            {(settings.autoBan || settings.autoHover) && (
+                <div className="grid grid-cols-[5rem_1fr_1fr] gap-1 items-center text-xs mb-3">
+                    <span />
+                    <span className="text-[#5a8ab0]">Ban (priorità)</span>
+                    <span className="text-[#5a8ab0]">Pick (priorità)</span>
+                    {AUTOMATION_ROLES.map(role => (
+                        <Fragment key={role}>
+                            <span className="text-[#3a6080]">{role === "DEFAULT" ? "altri" : role.toLowerCase()}</span>
+                            {["bans", "picks"].map(key => (
+                                <input key={key} defaultValue={(settings[key][role] ?? []).join(", ")}
+                                    onBlur={e => changeList(key, role, e.target.value)}
+                                    className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1" />
+                            ))}
+                        </Fragment>
+                    ))}
+                </div>
+            )}
+            {error && <p className="text-xs text-red-400 mb-2">{error}</p>}
+            {log.length > 0 && (
+                <div className="pt-3 border-t border-[#1a3558] space-y-0.5 max-h-32 overflow-y-auto">
+                    {[...log].reverse().map((a, i) => (
+                        <div key={i} className={`text-xs ${a.ok ? "text-[#5a8ab0]" : "text-red-400"}`}>
+                            {new Date(a.timestamp_ms).toLocaleTimeString("it-IT")} · {a.kind}
+                            {a.position && ` ${a.position.toLowerCase()}`}: {a.message}
+                        </div>
+                    ))}
+                </div>
+            )}
+        </Card>
+    );
+}
+
+function DraftPanel({ region }) {
+    const [draft, setDraft] = useState(null);
+
+    useEffect(() => {
+        // Aggiornato a ogni cambio della sessione (evento "lcu-draft" dal backend)
+        invoke("get_draft_analysis").then(setDraft).catch(() => {});
+        const unlisten = listen("lcu-draft", e => setDraft(e.payload));
+        return () => { unlisten.then(f => f()); };
+    }, []);
+
+    if (!draft?.in_progress) return null;
+    return (
+        <Card className="p-4 bg-[#070f1e] border-[#1a3558]">
+            <h4 className="text-[#b8d4e8] font-semibold mb-3 flex items-center gap-2">
+                <Shield className="w-4 h-4 text-[#4fc3f7]" /> Draft
+                {draft.phase && <span className="text-xs text-[#5a8ab0] font-normal">{draft.phase}</span>}
+            </h4>
+            <div className="grid grid-cols-2 gap-4">
+                <DraftTeam title="La tua squadra" team={draft.my_team} />
+                <DraftTeam title="Avversari" team={draft.their_team} />
+            </div>
+            <ScoutingPanel region={region}
+                teamKey={draft.my_team.picks.filter(p => !p.is_me).map(p => `${p.cell_id}:${p.champion_id}`).join(",")} />
+            <MatchupPanel region={region}
+                enemyKey={draft.their_team.picks.filter(p => p.champion_id).map(p => p.champion_id).join(",")} />
+        </Card>
+    );
+}
+
 // ── Main Component ────────────────────────────────────────────────────────────
-export function ChampSelectTab() {
+export function ChampSelectTab({ region = "euw" }) {
     const [status, setStatus] = useState(STATUS.IDLE);
     const [champData, setChampData] = useState(null);
     const [importResult, setImportResult] = useState(null);
-    const [runeTab, setRuneTab] = useState("opgg"); // solo tab OP.GG attivo
-    // const [runeTab, setRuneTab] = useState("opgg"); // "opgg" | "db"
-    const pollRef = useRef(null);
+    const [runeTab, setRuneTab] = useState("opgg"); // id della sorgente mostrata ("opgg", "local", ...)
     const importingRef = useRef(false);
     const lastChampRef = useRef(null);
     const runeMap = useRuneData();
@@ -258,82 +543,72 @@ export function ChampSelectTab() {
     }
 
     useEffect(() => {
-        pollRef.current = setInterval(poll, 2500);
+        // Stato iniziale, poi aggiornamenti dal websocket LCU (evento "lcu-champ-select").
+        // L'import parte dal backend al pick confermato (evento "champ-select-import"),
+        // anche con la finestra ridotta a icona: qui si mostra solo l'avanzamento.
         poll();
-        return () => clearInterval(pollRef.current);
+        invoke("get_auto_import_state").then(onImport).catch(() => {});
+        invoke("get_build_policy").then(setBuildPolicy).catch(() => {});
+        invoke("get_spell_prefs").then(setSpellPrefs).catch(() => {});
+        const unlistenSession = listen("lcu-champ-select", e => onSession(e.payload));
+        const unlistenImport = listen("champ-select-import", e => onImport(e.payload));
+        return () => {
+            unlistenSession.then(f => f());
+            unlistenImport.then(f => f());
+        };
     }, []);
 
     async function poll() {
         try {
-            const session = await invoke("get_champ_select_session");
-            if (!session || !session.in_progress) {
-                if (!importingRef.current) {
-                    setStatus(STATUS.DETECTING);
-                    if (status === STATUS.DONE || status === STATUS.ERROR) {
-                        setChampData(null); setImportResult(null);
-                        lastChampRef.current = null; importingRef.current = false;
-                    }
-                }
-                return;
-            }
-            if (!session.champion_name) return;
-            const key = `${session.champion_name}_${session.assigned_position}_${session.game_mode ?? "ranked"}`;
-            if (lastChampRef.current === key) return;
-            lastChampRef.current = key;
-            setChampData(session);
-            setStatus(STATUS.FOUND);
-            runImport(session);
+            onSession(await invoke("get_champ_select_session"));
         } catch (e) {
             if (status !== STATUS.IDLE && status !== STATUS.DETECTING) return;
             setStatus(STATUS.DETECTING);
         }
     }
 
-    async function runImport(session) {
-        if (importingRef.current) return;
-        importingRef.current = true;
+    function onSession(session) {
+        if (!session || !session.in_progress) {
+            // Champ select finita: il backend scarta anche un eventuale import in corso
+            setStatus(STATUS.DETECTING);
+            setChampData(null); setImportResult(null);
+            lastChampRef.current = null; importingRef.current = false;
+            return;
+        }
+        if (!session.champion_name || importingRef.current) return;
+        const key = `${session.champion_name}_${session.assigned_position}_${session.game_mode ?? "ranked"}`;
+        if (lastChampRef.current === key) return;
+        // Campione in hover / appena scelto: l'import arriva con "champ-select-import"
+        lastChampRef.current = key;
+        setChampData(session);
         setImportResult(null);
-        setRuneTab("opgg"); // reset al tab OP.GG (unico attivo)
-        try {
+        setStatus(STATUS.FOUND);
+    }
+
+    function onImport(event) {
+        if (!event) return;
+        const { session, result } = event;
+        lastChampRef.current = `${session.champion_name}_${session.assigned_position}_${session.game_mode ?? "ranked"}`;
+        setChampData(session);
+        if (!result) {
+            importingRef.current = true;
+            setImportResult(null);
+            setRuneTab("opgg");
             setStatus(STATUS.FETCHING);
-            const result = await invoke("auto_import_build", {
-                championName: session.champion_name,
-                assignedPosition: session.assigned_position || "DEFAULT",
-                gameMode: session.game_mode || "ranked",
-            });
-            setImportResult(result);
-            setStatus(result.errors?.length ? STATUS.ERROR : STATUS.DONE);
-        } catch (e) {
-            setStatus(STATUS.ERROR);
-            addLog(`Errore: ${e}`);
-        } finally {
-            importingRef.current = false;
+            return;
         }
+        importingRef.current = false;
+        setImportResult(result);
+        setRuneTab(result.builds?.find(b => b.imported)?.source ?? "opgg");
+        setStatus(result.errors?.length ? STATUS.ERROR : STATUS.DONE);
+        if (result.errors?.length) addLog(`Errori: ${result.errors.join("; ")}`);
     }
 
     const [applyingRunes, setApplyingRunes] = useState(false);
     const [applyRuneError, setApplyRuneError] = useState(null);
 
     async function applyRunes(tab) {
-        const runeData = tab === "opgg"
-            ? importResult?.opgg_runes
-            // : (hasDbBuild ? {
-            //     champion_name: champData?.champion_name ?? "",
-            //     primary_path_id: importResult.db_build.primary_path_id,
-            //     primary_path_name: importResult.db_build.primary_path_name,
-            //     // [keystone, slot1, slot2, slot3] — il backend padda con 0 gli slot mancanti
-            //     primary_rune_ids: [
-            //         importResult.db_build.keystone_id,
-            //         ...(importResult.db_build.primary_slot_ids ?? [])
-            //     ],
-            //     secondary_path_id: importResult.db_build.secondary_path_id,
-            //     secondary_path_name: importResult.db_build.secondary_path_name,
-            //     secondary_rune_ids: importResult.db_build.secondary_rune_ids ?? [],
-            //     stat_mod_ids: importResult?.opgg_runes?.stat_mod_ids?.length >= 3
-            //         ? importResult.opgg_runes.stat_mod_ids
-            //         : [5008, 5008, 5002],
-            // } : null);
-            : null;
+        const runeData = importResult?.builds?.find(b => b.source === tab)?.runes;
 
         if (!runeData) return;
         setApplyingRunes(true);
@@ -341,13 +616,84 @@ export function ChampSelectTab() {
         try {
             await invoke("apply_rune_page", { runeData });
         } catch (e) {
-            console.error("[applyRunes] errore:", e);
-            setApplyRuneError(String(e));
+            // Limite pagine e nessuna pagina RLP da riciclare: la pagina dell'utente
+            // si sostituisce solo su conferma esplicita (il backend fa prima un backup)
+            if (String(e).startsWith("RUNE_PAGE_LIMIT") &&
+                window.confirm("Limite pagine rune raggiunto. Sostituire la pagina attiva? Verrà salvato un backup.")) {
+                try {
+                    await invoke("apply_rune_page", { runeData, replaceActive: true });
+                } catch (e2) {
+                    console.error("[applyRunes] errore:", e2);
+                    setApplyRuneError(String(e2));
+                }
+            } else {
+                console.error("[applyRunes] errore:", e);
+                setApplyRuneError(String(e));
+            }
         } finally {
             setApplyingRunes(false);
         }
     }
 
+    // ── Sorgente build preferita ─────────────────────────────────────────────
+    const [buildPolicy, setBuildPolicy] = useState(null);
+
+    async function changePrimarySource(primary) {
+        const policy = { ...buildPolicy.policy, primary };
+        try {
+            await invoke("set_build_policy", { policy });
+            setBuildPolicy({ ...buildPolicy, policy });
+        } catch (e) {
+            setPagesMessage(`Sorgente non salvata: ${e}`);
+        }
+    }
+
+    // ── Preferenza Flash D/F ─────────────────────────────────────────────────
+    const [spellPrefs, setSpellPrefs] = useState(null);
+
+    async function changeFlashKey(flashKey) {
+        const prefs = { ...spellPrefs, flashKey };
+        try {
+            await invoke("set_spell_prefs", { prefs });
+            setSpellPrefs(prefs);
+        } catch (e) {
+            setPagesMessage(`Preferenza spell non salvata: ${e}`);
+        }
+    }
+
+    // ── Backup / restore pagine rune ─────────────────────────────────────────
+    const [pagesMessage, setPagesMessage] = useState(null);
+
+    async function backupPages() {
+        try {
+            const info = await invoke("backup_rune_pages");
+            setPagesMessage(`Backup di ${info.pages} pagine salvato`);
+        } catch (e) {
+            setPagesMessage(`Backup fallito: ${e}`);
+        }
+    }
+
+    async function restorePages() {
+        try {
+            const r = await invoke("restore_rune_pages");
+            setPagesMessage(`Ripristinate ${r.restored} pagine` +
+                (r.skipped ? `, ${r.skipped} già presenti` : "") +
+                (r.no_space ? `, ${r.no_space} senza spazio` : ""));
+        } catch (e) {
+            setPagesMessage(`Ripristino fallito: ${e}`);
+        }
+    }
+
+    async function removeItemSets() {
+        if (!window.confirm("Rimuovere tutti gli item set scritti da RLP?")) return;
+        try {
+            const removed = await invoke("remove_all_item_sets");
+            setPagesMessage(`Rimossi ${removed} item set RLP`);
+        } catch (e) {
+            setPagesMessage(`Rimozione item set fallita: ${e}`);
+        }
+    }
+
     const isActive = (s) => status === s;
     const isDone = (s) => {
         const order = [STATUS.DETECTING, STATUS.FOUND, STATUS.FETCHING, STATUS.IMPORTING, STATUS.DONE];
@@ -355,31 +701,10 @@ export function ChampSelectTab() {
     };
     const isError = (s) => status === STATUS.ERROR && s === STATUS.IMPORTING;
 
-    const hasDbBuild = false; // !!importResult?.db_build;
-
-    // Shape DB rune data to match RuneDisplay interface
-    // const dbRuneData = hasDbBuild ? {
-    //     champion_name: champData?.champion_name ?? "",
-    //     primary_path_id: importResult.db_build.primary_path_id,
-    //     primary_path_name: importResult.db_build.primary_path_name,
-    //     // Ricostruisce primary_rune_ids = [keystone, slot1, slot2, slot3]
-    //     // Gli 0 indicano slot senza dati sufficienti nel DB (verranno mostrati come placeholder)
-    //     primary_rune_ids: [
-    //         importResult.db_build.keystone_id,
-    //         ...(importResult.db_build.primary_slot_ids ?? [0, 0, 0])
-    //     ],
-    //     secondary_path_id: importResult.db_build.secondary_path_id,
-    //     secondary_path_name: importResult.db_build.secondary_path_name,
-    //     secondary_rune_ids: importResult.db_build.secondary_rune_ids ?? [],
-    //     // Default frammenti: Adaptive Force, Adaptive Force, Armor (stesso default del backend)
-    //     // Frammenti: presi da OP.GG se disponibili, altrimenti default Adaptive/Adaptive/Armor
-    //     stat_mod_ids: importResult?.opgg_runes?.stat_mod_ids?.length >= 3
-    //         ? importResult.opgg_runes.stat_mod_ids
-    //         : [5008, 5008, 5002],
-    // } : null;
-    const dbRuneData = null;
-
-    const activeRuneData = runeTab === "opgg" ? importResult?.opgg_runes : dbRuneData;
+    // Sorgente mostrata nel pannello rune (le pagine delle altre si applicano al cambio tab)
+    const builds = importResult?.builds ?? [];
+    const activeBuild = builds.find(b => b.source === runeTab);
+    const activeRuneData = activeBuild?.runes ?? null;
     const accentColor = RUNE_PATH_COLORS[Number(activeRuneData?.primary_path_id)] ?? "#4fc3f7";
 
     return (
@@ -430,6 +755,10 @@ export function ChampSelectTab() {
                 </div>
             </Card>
 
+            <DraftPanel region={region} />
+
+            <AutomationPanel />
+
             {/* ── Steps ── */}
             <Card className="p-4 bg-[#070f1e] border-[#1a3558]">
                 <h3 className="text-xs font-semibold text-[#5a8ab0] uppercase tracking-wider mb-3">
@@ -447,6 +776,46 @@ export function ChampSelectTab() {
                     <StepRow icon={CheckCircle} label="Build attiva nel client"
                         active={false} done={status === STATUS.DONE} />
                 </div>
+                {buildPolicy && (
+                    <div className="mt-3 pt-3 border-t border-[#1a3558] flex items-center gap-2">
+                        <span className="text-xs text-[#5a8ab0]">Sorgente build</span>
+                        <select value={buildPolicy.policy.primary}
+                            onChange={e => changePrimarySource(e.target.value)}
+                            className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1">
+                            {buildPolicy.sources.map(src => (
+                                <option key={src.id} value={src.id}>{src.label}</option>
+                            ))}
+                        </select>
+                    </div>
+                )}
+                {spellPrefs && (
+                    <div className="mt-3 pt-3 border-t border-[#1a3558] flex items-center gap-2">
+                        <span className="text-xs text-[#5a8ab0]">Flash su</span>
+                        <select value={spellPrefs.flashKey}
+                            onChange={e => changeFlashKey(e.target.value)}
+                            className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1">
+                            <option value="auto">Come nel client</option>
+                            <option value="d">D</option>
+                            <option value="f">F</option>
+                        </select>
+                    </div>
+                )}
+                <div className="mt-3 pt-3 border-t border-[#1a3558] flex items-center gap-2">
+                    <span className="text-xs text-[#5a8ab0]">Pagine rune</span>
+                    <button onClick={backupPages}
+                        className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1 hover:bg-[#0a1e4a]">
+                        Backup
+                    </button>
+                    <button onClick={restorePages}
+                        className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1 hover:bg-[#0a1e4a]">
+                        Ripristina
+                    </button>
+                    <button onClick={removeItemSets}
+                        className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1 hover:bg-[#0a1e4a]">
+                        Rimuovi item set RLP
+                    </button>
+                    {pagesMessage && <span className="text-xs text-[#7dd8ff] ml-auto">{pagesMessage}</span>}
+                </div>
             </Card>
 
             {/* ── Result cards ── */}
@@ -472,8 +841,15 @@ export function ChampSelectTab() {
                                         </div>
                                     ))}
                                 </div>
+                            ) : importResult.summoners_skipped ? (
+                                <p className="text-[#3a6080] text-sm">Saltate: {importResult.summoners_skipped}</p>
                             ) : (
-                                <p className="text-[#3a6080] text-sm">Modalità non-ranked, skip</p>
+                                <p className="text-red-400 text-sm">✗ Spell non importate</p>
+                            )}
+                            {importResult.spell_fixes?.length > 0 && (
+                                <p className="text-xs text-[#5a8ab0] mt-2">
+                                    Corrette: {importResult.spell_fixes.join(", ")}
+                                </p>
                             )}
                         </Card>
 
@@ -484,18 +860,28 @@ export function ChampSelectTab() {
                             <div className="space-y-1.5">
                                 <p className={`text-sm flex items-center gap-1.5 ${importResult.items_imported ? "text-green-400" : "text-red-400"}`}>
                                     {importResult.items_imported
-                                        ? <><CheckCircle className="w-3.5 h-3.5" /> OP.GG importato</>
-                                        : <>✗ OP.GG non importato</>
+                                        ? <><CheckCircle className="w-3.5 h-3.5" /> {importResult.build_source} importato</>
+                                        : <>✗ Item set non importato</>
                                     }
                                 </p>
-                                {/* {hasDbBuild && (
-                                    <p className={`text-sm flex items-center gap-1.5 ${importResult.db_build.items_imported ? "text-purple-400" : "text-[#5a5080]"}`}>
-                                        {importResult.db_build.items_imported
-                                            ? <><Database className="w-3.5 h-3.5" /> Dataset DB importato</>
-                                            : <><Database className="w-3.5 h-3.5 opacity-50" /> DB non disponibile</>
-                                        }
+                                {importResult.build_mode && (
+                                    <p className="text-xs text-[#5a8ab0]">
+                                        {importResult.build_source} · build {importResult.build_mode}
+                                        {champData?.game_mode && importResult.build_mode !== champData.game_mode && " (ripiego)"}
+                                    </p>
+                                )}
+                                {importResult.item_set_target && (
+                                    <p className="text-xs text-[#5a8ab0]">
+                                        {importResult.item_set_target === "lcu"
+                                            ? "Salvato sull'account"
+                                            : "Salvato in Config/ (client non raggiungibile)"}
                                     </p>
-                                )} */}
+                                )}
+                                {importResult.augments?.length > 0 && (
+                                    <p className="text-xs text-[#b8d4e8]">
+                                        Augment: {importResult.augments.join(", ")}
+                                    </p>
+                                )}
                             </div>
                         </Card>
                     </div>
@@ -505,36 +891,28 @@ export function ChampSelectTab() {
 
                         {/* Tab switcher */}
                         <div className="flex border-b border-[#1a3558] bg-[#050d1a]">
-                            <button
-                                onClick={() => { setRuneTab("opgg"); applyRunes("opgg"); }}
-                                className={`flex items-center gap-2 px-5 py-3.5 text-sm font-semibold transition-all border-b-2 -mb-px focus:outline-none
-                                    ${runeTab === "opgg"
-                                        ? "border-[#4fc3f7] text-[#4fc3f7] bg-[#0d1f38]/30"
-                                        : "border-transparent text-[#5a8ab0] hover:text-[#8ab0cc] hover:bg-[#0d1f38]/20"}`}
-                            >
-                                <TrendingUp className="w-3.5 h-3.5" />
-                                OP.GG
-                            </button>
-
-                            {/* Tab DB disabilitato — db_build commentato
-                            <button
-                                onClick={() => { if (hasDbBuild) { setRuneTab("db"); applyRunes("db"); } }}
-                                className={`flex items-center gap-2 px-5 py-3.5 text-sm font-semibold transition-all border-b-2 -mb-px focus:outline-none
-                                    ${!hasDbBuild
-                                        ? "border-transparent text-[#2a4060] cursor-not-allowed"
-                                        : runeTab === "db"
-                                            ? "border-purple-400 text-purple-300 bg-[#0d1f38]/30"
-                                            : "border-transparent text-[#5a8ab0] hover:text-purple-300 hover:bg-[#0d1f38]/20"}`}
-                            >
-                                <Database className="w-3.5 h-3.5" />
-                                Dataset RLP
-                                {hasDbBuild && (
-                                    <span className="text-xs bg-purple-900/40 text-purple-300 border border-purple-800/50 rounded px-1.5 py-0.5 font-normal">
-                                        {importResult.db_build.sample_size.toLocaleString()} partite
-                                    </span>
-                                )}
-                            </button>
-                            */}
+                            {builds.map(b => (
+                                <button
+                                    key={b.source}
+                                    onClick={() => { if (b.runes) { setRuneTab(b.source); applyRunes(b.source); } }}
+                                    className={`flex items-center gap-2 px-5 py-3.5 text-sm font-semibold transition-all border-b-2 -mb-px focus:outline-none
+                                        ${!b.runes
+                                            ? "border-transparent text-[#2a4060] cursor-not-allowed"
+                                            : runeTab === b.source
+                                                ? "border-[#4fc3f7] text-[#4fc3f7] bg-[#0d1f38]/30"
+                                                : "border-transparent text-[#5a8ab0] hover:text-[#8ab0cc] hover:bg-[#0d1f38]/20"}`}
+                                    title={b.error ?? undefined}
+                                >
+                                    {b.source === "opgg" ? <TrendingUp className="w-3.5 h-3.5" /> : <Database className="w-3.5 h-3.5" />}
+                                    {b.label}
+                                    {b.sample_size != null && (
+                                        <span className="text-xs bg-purple-900/40 text-purple-300 border border-purple-800/50 rounded px-1.5 py-0.5 font-normal">
+                                            {b.sample_size.toLocaleString()} partite
+                                            {b.win_rate != null && ` · ${Math.round(b.win_rate * 100)}% WR`}
+                                        </span>
+                                    )}
+                                </button>
+                            ))}
                         </div>
 
                         {/* Content */}
@@ -574,12 +952,11 @@ export function ChampSelectTab() {
                                             <span className="text-xs text-red-300 bg-red-900/30 border border-red-700/50 rounded px-2 py-1" title={applyRuneError}>
                                                 ✗ Errore applicazione
                                             </span>
-                                        ) : runeTab === "opgg" ? (
+                                        ) : activeBuild?.imported ? (
                                             <span className="text-xs text-[#4fc3f7] bg-[#0a1e4a]/60 border border-[#1459d4]/50 rounded px-2 py-1">
                                                 Importata nel client ✓
                                             </span>
                                         ) : (
-                                            // Tab DB disabilitato
                                             <span className="text-xs text-green-300 bg-green-900/30 border border-green-700/50 rounded px-2 py-1">
                                                 Applicata nel client ✓
                                             </span>
@@ -590,19 +967,43 @@ export function ChampSelectTab() {
 
                             <RuneDisplay runeData={activeRuneData} runeMap={runeMap} />
 
-                            {!activeRuneData && runeTab === "opgg" && (
-                                <p className="text-[#3a6080] text-sm py-4">Rune OP.GG non disponibili.</p>
-                            )}
-                            {/* Tab DB disabilitato
-                            {!activeRuneData && runeTab === "db" && (
-                                <div className="py-4 text-center">
-                                    <p className="text-[#3a6080] text-sm">Nessun dato per questo campione/lane.</p>
-                                    <p className="text-[#2a5060] text-xs mt-1">Sample insufficiente (min. 5 partite).</p>
-                                </div>
+                            {!activeRuneData && (
+                                <p className="text-[#3a6080] text-sm py-4">
+                                    {activeBuild?.error ?? `Rune ${activeBuild?.label ?? ""} non disponibili.`}
+                                </p>
                             )}
-                            */}
                         </div>
                     </Card>
+
+                    {/* ── Abilità livello per livello ── */}
+                    {activeBuild?.skill_plan && (
+                        <Card className="p-4 bg-[#070f1e] border-[#1a3558]">
+                            <h4 className="text-[#b8d4e8] font-semibold mb-3 flex items-center gap-2">
+                                <TrendingUp className="w-4 h-4 text-[#4fc3f7]" /> Abilità
+                                {activeBuild.skill_plan.max_order && (
+                                    <span className="text-xs text-[#5a8ab0] font-normal">
+                                        max {activeBuild.skill_plan.max_order}
+                                    </span>
+                                )}
+                            </h4>
+                            <div className="flex flex-wrap gap-1">
+                                {activeBuild.skill_plan.levels.map(l => (
+                                    <div key={l.level} className="text-center w-7">
+                                        <p className="text-[10px] text-[#3a6080]">{l.level}</p>
+                                        <p className={`text-xs font-semibold rounded border py-0.5 ${
+                                            l.skill === "R"
+                                                ? "text-yellow-300 border-yellow-500/40"
+                                                : l.level <= 3
+                                                    ? "text-[#7dd8ff] border-[#1459d4]/60"
+                                                    : "text-[#b8d4e8] border-[#1a3558]"
+                                        }`}>
+                                            {l.skill}
+                                        </p>
+                                    </div>
+                                ))}
+                            </div>
+                        </Card>
+                    )}
                 </>
             )}
         </div>
@@ -1,8 +1,10 @@
 import { useEffect, useState, useRef, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Card } from "./ui/card";
 import { Shield, Swords, Loader2, WifiOff, Wifi, Clock } from "lucide-react";
 import { PATCH } from "./constants";
+import { getChampionMap } from "./utils";
 
 // ── Costanti ──────────────────────────────────────────────────────────────────
 
@@ -101,18 +103,7 @@ function calcGameTime(data, elapsed) {
 function useChampIdMap() {
     const [map, setMap] = useState({});
     useEffect(() => {
-        fetch(`https://ddragon.leagueoflegends.com/cdn/${PATCH}/data/en_US/champion.json`)
-            .then(r => r.json())
-            .then(json => {
-                const m = {};
-                if (json?.data) {
-                    for (const [name, info] of Object.entries(json.data)) {
-                        m[parseInt(info.key)] = name;
-                    }
-                }
-                setMap(m);
-            })
-            .catch(() => { });
+        getChampionMap().then(m => setMap(m.byId));
     }, []);
     return map;
 }
@@ -361,11 +352,20 @@ export function LiveGameTab({ puuidOverride = null, myPuuid = null, region = "eu
         clearInterval(tickRef.current);
 
         fetchData();
-        pollRef.current = setInterval(fetchData, 30000);
+        // Partita propria: basta il gameflow dal websocket LCU. Altri giocatori: polling Spectator
+        let unlisten = null;
+        if (isSelf) {
+            unlisten = listen("lcu-gameflow-phase", e => {
+                if (["InProgress", "EndOfGame", "None", "Lobby"].includes(e.payload?.phase)) fetchData();
+            });
+        } else {
+            pollRef.current = setInterval(fetchData, 30000);
+        }
 
         return () => {
             clearInterval(pollRef.current);
             clearInterval(tickRef.current);
+            unlisten?.then(f => f());
         };
     }, [fetchData]); // fetchData cambia quando cambia puuidOverride
 
@@ -1,4 +1,5 @@
 // Shared utilities — used by both MatchHistoryTab and ChampionMetaTab
+import { invoke } from "@tauri-apps/api/core";
 
 /**
  * Estrae i dati del giocatore corrente da un match (struttura Riot API o flat OP.GG).
@@ -31,11 +32,21 @@ export function resolveMe(match, myPuuid, mySummonerName) {
 /** Inizio Season 2026 in ms (8 gennaio 2026 00:00:00 UTC) */
 export const SEASON_2026_START_MS = 1736294400000;
 
-// ── champion.json DDragon — singleton fetch condiviso tra tutti i tab ─────────
-// Una sola HTTP request per tutta la sessione app, indipendentemente da quanti
-// componenti chiamano getChampionMap().
+// ── Mappa campioni — singleton condiviso tra tutti i tab ─────────────────────
+// Arriva dal backend (get_champion_map: file Data Dragon su disco, funziona anche
+// offline); se il comando fallisce si ripiega sul CDN. Una sola richiesta per sessione.
 let _champMapPromise = null;
 
+function buildChampionMap(entries) {
+    const byKey = {};
+    const byId = {};
+    for (const [key, name] of entries) {
+        byKey[String(key)] = name;
+        byId[parseInt(key)] = name;
+    }
+    return { byKey, byId };
+}
+
 /**
  * Ritorna una Promise<{byKey, byId}> risolta una sola volta per sessione.
  *   byKey: { "157": "Yasuo", ... }  — chiave numerica come stringa (masteries)
@@ -44,21 +55,11 @@ let _champMapPromise = null;
 export function getChampionMap() {
     if (!_champMapPromise) {
         const PATCH_VAL = "16.4.1";
-        _champMapPromise = fetch(
-            `https://ddragon.leagueoflegends.com/cdn/${PATCH_VAL}/data/en_US/champion.json`
-        )
-            .then(r => r.json())
-            .then(json => {
-                const byKey = {};
-                const byId = {};
-                if (json?.data) {
-                    for (const [name, info] of Object.entries(json.data)) {
-                        byKey[String(info.key)] = name;
-                        byId[parseInt(info.key)] = name;
-                    }
-                }
-                return { byKey, byId };
-            })
+        _champMapPromise = invoke("get_champion_map")
+            .then(res => buildChampionMap(Object.entries(res?.champions ?? {}).map(([key, c]) => [key, c.id])))
+            .catch(() => fetch(`https://ddragon.leagueoflegends.com/cdn/${PATCH_VAL}/data/en_US/champion.json`)
+                .then(r => r.json())
+                .then(json => buildChampionMap(Object.entries(json?.data ?? {}).map(([name, info]) => [info.key, name]))))
             .catch(() => ({ byKey: {}, byId: {} }));
     }
     return _champMapPromise;